@@ -0,0 +1,140 @@
+//! Builds the opcode tables in `src/opcodes.rs` from `src/opcodes.csv`, so that fixing a cycle or
+//! byte count is a one line change to the data.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+struct Row {
+    code: u8,
+    instruction: String,
+    mode: String,
+    bytes: u8,
+    cycles: i8,
+    unofficial: bool,
+}
+
+fn expected_bytes(mode: &str, instruction: &str) -> u8 {
+    match mode {
+        // BRK is followed by a padding byte that the return address skips.
+        "Implied" if instruction == "BRK" => 2,
+        "Implied" | "Accumulator" => 1,
+        "Immediate" | "ZeroPage" | "ZeroPageX" | "ZeroPageY" | "IndirectX" | "IndirectY"
+        | "Relative" => 2,
+        "Absolute" | "AbsoluteX" | "AbsoluteY" | "Indirect" => 3,
+        _ => panic!("Unknown addressing mode {}", mode),
+    }
+}
+
+fn parse(csv: &str) -> Vec<Row> {
+    let mut rows: Vec<Row> = vec![];
+
+    for (number, line) in csv.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [code, instruction, mode, bytes, cycles] = fields[..] else {
+            panic!("opcodes.csv line {}: expected 5 fields", number + 1);
+        };
+
+        let (instruction, unofficial) = match instruction.strip_prefix('*') {
+            Some(instruction) => (instruction, true),
+            None => (instruction, false),
+        };
+
+        let row = Row {
+            code: u8::from_str_radix(code, 16).expect("Invalid opcode"),
+            instruction: instruction.to_string(),
+            mode: mode.to_string(),
+            bytes: bytes.parse().expect("Invalid byte count"),
+            cycles: cycles.parse().expect("Invalid cycle count"),
+            unofficial,
+        };
+
+        assert!(
+            rows.iter().all(|other| other.code != row.code),
+            "opcodes.csv line {}: {:02x} is listed twice",
+            number + 1,
+            row.code
+        );
+        assert_eq!(
+            row.bytes,
+            expected_bytes(&row.mode, &row.instruction),
+            "opcodes.csv line {}: wrong byte count for {} {}",
+            number + 1,
+            row.instruction,
+            row.mode
+        );
+
+        rows.push(row);
+    }
+
+    rows.sort_by_key(|row| row.code);
+    rows
+}
+
+fn generate(rows: &[Row]) -> String {
+    let mut code = String::new();
+
+    code.push_str("#[derive(Debug, Clone, Copy, PartialEq)]\npub enum OpCode {\n");
+    for row in rows {
+        writeln!(code, "    X{:02x},", row.code).unwrap();
+    }
+    code.push_str("}\n\n");
+
+    writeln!(
+        code,
+        "/// Every opcode in the table, in order.\npub const OPCODES: [u8; {}] = [{}];\n",
+        rows.len(),
+        rows.iter()
+            .map(|row| format!("0x{:02x}", row.code))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+    .unwrap();
+
+    code.push_str("impl OpCode {\n");
+    code.push_str("    pub fn from_code(code: &u8) -> Result<OpCode, NesError> {\n");
+    code.push_str("        let opcode = match code {\n");
+    for row in rows {
+        writeln!(code, "            0x{0:02x} => OpCode::X{0:02x},", row.code).unwrap();
+    }
+    code.push_str("            _ => {\n");
+    code.push_str(
+        "                return Err(NesError::new(&format!(\"Unknown OpCode: {}\", code)));\n",
+    );
+    code.push_str("            }\n        };\n\n        Ok(opcode)\n    }\n}\n\n");
+
+    code.push_str("impl OpCodeDetail {\n");
+    code.push_str("    pub fn from_opcode(opcode: &OpCode) -> Self {\n");
+    code.push_str("        match opcode {\n");
+    for row in rows {
+        writeln!(
+            code,
+            "            OpCode::X{:02x} => OpCodeDetail {{ instruction: Instruction::{}, bytes: {}, cycles: {}, address_mode: AddressingMode::{}, unofficial: {} }},",
+            row.code, row.instruction, row.bytes, row.cycles, row.mode, row.unofficial
+        )
+        .unwrap();
+    }
+    code.push_str("        }\n    }\n}\n");
+
+    code
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/opcodes.csv");
+
+    let csv = fs::read_to_string("src/opcodes.csv").expect("Could not read src/opcodes.csv");
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    fs::write(
+        Path::new(&out_dir).join("opcodes.rs"),
+        generate(&parse(&csv)),
+    )
+    .expect("Could not write the opcode table");
+}
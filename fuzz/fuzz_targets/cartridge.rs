@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nes_emulator::cartridge::Cartridge;
+
+// Any bytes at all should either load or be rejected with an error, never panic.
+fuzz_target!(|data: &[u8]| {
+    let _ = Cartridge::new(data);
+});
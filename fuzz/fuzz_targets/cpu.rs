@@ -0,0 +1,38 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nes_emulator::bus::CpuBus;
+use nes_emulator::cartridge::{Cartridge, NES_TAG, PRG_ROM_PAGE_SIZE};
+use nes_emulator::cpu::CPU;
+
+const MAX_INSTRUCTIONS: usize = 10_000;
+
+// Run the fuzzer's bytes as a single page NROM program starting at $8000. Illegal opcodes and bad
+// addresses are fine as long as they come back as errors rather than panics.
+fuzz_target!(|data: &[u8]| {
+    let mut prg_rom = vec![0; PRG_ROM_PAGE_SIZE];
+    let length = data.len().min(PRG_ROM_PAGE_SIZE - 6);
+    prg_rom[..length].copy_from_slice(&data[..length]);
+
+    // Reset vector pointing at the start of the program.
+    prg_rom[PRG_ROM_PAGE_SIZE - 4] = 0x00;
+    prg_rom[PRG_ROM_PAGE_SIZE - 3] = 0x80;
+
+    let mut raw = NES_TAG.to_vec();
+    raw.extend([0x01, 0x00, 0x00, 0x00]);
+    raw.extend([0; 8]);
+    raw.extend(prg_rom);
+
+    let cartridge = Cartridge::new(&raw).expect("Fuzz cartridge should always load");
+    let mut cpu = CPU::new(CpuBus::new(cartridge));
+
+    if cpu.reset().is_err() {
+        return;
+    }
+
+    for _ in 0..MAX_INSTRUCTIONS {
+        if cpu.step().is_err() {
+            return;
+        }
+    }
+});
@@ -1,65 +1,613 @@
-use crate::cartridge::Cartridge;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::cartridge::{Cartridge, ConsoleType};
+use crate::cpu::bus::Bus;
+use crate::cpu::interrupts::{IrqLine, IrqSource};
+use crate::cpu::WatchHit;
+use crate::debugger::mmio::{Access, MmioLogger};
+use crate::debugger::sram::SramListener;
 use crate::errors::NesError;
+use crate::joypad::expansion::ExpansionDevice;
+use crate::joypad::vs_system::{VsSystemInputs, VS_4016_BITS, VS_4017_BITS};
+use crate::joypad::{ControllerDevice, Joypad};
 use crate::memory::{Mem, RAM};
+use crate::registers::{ApuRegister, PpuRegister};
 
 const CPU_RAM_START: u16 = 0x0000;
 const CPU_MEMORY_END: u16 = 0x1fff;
 const PPU_RAM_START: u16 = 0x2000;
 const PPU_MEMORY_END: u16 = 0x3fff;
+const PRG_RAM_START: u16 = 0x6000;
+const PRG_RAM_END: u16 = 0x7fff;
 const CARTRIDGE_ROM_START: u16 = 0x8000;
 const CARTRIDGE_ROM_END: u16 = 0xffff;
 
+/// Only these bits of a controller read are driven by the controller; the rest keep whatever was
+/// last on the data bus.
+const JOYPAD_OPEN_BUS_MASK: u8 = 0b1110_0000;
+
+/// The address an access really goes to once mirroring is undone: internal RAM repeats every 2KB
+/// and the PPU registers every 8 bytes. Anything watching or freezing addresses goes through
+/// this so it agrees with the bus.
+pub fn canonical_address(address: u16) -> u16 {
+    match address {
+        CPU_RAM_START..=CPU_MEMORY_END => address & 0x07ff,
+        PPU_RAM_START..=PPU_MEMORY_END => address & 0x2007,
+        _ => address,
+    }
+}
+
+/// Roughly what is left on the data bus when nothing drives it: for absolute addressing the last
+/// byte fetched was the high byte of the address.
+fn open_bus(address: u16) -> u8 {
+    (address >> 8) as u8
+}
+
+/// What the bus does with an access it can't handle, like a read from unmapped memory or a write
+/// to ROM.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorPolicy {
+    /// Return the error, which stops the CPU.
+    Stop,
+    /// Record a `BusFault` and carry on: reads see open bus and writes are dropped, as on hardware.
+    /// Lets slightly off games stay playable.
+    Continue,
+}
+
+/// Why the bus couldn't handle an access.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultKind {
+    /// The PPU registers, as there's no PPU yet.
+    PpuRegister,
+    /// An address in $4000-$5FFF that nothing answers.
+    OutOfRange,
+    /// A write to the ROM of a board without any registers.
+    RomWrite,
+}
+
+impl FaultKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            FaultKind::PpuRegister => "PPU not implemented yet.",
+            FaultKind::OutOfRange => "Address out of range",
+            FaultKind::RomWrite => "Writing to cartridge ROM",
+        }
+    }
+
+    /// The error an access returns under `ErrorPolicy::Stop`.
+    fn error(&self, address: u16, access: Access) -> NesError {
+        match (self, access) {
+            (FaultKind::OutOfRange, Access::Read) => {
+                NesError::new(&format!("Reading to address out of range {}", address))
+            }
+            (FaultKind::OutOfRange, Access::Write) => {
+                NesError::new(&format!("Writing to address out of range {}", address))
+            }
+            _ => NesError::new(self.message()),
+        }
+    }
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// An access the bus couldn't handle while running with `ErrorPolicy::Continue`. Recording one
+/// doesn't allocate, so a game that keeps faulting doesn't slow down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BusFault {
+    pub address: u16,
+    pub access: Access,
+    pub kind: FaultKind,
+}
+
+/// A write that used a part of the mapper that isn't emulated, so the game may not behave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsupportedFeature {
+    pub address: u16,
+    pub feature: &'static str,
+}
+
+/// Where the bus sends an access to a CPU address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryRegion {
+    /// The 2KB of internal RAM or one of its mirrors.
+    InternalRam,
+    PpuRegister(PpuRegister),
+    ApuIo(ApuRegister),
+    PrgRam,
+    PrgRom {
+        bank: usize,
+    },
+    /// Nothing answers here, so reads see whatever was last on the data bus.
+    OpenBus,
+}
+
+/// How many faults, and separately unsupported feature uses, are kept before the oldest are
+/// dropped.
+pub const FAULT_LOG_CAPACITY: usize = 1024;
+
+/// Cloning a bus copies its memory, devices, frozen and watched addresses and error policy but not
+/// its SRAM listeners, MMIO logger, faults or unsupported feature uses. Two buses are equal when
+/// their memory, expansion device and Vs. System inputs are; controllers can't be compared and
+/// are left out.
 pub struct CpuBus {
     cpu_ram: RAM,
-    cartridge: Cartridge,
+    pub(crate) cartridge: Cartridge,
+    controllers: [Box<dyn ControllerDevice>; 2],
+    /// Whatever is plugged into the Famicom expansion port, if anything.
+    pub expansion: Option<ExpansionDevice>,
+    /// The coin slots and DIP switches, for a Vs. System cartridge.
+    pub vs_system: Option<VsSystemInputs>,
+    pub(crate) sram_listeners: Vec<SramListener>,
+    pub(crate) mmio_logger: Option<MmioLogger>,
+    /// Addresses locked to a value, see `freeze`.
+    pub(crate) frozen: Vec<(u16, u8)>,
+    /// Canonical addresses that stop the CPU when written, see `watch_writes`.
+    pub(crate) write_watches: Vec<u16>,
+    pub(crate) watch_hit: Option<WatchHit>,
+    nmi_pending: bool,
+    /// The CPU cycle the cartridge has been clocked up to.
+    clocked_cycle: u64,
+    /// The IRQ line, for the APU and mappers to hold.
+    pub irq: IrqLine,
+    pub error_policy: ErrorPolicy,
+    faults: RefCell<VecDeque<BusFault>>,
+    unsupported_features: RefCell<VecDeque<UnsupportedFeature>>,
+}
+
+impl Clone for CpuBus {
+    fn clone(&self) -> Self {
+        CpuBus {
+            cpu_ram: self.cpu_ram.clone(),
+            cartridge: self.cartridge.clone(),
+            controllers: self.controllers.clone(),
+            expansion: self.expansion.clone(),
+            vs_system: self.vs_system,
+            sram_listeners: vec![],
+            mmio_logger: None,
+            frozen: self.frozen.clone(),
+            write_watches: self.write_watches.clone(),
+            watch_hit: None,
+            nmi_pending: self.nmi_pending,
+            clocked_cycle: self.clocked_cycle,
+            irq: self.irq,
+            error_policy: self.error_policy,
+            faults: RefCell::new(VecDeque::with_capacity(FAULT_LOG_CAPACITY)),
+            unsupported_features: RefCell::new(VecDeque::with_capacity(FAULT_LOG_CAPACITY)),
+        }
+    }
+}
+
+impl PartialEq for CpuBus {
+    fn eq(&self, other: &Self) -> bool {
+        self.cpu_ram == other.cpu_ram
+            && self.cartridge == other.cartridge
+            && self.expansion == other.expansion
+            && self.vs_system == other.vs_system
+    }
+}
+
+impl fmt::Debug for CpuBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CpuBus")
+            .field("cpu_ram", &self.cpu_ram)
+            .field("cartridge", &self.cartridge)
+            .field("controllers", &self.controllers)
+            .field("expansion", &self.expansion)
+            .field("vs_system", &self.vs_system)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Mem for CpuBus {
     fn mem_write(&mut self, address: u16, data: u8) -> Result<(), NesError> {
+        let data = self.frozen_value(address).unwrap_or(data);
+
+        if let Some(logger) = &self.mmio_logger {
+            logger.log(address, data, Access::Write);
+        }
+
+        self.check_write_watch(address, data);
+
+        match self.write(address, data) {
+            Ok(()) => Ok(()),
+            Err(kind) if self.error_policy == ErrorPolicy::Continue => {
+                self.record_fault(address, Access::Write, kind);
+                Ok(())
+            }
+            Err(kind) => Err(kind.error(address, Access::Write)),
+        }
+    }
+
+    fn mem_read(&self, address: u16) -> Result<u8, NesError> {
+        let value = match self.read(address, false) {
+            Ok(value) => value,
+            Err(kind) if self.error_policy == ErrorPolicy::Continue => {
+                self.record_fault(address, Access::Read, kind);
+                open_bus(address)
+            }
+            Err(kind) => return Err(kind.error(address, Access::Read)),
+        };
+
+        if let Some(logger) = &self.mmio_logger {
+            logger.log(address, value, Access::Read);
+        }
+
+        Ok(value)
+    }
+
+    fn mem_peek(&self, address: u16) -> Result<u8, NesError> {
+        match self.read(address, true) {
+            Ok(value) => Ok(value),
+            Err(_) if self.error_policy == ErrorPolicy::Continue => Ok(open_bus(address)),
+            Err(kind) => Err(kind.error(address, Access::Read)),
+        }
+    }
+}
+
+impl Bus for CpuBus {
+    fn take_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi_pending)
+    }
+
+    fn irq_source(&self) -> Option<IrqSource> {
+        self.irq.source()
+    }
+
+    fn take_watch_hit(&mut self) -> Option<WatchHit> {
+        self.watch_hit.take()
+    }
+
+    fn begin_instruction(&mut self, program_counter: u16, instruction: u64, cycle: u64) {
+        // The count goes backwards when a save state is loaded, which runs no cycles
+        self.cartridge
+            .clock(cycle.saturating_sub(self.clocked_cycle));
+        self.clocked_cycle = cycle;
+
+        if let Some(logger) = &mut self.mmio_logger {
+            logger.begin_instruction(program_counter, instruction, cycle);
+        }
+    }
+}
+
+impl CpuBus {
+    pub fn new(cartridge: Cartridge) -> Self {
+        let vs_system = match cartridge.console_type {
+            ConsoleType::VsSystem { .. } => Some(VsSystemInputs::new()),
+            _ => None,
+        };
+
+        CpuBus {
+            cpu_ram: RAM::new(2048),
+            cartridge,
+            controllers: [Box::new(Joypad::new()), Box::new(Joypad::new())],
+            expansion: None,
+            vs_system,
+            sram_listeners: vec![],
+            mmio_logger: None,
+            frozen: vec![],
+            write_watches: vec![],
+            watch_hit: None,
+            nmi_pending: false,
+            clocked_cycle: 0,
+            irq: IrqLine::new(),
+            error_policy: ErrorPolicy::Stop,
+            faults: RefCell::new(VecDeque::with_capacity(FAULT_LOG_CAPACITY)),
+            unsupported_features: RefCell::new(VecDeque::with_capacity(FAULT_LOG_CAPACITY)),
+        }
+    }
+
+    /// Pull the NMI line, so the CPU takes a non-maskable interrupt before its next instruction.
+    pub fn request_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
+
+    /// The 2KB of internal CPU RAM, without its mirrors.
+    pub fn ram(&self) -> &[u8] {
+        self.cpu_ram.as_slice()
+    }
+
+    /// The internal RAM, written directly without going through the bus.
+    pub(crate) fn ram_mut(&mut self) -> &mut [u8] {
+        self.cpu_ram.as_mut_slice()
+    }
+
+    /// The PRG ROM bank an address is mapped to, or None if it isn't in cartridge ROM.
+    pub fn prg_bank(&self, address: u16) -> Option<usize> {
+        match address {
+            CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => Some(self.cartridge.prg_bank(address)),
+            _ => None,
+        }
+    }
+
+    /// Which part of the machine an access to `address` goes to, with PRG ROM resolved to the bank
+    /// currently mapped there.
+    pub fn region_of(&self, address: u16) -> MemoryRegion {
+        match address {
+            CPU_RAM_START..=CPU_MEMORY_END => MemoryRegion::InternalRam,
+            PRG_RAM_START..=PRG_RAM_END => MemoryRegion::PrgRam,
+            CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => MemoryRegion::PrgRom {
+                bank: self.cartridge.prg_bank(address),
+            },
+            _ => match (
+                PpuRegister::from_address(address),
+                ApuRegister::from_address(address),
+            ) {
+                (Some(register), _) => MemoryRegion::PpuRegister(register),
+                (_, Some(register)) => MemoryRegion::ApuIo(register),
+                _ => MemoryRegion::OpenBus,
+            },
+        }
+    }
+
+    /// The faults recorded since the last call, oldest first.
+    pub fn take_faults(&self) -> Vec<BusFault> {
+        self.faults.borrow_mut().drain(..).collect()
+    }
+
+    /// The writes to unemulated mapper features since the last call, oldest first.
+    pub fn take_unsupported_features(&self) -> Vec<UnsupportedFeature> {
+        self.unsupported_features.borrow_mut().drain(..).collect()
+    }
+
+    fn record_unsupported_feature(&self, address: u16, feature: &'static str) {
+        let mut features = self.unsupported_features.borrow_mut();
+
+        if features.len() == FAULT_LOG_CAPACITY {
+            features.pop_front();
+        }
+
+        features.push_back(UnsupportedFeature { address, feature });
+    }
+
+    fn record_fault(&self, address: u16, access: Access, kind: FaultKind) {
+        let mut faults = self.faults.borrow_mut();
+
+        if faults.len() == FAULT_LOG_CAPACITY {
+            faults.pop_front();
+        }
+
+        faults.push_back(BusFault {
+            address,
+            access,
+            kind,
+        });
+    }
+
+    fn write(&mut self, address: u16, data: u8) -> Result<(), FaultKind> {
         match address {
             CPU_RAM_START..=CPU_MEMORY_END => {
-                let address = address & 0b00000111_11111111;
-                self.cpu_ram.mem_write(address, data)?;
+                self.cpu_ram.as_mut_slice()[canonical_address(address) as usize] = data;
                 Ok(())
             }
-            PPU_RAM_START..=PPU_MEMORY_END => {
-                let address = address & 0b00000000_00000111;
-                Err(NesError::new("PPU not implemented yet."))
+            PRG_RAM_START..=PRG_RAM_END => {
+                self.set_sram((address - PRG_RAM_START) as usize, data);
+                Ok(())
             }
             CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => {
-                Err(NesError::new("Writing to cartridge ROM"))
+                if let Some(feature) = self.cartridge.mapper.unsupported_feature(address, data) {
+                    self.record_unsupported_feature(address, feature);
+                }
+
+                self.cartridge.cpu_write(address, data)
             }
-            _ => Err(NesError::new(&format!(
-                "Writing to address out of range {}",
-                address
-            ))),
+            _ => self.write_register(address, data),
         }
     }
 
-    fn mem_read(&self, address: u16) -> Result<u8, NesError> {
+    /// A write to one of the PPU, APU or I/O registers.
+    fn write_register(&mut self, address: u16, data: u8) -> Result<(), FaultKind> {
+        if PpuRegister::from_address(address).is_some() {
+            return Err(FaultKind::PpuRegister);
+        }
+
+        match ApuRegister::from_address(address) {
+            Some(ApuRegister::Joypad1) => {
+                for controller in self.controllers.iter_mut() {
+                    controller.strobe(data);
+                }
+                if let Some(expansion) = self.expansion.as_mut() {
+                    expansion.write(data);
+                }
+                Ok(())
+            }
+            _ => Err(FaultKind::OutOfRange),
+        }
+    }
+
+    /// A read with or without side effects on the device being read.
+    fn read(&self, address: u16, peek: bool) -> Result<u8, FaultKind> {
         match address {
             CPU_RAM_START..=CPU_MEMORY_END => {
-                let address = address & 0b00000111_11111111;
-                Ok(self.cpu_ram.mem_read(address)?)
-            }
-            PPU_RAM_START..=PPU_MEMORY_END => {
-                let address = address & 0b00000000_00000111;
-                Err(NesError::new("PPU not implemented yet."))
+                Ok(self.cpu_ram.as_slice()[canonical_address(address) as usize])
             }
+            PRG_RAM_START..=PRG_RAM_END => Ok(self.cartridge.prg_ram_read(address)),
             CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => Ok(self.cartridge.cpu_read(address)),
-            _ => Err(NesError::new(&format!(
-                "Reading to address out of range {}",
-                address
-            ))),
+            _ => self.read_register(address, peek),
         }
     }
-}
 
-impl CpuBus {
-    pub fn new(cartridge: Cartridge) -> Self {
-        CpuBus {
-            cpu_ram: RAM::new(2048),
-            cartridge,
+    /// A read of one of the PPU, APU or I/O registers.
+    fn read_register(&self, address: u16, peek: bool) -> Result<u8, FaultKind> {
+        if PpuRegister::from_address(address).is_some() {
+            return Err(FaultKind::PpuRegister);
         }
+
+        match ApuRegister::from_address(address) {
+            Some(port @ (ApuRegister::Joypad1 | ApuRegister::Joypad2)) => {
+                // The last thing on the data bus for an absolute read is the high byte of the
+                // address, so games see $40 or $41 here. Some (Paperboy) depend on it.
+                let (cabinet, driven) = self.vs_system_bits(port);
+                let open_bus = (address >> 8) as u8 & JOYPAD_OPEN_BUS_MASK & !driven;
+                let controller = &self.controllers[(port == ApuRegister::Joypad2) as usize];
+
+                let bits = if peek {
+                    controller.peek()
+                } else {
+                    controller.read()
+                };
+
+                Ok(open_bus | cabinet | self.expansion_bits(port) | bits)
+            }
+            _ => Err(FaultKind::OutOfRange),
+        }
+    }
+
+    /// The bits a Vs. System cabinet drives on a read of `port`, and which bits those are.
+    fn vs_system_bits(&self, port: ApuRegister) -> (u8, u8) {
+        match (&self.vs_system, port) {
+            (Some(vs_system), ApuRegister::Joypad1) => (vs_system.read_4016(), VS_4016_BITS),
+            (Some(vs_system), _) => (vs_system.read_4017(), VS_4017_BITS),
+            (None, _) => (0, 0),
+        }
+    }
+
+    fn expansion_bits(&self, port: ApuRegister) -> u8 {
+        match (&self.expansion, port) {
+            (Some(expansion), ApuRegister::Joypad1) => expansion.read_4016(),
+            (Some(expansion), _) => expansion.read_4017(),
+            (None, _) => 0,
+        }
+    }
+
+    /// Plug a device into controller port 0 or 1, replacing whatever was there. Other ports are
+    /// ignored.
+    pub fn connect(&mut self, port: usize, device: Box<dyn ControllerDevice>) {
+        if let Some(slot) = self.controllers.get_mut(port) {
+            *slot = device;
+        }
+    }
+
+    /// An NMI raised but not yet taken, and the sources holding the IRQ line.
+    pub(crate) fn interrupt_state(&self) -> [u8; 2] {
+        [self.nmi_pending as u8, self.irq.sources()]
+    }
+
+    pub fn controller(&self, port: usize) -> Option<&dyn ControllerDevice> {
+        self.controllers.get(port).map(|device| device.as_ref())
+    }
+
+    /// The device in a port as its concrete type, so a frontend can feed it input. None if the
+    /// port is empty or holds a different kind of device.
+    pub fn controller_mut<T: ControllerDevice>(&mut self, port: usize) -> Option<&mut T> {
+        let device: &mut dyn Any = self.controllers.get_mut(port)?.as_mut();
+        device.downcast_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::PRG_ROM_PAGE_SIZE;
+    use crate::joypad::paddle::Paddle;
+    use crate::joypad::Button;
+
+    fn test_bus() -> CpuBus {
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        contents.extend([0; 8]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        CpuBus::new(Cartridge::new(&contents).unwrap())
+    }
+
+    #[test]
+    fn test_continue_after_error() {
+        let mut bus = test_bus();
+
+        assert!(bus.mem_read(PpuRegister::Status.address()).is_err());
+        assert!(bus.take_faults().is_empty());
+
+        bus.error_policy = ErrorPolicy::Continue;
+
+        assert_eq!(bus.mem_read(0x2002).unwrap(), 0x20);
+        bus.mem_write(0x8000, 0x01).unwrap();
+        assert_eq!(bus.mem_peek(0x5000).unwrap(), 0x50);
+
+        let faults = bus.take_faults();
+        assert_eq!(faults.len(), 2);
+        assert_eq!(faults[0].address, 0x2002);
+        assert_eq!(faults[1].access, Access::Write);
+        assert_eq!(faults[0].kind, FaultKind::PpuRegister);
+        assert_eq!(faults[1].kind, FaultKind::RomWrite);
+    }
+
+    #[test]
+    fn test_region_of() {
+        let bus = test_bus();
+
+        assert_eq!(bus.region_of(0x1805), MemoryRegion::InternalRam);
+        assert_eq!(
+            bus.region_of(0x3ffa),
+            MemoryRegion::PpuRegister(PpuRegister::Status)
+        );
+        assert_eq!(
+            bus.region_of(0x4016),
+            MemoryRegion::ApuIo(ApuRegister::Joypad1)
+        );
+        assert_eq!(bus.region_of(0x4020), MemoryRegion::OpenBus);
+        assert_eq!(bus.region_of(0x6000), MemoryRegion::PrgRam);
+        assert_eq!(bus.region_of(0xfffc), MemoryRegion::PrgRom { bank: 0 });
+    }
+
+    #[test]
+    fn test_joypad_open_bus() {
+        let mut bus = test_bus();
+        let joypad: &mut Joypad = bus.controller_mut(0).unwrap();
+        joypad.buttons.set(Button::A, true);
+
+        bus.mem_write(ApuRegister::Joypad1.address(), 1).unwrap();
+        bus.mem_write(ApuRegister::Joypad1.address(), 0).unwrap();
+
+        assert_eq!(bus.mem_read(ApuRegister::Joypad1.address()).unwrap(), 0x41);
+        assert_eq!(bus.mem_read(ApuRegister::Joypad1.address()).unwrap(), 0x40);
+        assert_eq!(bus.mem_read(ApuRegister::Joypad2.address()).unwrap(), 0x40);
+    }
+
+    #[test]
+    fn test_paddle_port() {
+        let mut bus = test_bus();
+        bus.connect(1, Box::new(Paddle::new()));
+        bus.controller_mut::<Paddle>(1).unwrap().fire = true;
+        assert!(bus.controller_mut::<Joypad>(1).is_none());
+
+        assert_eq!(
+            bus.mem_read(ApuRegister::Joypad2.address()).unwrap() & 0x08,
+            0x08
+        );
+        assert_eq!(bus.mem_read(ApuRegister::Joypad1.address()).unwrap(), 0x40);
+    }
+
+    #[test]
+    fn test_expansion_port() {
+        let mut bus = test_bus();
+        bus.expansion = Some(ExpansionDevice::Microphone { active: true });
+
+        assert_eq!(bus.mem_read(ApuRegister::Joypad1.address()).unwrap(), 0x44);
+        assert_eq!(bus.mem_read(ApuRegister::Joypad2.address()).unwrap(), 0x40);
+    }
+
+    #[test]
+    fn test_vs_system_inputs() {
+        assert_eq!(test_bus().vs_system, None);
+
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x01];
+        contents.extend([0; 8]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        let mut bus = CpuBus::new(Cartridge::new(&contents).unwrap());
+        let vs_system = bus.vs_system.as_mut().unwrap();
+        vs_system.dip_switches = 0b1000_0101;
+        vs_system.coins[1] = true;
+
+        // Coin 2 and DIP switch 1, with only bit 7 left to open bus
+        assert_eq!(bus.mem_read(ApuRegister::Joypad1.address()).unwrap(), 0x48);
+        // DIP switches 3 and 8
+        assert_eq!(bus.mem_read(ApuRegister::Joypad2.address()).unwrap(), 0x84);
     }
 }
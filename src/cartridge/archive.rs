@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+
+use crate::errors::NesError;
+
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+const SEVENZ_MAGIC: [u8; 6] = [0x37, 0x7a, 0xbc, 0xaf, 0x27, 0x1c];
+
+/// The ROM inside `raw` if it is a .zip or .7z archive holding a single .nes file, or `raw`
+/// itself if it isn't an archive. Each archive format needs its feature (`zip`, `sevenz`).
+pub fn extract_rom(raw: &[u8]) -> Result<Cow<'_, [u8]>, NesError> {
+    if raw.starts_with(&ZIP_MAGIC) {
+        extract_zip(raw).map(Cow::Owned)
+    } else if raw.starts_with(&SEVENZ_MAGIC) {
+        extract_sevenz(raw).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(raw))
+    }
+}
+
+fn is_rom(name: &str) -> bool {
+    name.to_ascii_lowercase().ends_with(".nes")
+}
+
+/// The only ROM out of an archive's (name, contents) entries.
+#[cfg_attr(not(any(feature = "zip", feature = "sevenz")), allow(dead_code))]
+fn single_rom(roms: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>, NesError> {
+    let mut roms = roms.into_iter().filter(|(name, _)| is_rom(name));
+
+    match (roms.next(), roms.next()) {
+        (Some((_, rom)), None) => Ok(rom),
+        (None, _) => Err(NesError::new("Archive has no .nes file in it")),
+        (Some(_), Some(_)) => Err(NesError::new("Archive has more than one .nes file in it")),
+    }
+}
+
+#[cfg(feature = "zip")]
+fn extract_zip(raw: &[u8]) -> Result<Vec<u8>, NesError> {
+    use std::io::{Cursor, Read};
+
+    let error = |error: &dyn std::fmt::Display| NesError::new(&format!("Bad zip: {}", error));
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(raw)).map_err(|e| error(&e))?;
+    let mut roms = vec![];
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| error(&e))?;
+
+        if file.is_file() && is_rom(file.name()) {
+            let mut rom = vec![];
+            file.read_to_end(&mut rom).map_err(|e| error(&e))?;
+            roms.push((file.name().to_string(), rom));
+        }
+    }
+
+    single_rom(roms)
+}
+
+#[cfg(not(feature = "zip"))]
+fn extract_zip(_raw: &[u8]) -> Result<Vec<u8>, NesError> {
+    Err(NesError::new("Built without zip support"))
+}
+
+#[cfg(feature = "sevenz")]
+fn extract_sevenz(raw: &[u8]) -> Result<Vec<u8>, NesError> {
+    use std::io::Cursor;
+
+    let error = |error: &dyn std::fmt::Display| NesError::new(&format!("Bad 7z: {}", error));
+
+    let mut archive = sevenz_rust::SevenZReader::new(
+        Cursor::new(raw),
+        raw.len() as u64,
+        sevenz_rust::Password::empty(),
+    )
+    .map_err(|e| error(&e))?;
+    let mut roms = vec![];
+
+    archive
+        .for_each_entries(|entry, reader| {
+            if !entry.is_directory() && is_rom(entry.name()) {
+                let mut rom = vec![];
+                reader.read_to_end(&mut rom)?;
+                roms.push((entry.name().to_string(), rom));
+            }
+            Ok(true)
+        })
+        .map_err(|e| error(&e))?;
+
+    single_rom(roms)
+}
+
+#[cfg(not(feature = "sevenz"))]
+fn extract_sevenz(_raw: &[u8]) -> Result<Vec<u8>, NesError> {
+    Err(NesError::new("Built without 7z support"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_not_an_archive() {
+        let raw = [0x4e, 0x45, 0x53, 0x1a];
+        assert_eq!(extract_rom(&raw).unwrap(), Cow::Borrowed(&raw[..]));
+    }
+
+    #[test]
+    fn test_single_rom() {
+        let entry = |name: &str| (name.to_string(), name.as_bytes().to_vec());
+
+        assert_eq!(
+            single_rom(vec![entry("readme.txt"), entry("Game.NES")]).unwrap(),
+            b"Game.NES"
+        );
+        assert!(single_rom(vec![entry("readme.txt")]).is_err());
+        assert!(single_rom(vec![entry("a.nes"), entry("b.nes")]).is_err());
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_extract_zip() {
+        use std::io::{Cursor, Write};
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+        let options = zip::write::FileOptions::default();
+        writer.start_file("readme.txt", options).unwrap();
+        writer.write_all(b"not a rom").unwrap();
+        writer.start_file("game.nes", options).unwrap();
+        writer.write_all(&[0x4e, 0x45, 0x53, 0x1a]).unwrap();
+        let raw = writer.finish().unwrap().into_inner();
+
+        assert_eq!(&*extract_rom(&raw).unwrap(), [0x4e, 0x45, 0x53, 0x1a]);
+    }
+
+    #[cfg(not(feature = "zip"))]
+    #[test]
+    fn test_zip_needs_feature() {
+        assert!(extract_rom(&[0x50, 0x4b, 0x03, 0x04, 0x00]).is_err());
+    }
+}
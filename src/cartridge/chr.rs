@@ -0,0 +1,151 @@
+use crate::frame::IndexedImage;
+
+/// How many tiles wide a tile sheet is, the usual layout for CHR viewers.
+pub const SHEET_TILES_PER_ROW: usize = 16;
+
+/// The bytes in one 8x8 tile: eight rows of the low bit plane, then eight of the high.
+pub const TILE_SIZE: usize = 16;
+
+/// Which tiles have been written since a viewer last looked, so it only has to decode those rather
+/// than all of CHR RAM every frame.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DirtyTiles {
+    bits: Vec<u64>,
+}
+
+impl DirtyTiles {
+    pub fn new() -> Self {
+        DirtyTiles::default()
+    }
+
+    /// Mark the tile containing CHR address `address`.
+    pub fn mark(&mut self, address: usize) {
+        let tile = address / TILE_SIZE;
+        let word = tile / 64;
+
+        if self.bits.len() <= word {
+            self.bits.resize(word + 1, 0);
+        }
+
+        self.bits[word] |= 1 << (tile % 64);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// The dirty tile indices in order, clearing them all.
+    pub fn take(&mut self) -> Vec<usize> {
+        let mut tiles = vec![];
+
+        for (word_index, word) in self.bits.iter_mut().enumerate() {
+            while *word != 0 {
+                let bit = word.trailing_zeros() as usize;
+                tiles.push(word_index * 64 + bit);
+                *word &= *word - 1;
+            }
+        }
+
+        tiles
+    }
+}
+
+/// Decode tile `index` of some 2bpp CHR data into rows of pixel values 0-3, or None if there
+/// aren't that many tiles.
+pub fn decode_tile(chr: &[u8], index: usize) -> Option<[[u8; 8]; 8]> {
+    let tile = chr.get(index * TILE_SIZE..(index + 1) * TILE_SIZE)?;
+    let mut pixels = [[0; 8]; 8];
+
+    for (y, row) in pixels.iter_mut().enumerate() {
+        let low = tile[y];
+        let high = tile[y + 8];
+
+        for (x, pixel) in row.iter_mut().enumerate() {
+            let bit = 7 - x;
+            *pixel = ((low >> bit) & 1) | (((high >> bit) & 1) << 1);
+        }
+    }
+
+    Some(pixels)
+}
+
+/// The number of whole tiles in some CHR data.
+pub fn tile_count(chr: &[u8]) -> usize {
+    chr.len() / TILE_SIZE
+}
+
+/// Draw every tile in `chr`, 16 to a row, with pixel values 0-3 coloured by the system palette
+/// entries in `palette`, e.g. `[0x0f, 0x00, 0x10, 0x30]` for greys.
+pub fn tile_sheet(chr: &[u8], palette: [u8; 4]) -> IndexedImage {
+    let tiles = tile_count(chr);
+    let rows = tiles.div_ceil(SHEET_TILES_PER_ROW);
+
+    let width = SHEET_TILES_PER_ROW * 8;
+    let height = rows * 8;
+    let mut pixels = vec![palette[0]; width * height];
+
+    for index in 0..tiles {
+        let Some(tile) = decode_tile(chr, index) else {
+            break;
+        };
+
+        let left = (index % SHEET_TILES_PER_ROW) * 8;
+        let top = (index / SHEET_TILES_PER_ROW) * 8;
+
+        for (y, row) in tile.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                pixels[(top + y) * width + left + x] = palette[*value as usize];
+            }
+        }
+    }
+
+    IndexedImage {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_tile() {
+        let mut chr = [0; TILE_SIZE * 2];
+        chr[TILE_SIZE] = 0b1000_0001;
+        chr[TILE_SIZE + 8] = 0b1100_0000;
+
+        let tile = decode_tile(&chr, 1).unwrap();
+
+        assert_eq!(tile[0], [3, 2, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(tile[1], [0; 8]);
+        assert_eq!(tile_count(&chr), 2);
+        assert_eq!(decode_tile(&chr, 2), None);
+    }
+
+    #[test]
+    fn test_dirty_tiles() {
+        let mut dirty = DirtyTiles::new();
+        dirty.mark(0x1fff);
+        dirty.mark(0x0010);
+        dirty.mark(0x001f);
+
+        assert!(!dirty.is_empty());
+        assert_eq!(dirty.take(), [1, 511]);
+        assert!(dirty.is_empty());
+        assert!(dirty.take().is_empty());
+    }
+
+    #[test]
+    fn test_tile_sheet() {
+        let mut chr = vec![0; TILE_SIZE * 17];
+        chr[TILE_SIZE * 16] = 0b1000_0000;
+
+        let sheet = tile_sheet(&chr, [0x0f, 0x00, 0x10, 0x30]);
+
+        assert_eq!((sheet.width, sheet.height), (128, 16));
+        assert_eq!(sheet.pixels[8 * 128], 0x00);
+        assert_eq!(sheet.pixels[8 * 128 + 1], 0x0f);
+    }
+}
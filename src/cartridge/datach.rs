@@ -0,0 +1,271 @@
+use crate::cartridge::{Mirroring, PRG_ROM_PAGE_SIZE};
+use crate::errors::NesError;
+
+/// How long the reader holds each sample of a scan, in CPU cycles.
+pub const CYCLES_PER_SAMPLE: u64 = 1000;
+
+/// The value at $6000 while the reader sees a space, which is also what it reads when idle.
+const SPACE: u8 = 0b1000;
+const BAR: u8 = 0;
+
+const QUIET_ZONE_BEFORE: usize = 33;
+const QUIET_ZONE_AFTER: usize = 32;
+
+const GUARD: [bool; 3] = [true, false, true];
+const CENTRE_GUARD: [bool; 5] = [false, true, false, true, false];
+
+/// The EAN left-hand odd parity (L) code of each digit, bars as set bits. The even parity (G)
+/// and right-hand (R) codes are derived from it.
+const L_CODES: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+
+/// Which of the six left-hand digits of an EAN-13 use even parity, picked by the first digit
+/// (which isn't printed as bars itself). Bit 5 is the leftmost digit.
+const EAN_13_PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+/// The Datach barcode reader, fed with the digits printed under a card's barcode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BarcodeReader {
+    samples: Vec<u8>,
+    /// CPU cycles since the current scan started.
+    elapsed: u64,
+}
+
+impl BarcodeReader {
+    pub fn new() -> Self {
+        BarcodeReader::default()
+    }
+
+    /// Swipe a card with an 8 or 13 digit EAN barcode. The check digit is passed to the game as
+    /// given, so deliberately bad codes can be fed in.
+    pub fn scan(&mut self, barcode: &str) -> Result<(), NesError> {
+        let digits = barcode
+            .chars()
+            .map(|digit| digit.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(|| NesError::new("Barcodes can only contain digits"))?;
+
+        let (parity, left, right) = match digits.len() {
+            13 => (EAN_13_PARITY[digits[0]], &digits[1..7], &digits[7..]),
+            8 => (0, &digits[..4], &digits[4..]),
+            _ => return Err(NesError::new("Barcodes must be 8 or 13 digits long")),
+        };
+
+        let mut modules = GUARD.to_vec();
+
+        for (index, &digit) in left.iter().enumerate() {
+            let even = parity & (0b100000 >> index) != 0;
+            let code = if even {
+                // G codes are R codes read backwards
+                (!L_CODES[digit] & 0x7f).reverse_bits() >> 1
+            } else {
+                L_CODES[digit]
+            };
+            modules.extend(code_modules(code));
+        }
+
+        modules.extend(CENTRE_GUARD);
+
+        for &digit in right {
+            modules.extend(code_modules(!L_CODES[digit] & 0x7f));
+        }
+
+        modules.extend(GUARD);
+
+        self.samples = vec![SPACE; QUIET_ZONE_BEFORE];
+        self.samples
+            .extend(modules.into_iter().map(|bar| if bar { BAR } else { SPACE }));
+        self.samples.extend([SPACE; QUIET_ZONE_AFTER]);
+        self.elapsed = 0;
+
+        Ok(())
+    }
+
+    /// Whether a scan is still being read out.
+    pub fn is_scanning(&self) -> bool {
+        self.sample_index() < self.samples.len()
+    }
+
+    /// The reader's output bit, as seen in bit 3 of $6000-$7FFF.
+    pub fn read(&self) -> u8 {
+        self.samples
+            .get(self.sample_index())
+            .copied()
+            .unwrap_or(SPACE)
+    }
+
+    fn sample_index(&self) -> usize {
+        (self.elapsed / CYCLES_PER_SAMPLE) as usize
+    }
+
+    pub(crate) fn clock(&mut self, cycles: u64) {
+        if self.is_scanning() {
+            self.elapsed += cycles;
+        }
+    }
+}
+
+/// The seven modules of a digit's code, leftmost first.
+fn code_modules(code: u8) -> impl Iterator<Item = bool> {
+    (0..7).rev().map(move |bit| code & (1 << bit) != 0)
+}
+
+/// The Bandai Datach Joint ROM System (mapper 157): a 16KB switchable PRG bank at $8000 with the
+/// last bank fixed at $C000, mapper controlled mirroring and the barcode reader at $6000.
+///
+/// The IRQ counter's registers are kept but the counter isn't emulated, so it never fires. The game
+/// and joint ROM EEPROMs aren't emulated either, so saves are lost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Datach {
+    prg_banks: usize,
+    prg_bank: usize,
+    pub irq_enabled: bool,
+    pub irq_latch: u16,
+    pub barcode: BarcodeReader,
+}
+
+impl Datach {
+    pub fn new(prg_banks: usize) -> Self {
+        Datach {
+            prg_banks: prg_banks.max(1),
+            prg_bank: 0,
+            irq_enabled: false,
+            irq_latch: 0,
+            barcode: BarcodeReader::new(),
+        }
+    }
+
+    pub fn prg_bank(&self) -> usize {
+        self.prg_bank
+    }
+
+    /// The registers as bytes, for save states. A scan in progress isn't kept.
+    pub(crate) fn state(&self) -> Vec<u8> {
+        let [irq_latch_low, irq_latch_high] = self.irq_latch.to_le_bytes();
+        vec![
+            self.prg_bank as u8,
+            self.irq_enabled as u8,
+            irq_latch_low,
+            irq_latch_high,
+        ]
+    }
+
+    /// Put back registers saved by `state`. The caller checks the length.
+    pub(crate) fn restore(&mut self, state: &[u8]) {
+        self.prg_bank = state[0] as usize;
+        self.irq_enabled = state[1] != 0;
+        self.irq_latch = u16::from_le_bytes([state[2], state[3]]);
+    }
+
+    pub fn prg_address(&self, address: u16) -> usize {
+        let bank = if address >= 0xc000 {
+            self.prg_banks - 1
+        } else {
+            self.prg_bank % self.prg_banks
+        };
+
+        bank * PRG_ROM_PAGE_SIZE + (address as usize & 0x3fff)
+    }
+
+    /// A write to $8000-$FFFF, returning the new mirroring if it changed. The registers repeat
+    /// every 16 bytes.
+    pub fn write(&mut self, address: u16, data: u8) -> Option<Mirroring> {
+        match address & 0x0f {
+            0x08 => self.prg_bank = (data & 0x0f) as usize,
+            0x09 => {
+                return Some(match data & 0b11 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLower,
+                    _ => Mirroring::SingleScreenUpper,
+                })
+            }
+            0x0a => self.irq_enabled = data & 1 != 0,
+            0x0b => self.irq_latch = (self.irq_latch & 0xff00) | data as u16,
+            0x0c => self.irq_latch = (self.irq_latch & 0x00ff) | (data as u16) << 8,
+            // $8000-$8007 and $800D drive the EEPROMs
+            _ => {}
+        }
+
+        None
+    }
+
+    /// The part of the board a write to `address` drives that isn't emulated, if any.
+    pub fn unsupported_feature(&self, address: u16, data: u8) -> Option<&'static str> {
+        match address & 0x0f {
+            0x00..=0x07 | 0x0d => Some("EEPROM"),
+            0x0a if data & 1 != 0 => Some("IRQ counter"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Read a scan back as bars (1) and spaces (0), without the quiet zones.
+    fn read_bars(reader: &mut BarcodeReader) -> String {
+        let mut bars = String::new();
+
+        while reader.is_scanning() {
+            bars.push(if reader.read() == BAR { '1' } else { '0' });
+
+            // Clock it the way instructions do, a few cycles at a time
+            for _ in 0..CYCLES_PER_SAMPLE / 4 {
+                reader.clock(4);
+            }
+        }
+
+        bars.trim_matches('0').to_string()
+    }
+
+    #[test]
+    fn test_scan_ean_13() {
+        let mut reader = BarcodeReader::new();
+        assert_eq!(reader.read(), SPACE);
+
+        reader.scan("4905040352507").unwrap();
+
+        // First digit 4 gives parity LGLLGG for 905040, then 352507 in R codes
+        assert_eq!(
+            read_bars(&mut reader),
+            [
+                "101", "0001011", "0100111", "0110001", "0001101", "0011101", "0100111", "01010",
+                "1000010", "1001110", "1101100", "1001110", "1110010", "1000100", "101",
+            ]
+            .concat()
+        );
+        assert_eq!(reader.read(), SPACE);
+    }
+
+    #[test]
+    fn test_scan_rejects_bad_barcodes() {
+        let mut reader = BarcodeReader::new();
+
+        assert!(reader.scan("1234").is_err());
+        assert!(reader.scan("490504035250X").is_err());
+        assert!(!reader.is_scanning());
+    }
+
+    #[test]
+    fn test_banking() {
+        let mut datach = Datach::new(16);
+
+        assert_eq!(datach.prg_address(0x8000), 0);
+        assert_eq!(datach.prg_address(0xc000), 15 * PRG_ROM_PAGE_SIZE);
+
+        assert_eq!(datach.write(0x8008, 0x03), None);
+        assert_eq!(datach.prg_address(0x8001), 3 * PRG_ROM_PAGE_SIZE + 1);
+
+        assert_eq!(
+            datach.write(0xfff9, 0x02),
+            Some(Mirroring::SingleScreenLower)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::cartridge::profile::ProfileDatabase;
+use crate::cartridge::save::{nes2_shift_count, SaveMemory};
+use crate::cartridge::{
+    Cartridge, ConsoleType, Mirroring, CHR_ROM_PAGE_SIZE, HEADER_SIZE, NES_TAG, PRG_ROM_PAGE_SIZE,
+};
+use crate::errors::NesError;
+
+/// The bits of header byte 6 that hold the mirroring.
+const MIRRORING_BITS: u8 = 0b1001;
+
+/// A fresh header describing the cartridge as it is now. It's written as iNES unless the console
+/// type or battery backed CHR RAM needs NES 2.0 to describe it, and the unused bytes are zeroed,
+/// which gets rid of the "DiskDude!" style junk some dumping tools left there.
+///
+/// Only what the cartridge knows about goes in: a NES 2.0 header gets its RAM sizes but no
+/// submapper and NTSC timing. Use `repair` to keep those from an existing header.
+pub fn header(cartridge: &Cartridge, trainer: bool) -> Result<[u8; HEADER_SIZE], NesError> {
+    let mut header = [0; HEADER_SIZE];
+    header[0..4].copy_from_slice(&NES_TAG);
+
+    header[4] = page_count(cartridge.prg_rom.len(), PRG_ROM_PAGE_SIZE, "PRG")?;
+    header[5] = page_count(cartridge.chr_rom.len(), CHR_ROM_PAGE_SIZE, "CHR")?;
+
+    let mapper = cartridge.mapper.number();
+
+    header[6] = ((mapper as u8) << 4) | mirroring_bits(cartridge.mirroring_type);
+
+    if cartridge.battery {
+        header[6] |= 0b10;
+    }
+
+    if trainer {
+        header[6] |= 0b100;
+    }
+
+    header[7] = (mapper as u8) & 0b1111_0000;
+
+    match cartridge.console_type {
+        ConsoleType::Nes => {}
+        ConsoleType::VsSystem {
+            ppu_type: 0,
+            hardware_type: 0,
+        } => header[7] |= 0b01,
+        ConsoleType::VsSystem {
+            ppu_type,
+            hardware_type,
+        } => {
+            header[7] |= 0b1001;
+            header[13] = (hardware_type << 4) | ppu_type;
+        }
+        ConsoleType::PlayChoice10 => header[7] |= 0b10,
+        ConsoleType::Extended(console) => {
+            header[7] |= 0b1011;
+            header[13] = console;
+        }
+    }
+
+    // Battery backed CHR RAM can only be described in NES 2.0
+    let nvram_size = |memory| {
+        cartridge
+            .save_regions
+            .iter()
+            .find(|region| region.memory == memory)
+            .map_or(0, |region| region.size)
+    };
+
+    if nvram_size(SaveMemory::ChrRam) != 0 {
+        header[7] |= 0b1000;
+    }
+
+    if is_nes2(&header) {
+        let prg_nvram = nvram_size(SaveMemory::PrgRam);
+        let chr_nvram = nvram_size(SaveMemory::ChrRam);
+        let prg_ram = cartridge.prg_ram.len().saturating_sub(prg_nvram);
+        let chr_ram = cartridge.chr_ram.len().saturating_sub(chr_nvram);
+
+        header[10] = (nes2_shift_count(prg_nvram) << 4) | nes2_shift_count(prg_ram);
+        header[11] = (nes2_shift_count(chr_nvram) << 4) | nes2_shift_count(chr_ram);
+    }
+
+    Ok(header)
+}
+
+/// The cartridge as an iNES file, with `trainer` (if any) kept in front of the PRG ROM.
+pub fn export(cartridge: &Cartridge, trainer: Option<&[u8]>) -> Result<Vec<u8>, NesError> {
+    let mut raw = header(cartridge, trainer.is_some())?.to_vec();
+
+    if let Some(trainer) = trainer {
+        raw.extend(trainer);
+    }
+
+    raw.extend(&cartridge.prg_rom);
+    raw.extend(&cartridge.chr_rom);
+
+    Ok(raw)
+}
+
+/// Rewrite a ROM's header with whatever the database knows better than it, e.g. the mirroring or
+/// the mapper. Everything else in the header is kept, apart from the junk iNES headers can have in
+/// their unused bytes.
+pub fn repair(raw: &[u8], database: &ProfileDatabase) -> Result<Vec<u8>, NesError> {
+    if raw.len() < HEADER_SIZE || raw[0..4] != NES_TAG {
+        return Err(NesError::new("File is not in iNES format"));
+    }
+
+    let mut repaired = raw.to_vec();
+
+    // NES 2.0 uses bytes 8-15 for the mapper and ROM size MSBs, submapper, RAM sizes and timing,
+    // but in iNES they're unused and some dumping tools wrote "DiskDude!" there. That junk starts
+    // at byte 7, where it reads as a mapper number and console type, so when the last bytes aren't
+    // blank byte 7 can't be trusted either. This has to happen before parsing, which would
+    // otherwise choke on byte 7.
+    if !is_nes2(raw) {
+        if raw[12..HEADER_SIZE].iter().any(|&byte| byte != 0) {
+            repaired[7] = 0;
+        }
+
+        repaired[8..HEADER_SIZE].fill(0);
+    }
+
+    let mut cartridge = Cartridge::new(&repaired)?;
+
+    if let Some(profile) = database.lookup(&cartridge) {
+        profile.apply(&mut cartridge);
+        repaired[6] = (repaired[6] & !MIRRORING_BITS) | mirroring_bits(cartridge.mirroring_type);
+
+        if let Some(mapper) = profile.mapper {
+            repaired[6] = (repaired[6] & 0b0000_1111) | (mapper << 4);
+            repaired[7] = (repaired[7] & 0b0000_1111) | (mapper & 0b1111_0000);
+        }
+    }
+
+    Ok(repaired)
+}
+
+fn is_nes2(header: &[u8]) -> bool {
+    header[7] & 0b1100 == 0b1000
+}
+
+fn mirroring_bits(mirroring: Mirroring) -> u8 {
+    match mirroring {
+        // Boards that set their mirroring at runtime don't use the header's, so any value will do
+        Mirroring::Horizontal
+        | Mirroring::SingleScreenLower
+        | Mirroring::SingleScreenUpper
+        | Mirroring::Mapped(_) => 0b0000,
+        Mirroring::Vertical => 0b0001,
+        Mirroring::FourScreen => 0b1000,
+    }
+}
+
+/// The number of `page_size` pages in `length` bytes of ROM, if the header byte can hold it.
+fn page_count(length: usize, page_size: usize, name: &str) -> Result<u8, NesError> {
+    u8::try_from(length / page_size).map_err(|_| {
+        NesError::new(&format!(
+            "{} KB of {} ROM is too big for the header to describe",
+            length / 1024,
+            name
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::profile::GameProfile;
+    use crate::hash::crc32;
+
+    #[test]
+    fn test_repair() {
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x01, 0b0100_0010];
+        raw.extend(b"DiskDude!");
+        raw.extend([0xea; PRG_ROM_PAGE_SIZE]);
+        raw.extend([0x11; CHR_ROM_PAGE_SIZE]);
+
+        let mut database = ProfileDatabase::new();
+        let mut rom = vec![0xea; PRG_ROM_PAGE_SIZE];
+        rom.extend([0x11; CHR_ROM_PAGE_SIZE]);
+        database.insert(
+            crc32(&rom),
+            GameProfile {
+                mirroring: Some(Mirroring::Vertical),
+                mapper: Some(0),
+                ..GameProfile::default()
+            },
+        );
+
+        let repaired = repair(&raw, &database).unwrap();
+
+        assert_eq!(
+            repaired[..HEADER_SIZE],
+            [
+                0x4e,
+                0x45,
+                0x53,
+                0x1a,
+                0x01,
+                0x01,
+                0b0000_0011,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0
+            ]
+        );
+        assert_eq!(repaired[HEADER_SIZE..], raw[HEADER_SIZE..]);
+
+        let cartridge = Cartridge::new(&repaired).unwrap();
+        assert_eq!(cartridge.mirroring_type, Mirroring::Vertical);
+        assert_eq!(cartridge.mapper.number(), 0);
+        assert!(cartridge.battery);
+    }
+
+    #[test]
+    fn test_repair_clears_junk_before_parsing() {
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00];
+        raw.extend(b"DiskDude!");
+        raw.extend([0xea; PRG_ROM_PAGE_SIZE]);
+
+        assert!(Cartridge::new(&raw).is_err());
+
+        let repaired = repair(&raw, &ProfileDatabase::new()).unwrap();
+
+        assert_eq!(repaired[7..HEADER_SIZE], [0; 9]);
+        assert_eq!(repaired[HEADER_SIZE..], raw[HEADER_SIZE..]);
+    }
+
+    #[test]
+    fn test_repair_keeps_nes2_bytes() {
+        // MMC3 submapper 1 with 8 KB of PRG RAM, 8 KB of CHR RAM and PAL timing
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x02, 0x00, 0x40, 0x08];
+        raw.extend([0x10, 0x00, 0x07, 0x07, 0x01, 0x00, 0x00, 0x00]);
+        raw.extend([0xea; 2 * PRG_ROM_PAGE_SIZE]);
+
+        assert_eq!(repair(&raw, &ProfileDatabase::new()).unwrap(), raw);
+
+        let mut database = ProfileDatabase::new();
+        database.insert(
+            crc32(&[0xea; 2 * PRG_ROM_PAGE_SIZE]),
+            GameProfile {
+                mirroring: Some(Mirroring::Vertical),
+                ..GameProfile::default()
+            },
+        );
+
+        let repaired = repair(&raw, &database).unwrap();
+        assert_eq!(repaired[6], 0x41);
+        assert_eq!(repaired[7..], raw[7..]);
+    }
+
+    #[test]
+    fn test_nes2_header_ram_sizes() {
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x0b];
+        raw.extend([0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00]);
+        raw.extend([0xea; PRG_ROM_PAGE_SIZE]);
+        let cartridge = Cartridge::new(&raw).unwrap();
+
+        let header = header(&cartridge, false).unwrap();
+
+        assert_eq!(cartridge.console_type, ConsoleType::Extended(3));
+        assert_eq!(header[7], 0x0b);
+        assert_eq!(header[10..14], [0x07, 0x07, 0x00, 0x03]);
+    }
+
+    #[test]
+    fn test_header_rejects_too_much_rom() {
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        raw.extend([0; 8]);
+        raw.extend([0xea; PRG_ROM_PAGE_SIZE]);
+        let mut cartridge = Cartridge::new(&raw).unwrap();
+        cartridge.prg_rom = vec![0xea; 256 * PRG_ROM_PAGE_SIZE];
+
+        assert_eq!(
+            header(&cartridge, false).unwrap_err().message,
+            "4096 KB of PRG ROM is too big for the header to describe"
+        );
+    }
+}
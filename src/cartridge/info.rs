@@ -0,0 +1,61 @@
+use crate::cartridge::{Cartridge, HEADER_SIZE, TRAINER_SIZE};
+use crate::errors::NesError;
+use crate::hash::crc32;
+
+/// A human readable summary of a ROM file's header and hashes.
+pub fn describe(raw: &[u8]) -> Result<String, NesError> {
+    let cartridge = Cartridge::new(raw)?;
+
+    let nes2 = (raw[7] >> 2) & 0b11 == 0b10;
+    let trainer = raw[6] & 0b100 != 0;
+    let rom_start = HEADER_SIZE + if trainer { TRAINER_SIZE } else { 0 };
+
+    let mut info = String::new();
+
+    info.push_str(&format!(
+        "Format:       {}\n",
+        if nes2 { "NES 2.0" } else { "iNES" }
+    ));
+    info.push_str(&format!(
+        "Mapper:       {} ({})\n",
+        cartridge.mapper.number(),
+        cartridge.mapper.name()
+    ));
+    info.push_str(&format!("Console:      {:?}\n", cartridge.console_type));
+    info.push_str(&format!(
+        "PRG ROM:      {} KB ({} x 16 KB)\n",
+        cartridge.prg_rom.len() / 1024,
+        raw[4]
+    ));
+    info.push_str(&format!(
+        "CHR ROM:      {} KB ({} x 8 KB)\n",
+        cartridge.chr_rom.len() / 1024,
+        raw[5]
+    ));
+    info.push_str(&format!("Mirroring:    {:?}\n", cartridge.mirroring_type));
+    info.push_str(&format!("Battery:      {}\n", yes_no(cartridge.battery)));
+    info.push_str(&format!("Trainer:      {}\n", yes_no(trainer)));
+    info.push_str(&format!("CRC32 (file): {:08X}\n", crc32(raw)));
+    info.push_str(&format!("CRC32 (ROM):  {:08X}\n", crc32(&raw[rom_start..])));
+    info.push_str(&format!(
+        "CRC32 (PRG):  {:08X}\n",
+        crc32(&cartridge.prg_rom)
+    ));
+
+    if !cartridge.chr_rom.is_empty() {
+        info.push_str(&format!(
+            "CRC32 (CHR):  {:08X}\n",
+            crc32(&cartridge.chr_rom)
+        ));
+    }
+
+    Ok(info)
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
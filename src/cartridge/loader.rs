@@ -0,0 +1,153 @@
+use std::future::Future;
+use std::io::Read;
+
+use crate::cartridge::archive::extract_rom;
+use crate::cartridge::{
+    Cartridge, CHR_ROM_PAGE_SIZE, HEADER_SIZE, NES_TAG, PRG_ROM_PAGE_SIZE, TRAINER_SIZE,
+};
+use crate::errors::NesError;
+
+/// Collects a ROM that arrives in pieces, like the chunks of a fetch response body, so a
+/// frontend can hand each one over as it comes in instead of blocking until the whole file is
+/// there.
+#[derive(Debug, Clone, Default)]
+pub struct RomLoader {
+    data: Vec<u8>,
+}
+
+impl RomLoader {
+    pub fn new() -> Self {
+        RomLoader::default()
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.data.extend_from_slice(chunk);
+    }
+
+    /// How many bytes have arrived so far.
+    pub fn received(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The size of the whole ROM, once enough of an iNES header has arrived to tell. Archives
+    /// don't say, so progress can't be shown for them.
+    pub fn expected_size(&self) -> Option<usize> {
+        let header = self.data.get(..HEADER_SIZE)?;
+
+        if header[0..4] != NES_TAG {
+            return None;
+        }
+
+        let trainer = if header[6] & 0b100 != 0 {
+            TRAINER_SIZE
+        } else {
+            0
+        };
+
+        Some(
+            HEADER_SIZE
+                + trainer
+                + header[4] as usize * PRG_ROM_PAGE_SIZE
+                + header[5] as usize * CHR_ROM_PAGE_SIZE,
+        )
+    }
+
+    /// Whether everything the header describes has arrived.
+    pub fn is_complete(&self) -> bool {
+        self.expected_size()
+            .is_some_and(|size| self.received() >= size)
+    }
+
+    /// Build the cartridge from everything pushed so far.
+    pub fn finish(self) -> Result<Cartridge, NesError> {
+        Cartridge::new(&extract_rom(&self.data)?)
+    }
+}
+
+impl Cartridge {
+    /// Load a ROM, or an archive holding one, from anything readable.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, NesError> {
+        let mut raw = vec![];
+        reader
+            .read_to_end(&mut raw)
+            .map_err(|error| NesError::new(&format!("Could not read ROM: {}", error)))?;
+
+        Cartridge::new(&extract_rom(&raw)?)
+    }
+
+    /// Load a ROM whose chunks come from awaiting `next_chunk` until it gives None. This doesn't
+    /// depend on any runtime, so it can await a browser stream reader through
+    /// wasm-bindgen-futures as easily as a socket.
+    pub async fn from_chunks<F, Fut>(mut next_chunk: F) -> Result<Self, NesError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Option<Vec<u8>>, NesError>>,
+    {
+        let mut loader = RomLoader::new();
+
+        while let Some(chunk) = next_chunk().await? {
+            loader.push(&chunk);
+        }
+
+        loader.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use super::*;
+
+    fn rom() -> Vec<u8> {
+        let mut rom = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        rom.extend([0; 8]);
+        rom.extend([0xea; PRG_ROM_PAGE_SIZE]);
+        rom
+    }
+
+    #[test]
+    fn test_rom_loader() {
+        let rom = rom();
+        let mut loader = RomLoader::new();
+
+        loader.push(&rom[..10]);
+        assert_eq!(loader.expected_size(), None);
+
+        loader.push(&rom[10..100]);
+        assert_eq!(loader.expected_size(), Some(rom.len()));
+        assert!(!loader.is_complete());
+
+        loader.push(&rom[100..]);
+        assert!(loader.is_complete());
+        assert_eq!(loader.finish().unwrap().prg_rom, &rom[HEADER_SIZE..]);
+
+        assert_eq!(
+            Cartridge::from_reader(Cursor::new(&rom)).unwrap().prg_rom,
+            &rom[HEADER_SIZE..]
+        );
+    }
+
+    #[test]
+    fn test_from_chunks() {
+        let mut chunks = rom()
+            .chunks(4096)
+            .map(|chunk| chunk.to_vec())
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        let future = pin!(Cartridge::from_chunks(|| {
+            let chunk = chunks.next();
+            async move { Ok(chunk) }
+        }));
+
+        // Every chunk is ready straight away, so one poll finishes it
+        let Poll::Ready(cartridge) = future.poll(&mut Context::from_waker(Waker::noop())) else {
+            panic!("from_chunks should not wait on ready chunks");
+        };
+
+        assert_eq!(cartridge.unwrap().prg_rom, [0xea; PRG_ROM_PAGE_SIZE]);
+    }
+}
@@ -1,30 +1,195 @@
-pub trait Mapping {
-    fn get_chr_address(address: &u8) -> u8;
+use crate::bus::FaultKind;
+use crate::cartridge::datach::Datach;
+use crate::cartridge::mmc3::{Mmc3, Mmc3Board};
+use crate::cartridge::vrc6::Vrc6;
+use crate::cartridge::{Mirroring, PRG_ROM_PAGE_SIZE};
+use crate::errors::NesError;
 
-    fn get_pgr_address(address: &u8) -> u8;
+/// Where a pattern table address ends up on the cartridge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChrAddress {
+    Rom(usize),
+    Ram(usize),
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Mapper {
-    Mapper000 { mirror_bank: bool },
+    Mapper000 {
+        mirror_bank: bool,
+    },
+    /// Mappers 4, 118 and 119, which differ only in how the board is wired.
+    Mmc3(Mmc3),
+    /// Mappers 24 and 26, told apart by the VRC6's wiring.
+    Vrc6(Vrc6),
+    Mapper157(Datach),
 }
 
 impl Mapper {
-    pub fn get_pgr_address(&self, address: u16) -> u16 {
+    /// The iNES mapper number.
+    pub fn number(&self) -> u16 {
+        match self {
+            Mapper::Mapper000 { .. } => 0,
+            Mapper::Mmc3(mmc3) => mmc3.number(),
+            Mapper::Vrc6(vrc6) => vrc6.number(),
+            Mapper::Mapper157(_) => 157,
+        }
+    }
+
+    /// The common name of the board.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mapper::Mapper000 { .. } => "NROM",
+            Mapper::Mmc3(mmc3) => match mmc3.board {
+                Mmc3Board::Standard => "MMC3",
+                Mmc3Board::TxSrom => "TxSROM",
+                Mmc3Board::Tqrom => "TQROM",
+            },
+            Mapper::Vrc6(vrc6) if vrc6.swap_address_lines => "VRC6b",
+            Mapper::Vrc6(_) => "VRC6a",
+            Mapper::Mapper157(_) => "Datach",
+        }
+    }
+
+    /// The banking state as (name, value) pairs, for debuggers and trace tooling. Registers a
+    /// board doesn't have are left out, so NROM only reports its fixed banks.
+    pub fn debug_state(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Mapper::Mapper000 { mirror_bank } => {
+                let prg_banks = if *mirror_bank { "0, 0" } else { "0, 1" };
+
+                vec![
+                    ("prg_banks", prg_banks.to_string()),
+                    ("chr_bank", "0".to_string()),
+                ]
+            }
+            Mapper::Mmc3(mmc3) => vec![
+                ("prg_banks", format!("{:02X?}", mmc3.prg_banks())),
+                (
+                    "chr_banks",
+                    mmc3.chr_banks()
+                        .map(|bank| match bank {
+                            ChrAddress::Rom(bank) => format!("{:02X}", bank),
+                            ChrAddress::Ram(bank) => format!("RAM {:X}", bank),
+                        })
+                        .join(", "),
+                ),
+                ("prg_ram_protect", format!("{:02X}", mmc3.prg_ram_protect)),
+                ("irq_enabled", mmc3.irq_enabled.to_string()),
+                ("irq_latch", format!("{:02X}", mmc3.irq_latch)),
+            ],
+            Mapper::Vrc6(vrc6) => {
+                let (prg_bank_16k, prg_bank_8k) = vrc6.prg_banks();
+
+                vec![
+                    (
+                        "prg_banks",
+                        format!("{}, {}, last", prg_bank_16k, prg_bank_8k),
+                    ),
+                    ("chr_banks", format!("{:02X?}", vrc6.chr_banks())),
+                    ("prg_ram_enabled", vrc6.prg_ram_enabled().to_string()),
+                ]
+            }
+            Mapper::Mapper157(datach) => vec![
+                ("prg_banks", format!("{}, last", datach.prg_bank())),
+                ("irq_enabled", datach.irq_enabled.to_string()),
+                ("irq_latch", format!("{:04X}", datach.irq_latch)),
+            ],
+        }
+    }
+
+    /// The banking registers as bytes, for save states.
+    pub fn state(&self) -> Vec<u8> {
+        match self {
+            Mapper::Mapper000 { .. } => vec![],
+            Mapper::Mmc3(mmc3) => mmc3.state(),
+            Mapper::Vrc6(vrc6) => vrc6.state(),
+            Mapper::Mapper157(datach) => datach.state(),
+        }
+    }
+
+    /// Put back the registers from `state`, which has to come from the same kind of board.
+    pub fn restore(&mut self, state: &[u8]) -> Result<(), NesError> {
+        if state.len() != self.state().len() {
+            return Err(NesError::new(&format!(
+                "{} state should be {} bytes, not {}",
+                self.name(),
+                self.state().len(),
+                state.len()
+            )));
+        }
+
+        match self {
+            Mapper::Mapper000 { .. } => {}
+            Mapper::Mmc3(mmc3) => mmc3.restore(state),
+            Mapper::Vrc6(vrc6) => vrc6.restore(state),
+            Mapper::Mapper157(datach) => datach.restore(state),
+        }
+
+        Ok(())
+    }
+
+    /// A CPU write to $8000-$FFFF, returning the new mirroring if the write changed it.
+    pub fn write(&mut self, address: u16, data: u8) -> Result<Option<Mirroring>, FaultKind> {
+        match self {
+            Mapper::Mapper000 { .. } => Err(FaultKind::RomWrite),
+            Mapper::Mmc3(mmc3) => Ok(mmc3.write(address, data)),
+            Mapper::Vrc6(vrc6) => Ok(vrc6.write(address, data)),
+            Mapper::Mapper157(datach) => Ok(datach.write(address, data)),
+        }
+    }
+
+    /// The part of the board a CPU write to $8000-$FFFF uses that isn't emulated, like VRC6 audio,
+    /// for compatibility reports.
+    pub fn unsupported_feature(&self, address: u16, data: u8) -> Option<&'static str> {
+        match self {
+            Mapper::Mapper000 { .. } => None,
+            Mapper::Mmc3(mmc3) => mmc3.unsupported_feature(address),
+            Mapper::Vrc6(vrc6) => vrc6.unsupported_feature(address, data),
+            Mapper::Mapper157(datach) => datach.unsupported_feature(address, data),
+        }
+    }
+
+    /// A CPU read from $6000-$7FFF that the board answers instead of PRG RAM.
+    pub fn read(&self, _address: u16) -> Option<u8> {
+        match self {
+            Mapper::Mapper000 { .. } | Mapper::Mmc3(_) | Mapper::Vrc6(_) => None,
+            Mapper::Mapper157(datach) => Some(datach.barcode.read()),
+        }
+    }
+
+    /// The size of the PRG ROM window a CPU address falls in, which is what the board counts its
+    /// banks in. VRC6 has a 16KB window followed by two 8KB ones.
+    pub fn prg_bank_size(&self, address: u16) -> usize {
+        match self {
+            Mapper::Mapper000 { .. } | Mapper::Mapper157(_) => PRG_ROM_PAGE_SIZE,
+            Mapper::Mmc3(_) => PRG_ROM_PAGE_SIZE / 2,
+            Mapper::Vrc6(_) if address < 0xc000 => PRG_ROM_PAGE_SIZE,
+            Mapper::Vrc6(_) => PRG_ROM_PAGE_SIZE / 2,
+        }
+    }
+
+    pub fn get_pgr_address(&self, address: u16) -> usize {
         match self {
             Mapper::Mapper000 { mirror_bank } => {
                 if *mirror_bank {
-                    address & 0x3fff
+                    address as usize & 0x3fff
                 } else {
-                    address & 0x7fff
+                    address as usize & 0x7fff
                 }
             }
+            Mapper::Mmc3(mmc3) => mmc3.prg_address(address),
+            Mapper::Vrc6(vrc6) => vrc6.prg_address(address),
+            Mapper::Mapper157(datach) => datach.prg_address(address),
         }
     }
 
-    pub fn get_chr_address(&self, address: u16) -> u16 {
+    /// Boards with only CHR RAM give ROM addresses too, which the cartridge sends to its RAM.
+    pub fn get_chr_address(&self, address: u16) -> ChrAddress {
         match self {
-            Mapper::Mapper000 { mirror_bank: _ } => address,
+            Mapper::Mapper000 { mirror_bank: _ } => ChrAddress::Rom(address as usize),
+            Mapper::Mmc3(mmc3) => mmc3.chr_address(address),
+            Mapper::Vrc6(vrc6) => ChrAddress::Rom(vrc6.chr_address(address)),
+            Mapper::Mapper157(_) => ChrAddress::Ram(address as usize & 0x1fff),
         }
     }
 }
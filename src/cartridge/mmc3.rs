@@ -0,0 +1,266 @@
+use crate::cartridge::mapper::ChrAddress;
+use crate::cartridge::Mirroring;
+
+const PRG_BANK_SIZE: usize = 8192;
+const CHR_BANK_SIZE: usize = 1024;
+
+const CHR_INVERSION: u8 = 0b1000_0000;
+const PRG_MODE: u8 = 0b0100_0000;
+
+/// TQROM's 1KB CHR banks with this bit set come from its 8KB of CHR RAM.
+const TQROM_CHR_RAM: u8 = 0b0100_0000;
+
+/// The boards built around the MMC3 that need more than the chip itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mmc3Board {
+    /// TxROM and the rest of mapper 4.
+    Standard,
+    /// TKSROM and TLSROM (mapper 118), which wire CHR A17 to the nametable select instead of
+    /// using $A000, so each nametable follows the top bit of a CHR bank register.
+    TxSrom,
+    /// TQROM (mapper 119), with both CHR ROM and 8KB of CHR RAM, picked per bank by bit 6.
+    Tqrom,
+}
+
+/// Nintendo's MMC3: two switchable 8KB PRG banks, two 2KB and four 1KB CHR banks and mapper
+/// controlled mirroring.
+///
+/// The scanline IRQ's registers are kept but it never fires, as there is no PPU to clock it yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mmc3 {
+    pub board: Mmc3Board,
+    prg_banks: usize,
+    bank_select: u8,
+    registers: [u8; 8],
+    pub prg_ram_protect: u8,
+    pub irq_latch: u8,
+    pub irq_enabled: bool,
+}
+
+impl Mmc3 {
+    /// A new MMC3 with `prg_banks` 16KB pages of PRG ROM.
+    pub fn new(prg_banks: usize, board: Mmc3Board) -> Self {
+        Mmc3 {
+            board,
+            prg_banks: prg_banks.max(1) * 2,
+            bank_select: 0,
+            registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            prg_ram_protect: 0,
+            irq_latch: 0,
+            irq_enabled: false,
+        }
+    }
+
+    pub fn number(&self) -> u16 {
+        match self.board {
+            Mmc3Board::Standard => 4,
+            Mmc3Board::TxSrom => 118,
+            Mmc3Board::Tqrom => 119,
+        }
+    }
+
+    /// The registers as bytes, for save states.
+    pub(crate) fn state(&self) -> Vec<u8> {
+        let mut state = vec![self.bank_select];
+        state.extend(self.registers);
+        state.extend([self.prg_ram_protect, self.irq_latch, self.irq_enabled as u8]);
+        state
+    }
+
+    /// Put back registers saved by `state`. The caller checks the length.
+    pub(crate) fn restore(&mut self, state: &[u8]) {
+        self.bank_select = state[0];
+        self.registers.copy_from_slice(&state[1..9]);
+        self.prg_ram_protect = state[9];
+        self.irq_latch = state[10];
+        self.irq_enabled = state[11] != 0;
+    }
+
+    /// The bank registers R0-R7.
+    pub fn registers(&self) -> [u8; 8] {
+        self.registers
+    }
+
+    /// The 8KB PRG banks mapped at $8000, $A000, $C000 and $E000, after the PRG mode is applied.
+    pub fn prg_banks(&self) -> [usize; 4] {
+        [0x8000, 0xa000, 0xc000, 0xe000].map(|address| self.prg_address(address) / PRG_BANK_SIZE)
+    }
+
+    /// The 1KB CHR banks mapped at each 1KB of the pattern tables, after CHR inversion is applied.
+    /// On TQROM these can be banks of CHR RAM.
+    pub fn chr_banks(&self) -> [ChrAddress; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|slot| match self.chr_address(slot * 0x400) {
+            ChrAddress::Rom(address) => ChrAddress::Rom(address / CHR_BANK_SIZE),
+            ChrAddress::Ram(address) => ChrAddress::Ram(address / CHR_BANK_SIZE),
+        })
+    }
+
+    pub fn prg_address(&self, address: u16) -> usize {
+        let second_last = self.prg_banks - 2;
+        let swap = self.bank_select & PRG_MODE != 0;
+
+        let bank = match (address, swap) {
+            (0x8000..=0x9fff, false) | (0xc000..=0xdfff, true) => self.registers[6] as usize,
+            (0x8000..=0x9fff, true) | (0xc000..=0xdfff, false) => second_last,
+            (0xa000..=0xbfff, _) => self.registers[7] as usize,
+            _ => self.prg_banks - 1,
+        };
+
+        (bank % self.prg_banks) * PRG_BANK_SIZE + (address as usize & 0x1fff)
+    }
+
+    pub fn chr_address(&self, address: u16) -> ChrAddress {
+        let bank = self.chr_bank(address);
+        let offset = address as usize & 0x3ff;
+
+        if self.board == Mmc3Board::Tqrom && bank & TQROM_CHR_RAM != 0 {
+            ChrAddress::Ram((bank & 0b111) as usize * CHR_BANK_SIZE + offset)
+        } else {
+            ChrAddress::Rom(bank as usize * CHR_BANK_SIZE + offset)
+        }
+    }
+
+    /// The 1KB CHR bank at a pattern table address.
+    fn chr_bank(&self, address: u16) -> u8 {
+        let mut slot = (address as usize >> 10) & 0b111;
+
+        if self.bank_select & CHR_INVERSION != 0 {
+            slot ^= 0b100;
+        }
+
+        match slot {
+            0..=3 => self.registers[slot / 2] & 0xfe | (slot & 1) as u8,
+            _ => self.registers[slot - 2],
+        }
+    }
+
+    /// TxSROM's nametables, from CHR A17 of the banks at $0000-$0FFF.
+    fn txsrom_mirroring(&self) -> Mirroring {
+        Mirroring::Mapped([0, 1, 2, 3].map(|index| self.chr_bank(index * 0x400) >> 7))
+    }
+
+    /// A write to $8000-$FFFF, returning the new mirroring if it changed. Each register pair is
+    /// told apart by A0.
+    pub fn write(&mut self, address: u16, data: u8) -> Option<Mirroring> {
+        match (address & 0xe000, address & 1) {
+            (0x8000, 0) => self.bank_select = data,
+            (0x8000, _) => self.registers[(self.bank_select & 0b111) as usize] = data,
+            (0xa000, 0) if self.board != Mmc3Board::TxSrom => {
+                return Some(if data & 1 == 0 {
+                    Mirroring::Vertical
+                } else {
+                    Mirroring::Horizontal
+                });
+            }
+            (0xa000, 0) => {}
+            (0xa000, _) => self.prg_ram_protect = data,
+            (0xc000, 0) => self.irq_latch = data,
+            // The reload at $C001 only matters once the counter runs
+            (0xc000, _) => {}
+            (0xe000, 0) => self.irq_enabled = false,
+            _ => self.irq_enabled = true,
+        }
+
+        match (self.board, address & 0xe000) {
+            (Mmc3Board::TxSrom, 0x8000) => Some(self.txsrom_mirroring()),
+            _ => None,
+        }
+    }
+
+    /// The part of the chip a write to `address` drives that isn't emulated, if any. The IRQ
+    /// counter is clocked by the PPU, so enabling it does nothing yet.
+    pub fn unsupported_feature(&self, address: u16) -> Option<&'static str> {
+        match (address & 0xe000, address & 1) {
+            (0xe000, 1) => Some("IRQ counter"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prg_banking() {
+        let mut mmc3 = Mmc3::new(8, Mmc3Board::Standard);
+
+        mmc3.write(0x8000, 6);
+        mmc3.write(0x8001, 3);
+
+        assert_eq!(mmc3.prg_address(0x8000), 3 * PRG_BANK_SIZE);
+        assert_eq!(mmc3.prg_address(0xc000), 14 * PRG_BANK_SIZE);
+        assert_eq!(mmc3.prg_address(0xffff), 16 * PRG_BANK_SIZE - 1);
+
+        mmc3.write(0x8000, PRG_MODE | 6);
+
+        assert_eq!(mmc3.prg_address(0x8000), 14 * PRG_BANK_SIZE);
+        assert_eq!(mmc3.prg_address(0xc000), 3 * PRG_BANK_SIZE);
+        assert_eq!(mmc3.write(0xa000, 1), Some(Mirroring::Horizontal));
+    }
+
+    #[test]
+    fn test_resolved_banks() {
+        let mut mmc3 = Mmc3::new(8, Mmc3Board::Tqrom);
+
+        mmc3.write(0x8000, PRG_MODE | 6);
+        mmc3.write(0x8001, 3);
+        mmc3.write(0x8000, CHR_INVERSION | PRG_MODE | 2);
+        mmc3.write(0x8001, TQROM_CHR_RAM | 0x01);
+
+        assert_eq!(mmc3.prg_banks(), [14, 1, 3, 15]);
+        assert_eq!(
+            mmc3.chr_banks(),
+            [
+                ChrAddress::Ram(1),
+                ChrAddress::Rom(5),
+                ChrAddress::Rom(6),
+                ChrAddress::Rom(7),
+                ChrAddress::Rom(0),
+                ChrAddress::Rom(1),
+                ChrAddress::Rom(2),
+                ChrAddress::Rom(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_txsrom_mirroring() {
+        let mut mmc3 = Mmc3::new(8, Mmc3Board::TxSrom);
+
+        // $A000 is not connected
+        assert_eq!(mmc3.write(0xa000, 1), None);
+
+        mmc3.write(0x8000, 0);
+        assert_eq!(
+            mmc3.write(0x8001, 0x80),
+            Some(Mirroring::Mapped([1, 1, 0, 0]))
+        );
+
+        // With the CHR halves swapped the nametables follow R2-R5
+        mmc3.write(0x8000, CHR_INVERSION | 3);
+        assert_eq!(
+            mmc3.write(0x8001, 0x80),
+            Some(Mirroring::Mapped([0, 1, 0, 0]))
+        );
+    }
+
+    #[test]
+    fn test_tqrom_chr_ram() {
+        let mut mmc3 = Mmc3::new(8, Mmc3Board::Tqrom);
+
+        mmc3.write(0x8000, 2);
+        mmc3.write(0x8001, 0x05);
+        mmc3.write(0x8000, 3);
+        mmc3.write(0x8001, TQROM_CHR_RAM | 0x03);
+
+        assert_eq!(
+            mmc3.chr_address(0x1001),
+            ChrAddress::Rom(5 * CHR_BANK_SIZE + 1)
+        );
+        assert_eq!(
+            mmc3.chr_address(0x1401),
+            ChrAddress::Ram(3 * CHR_BANK_SIZE + 1)
+        );
+    }
+}
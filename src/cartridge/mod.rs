@@ -1,43 +1,124 @@
-use crate::cartridge::mapper::Mapper;
-use crate::memory::Mem;
+use std::fmt;
+use std::fs;
+use std::path::Path;
 
+use crate::bus::FaultKind;
+use crate::cartridge::archive::extract_rom;
+use crate::cartridge::chr::DirtyTiles;
+use crate::cartridge::datach::{BarcodeReader, Datach};
+use crate::cartridge::mapper::{ChrAddress, Mapper};
+use crate::cartridge::mmc3::{Mmc3, Mmc3Board};
+use crate::cartridge::save::{nes2_prg_ram_size, nes2_save_regions, SaveMemory, SaveRegion};
+use crate::cartridge::vrc6::Vrc6;
+use crate::errors::NesError;
+
+pub const NES_TAG: [u8; 4] = [0x4e, 0x45, 0x53, 0x1a];
+pub const HEADER_SIZE: usize = 16;
+pub const TRAINER_SIZE: usize = 512;
 pub const PRG_ROM_PAGE_SIZE: usize = 16384;
 pub const CHR_ROM_PAGE_SIZE: usize = 8192;
+pub const PRG_RAM_SIZE: usize = 8192;
+pub const CHR_RAM_SIZE: usize = 8192;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mirroring {
     Vertical,
     Horizontal,
     FourScreen,
+    /// Every nametable address shows the first physical nametable, under mapper control.
+    SingleScreenLower,
+    /// Every nametable address shows the second physical nametable.
+    SingleScreenUpper,
+    /// The physical nametable behind each of the four logical ones, for boards that pick them
+    /// individually (TxSROM).
+    Mapped([u8; 4]),
+}
+
+/// The kind of machine the ROM was made for, from byte 7 of the header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleType {
+    Nes,
+    /// A Vs. System arcade board. The PPU and hardware types are the numbers from byte 13 of an
+    /// NES 2.0 header, which pick the palette PPU and any copy protection; iNES 1 headers leave
+    /// them both as 0 (RP2C03B, plain Vs. UniSystem).
+    VsSystem {
+        ppu_type: u8,
+        hardware_type: u8,
+    },
+    PlayChoice10,
+    /// One of the NES 2.0 extended console types from the low bits of byte 13.
+    Extended(u8),
 }
 
+#[derive(Clone, PartialEq)]
 pub struct Cartridge {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
+    /// Boards without CHR ROM have 8KB of CHR RAM instead, and a few (TQROM) have both.
+    pub chr_ram: Vec<u8>,
     pub mapper: Mapper,
     pub mirroring_type: Mirroring,
+    pub console_type: ConsoleType,
+    /// The work RAM at $6000-$7fff, which holds the save when the cartridge has a battery.
+    pub prg_ram: Vec<u8>,
+    pub battery: bool,
+    /// The parts of RAM the battery keeps, in save file order. iNES headers can only say that
+    /// PRG RAM is kept; NES 2.0 ones can add CHR RAM.
+    pub save_regions: Vec<SaveRegion>,
+    /// CHR tiles written since `take_dirty_tiles` was last called.
+    chr_dirty: DirtyTiles,
 }
 
-mod mapper;
+pub mod archive;
+pub mod chr;
+pub mod datach;
+pub mod header;
+pub mod info;
+pub mod loader;
+pub mod mapper;
+pub mod mmc3;
+pub mod profile;
+pub mod save;
+pub mod vrc6;
 
 impl Cartridge {
-    pub fn new(raw: &Vec<u8>) -> Self {
+    pub fn new(raw: &[u8]) -> Result<Self, NesError> {
+        if raw.len() < HEADER_SIZE || raw[0..4] != NES_TAG {
+            return Err(NesError::new("File is not in iNES format"));
+        }
+
         let control_byte_6 = raw[6];
         let control_byte_7 = raw[7];
 
         let mapper_type = (control_byte_7 & 0b1111_0000) | (control_byte_6 >> 4);
 
-        let ines_version: u8;
-
         let ines_byte = (control_byte_7 >> 2) & 0b11;
 
-        match ines_byte {
-            0 => ines_version = 1,
-            0b10 => ines_version = 2,
-            _ => {
-                panic!("Unsupported iNES version.")
-            }
+        if ines_byte != 0 && ines_byte != 0b10 {
+            return Err(NesError::new("Unsupported iNES version."));
         }
 
+        let console_type = if ines_byte == 0b10 {
+            match control_byte_7 & 0b11 {
+                0 => ConsoleType::Nes,
+                1 => ConsoleType::VsSystem {
+                    ppu_type: raw[13] & 0b1111,
+                    hardware_type: raw[13] >> 4,
+                },
+                2 => ConsoleType::PlayChoice10,
+                _ => ConsoleType::Extended(raw[13] & 0b1111),
+            }
+        } else if control_byte_7 & 0b01 != 0 {
+            ConsoleType::VsSystem {
+                ppu_type: 0,
+                hardware_type: 0,
+            }
+        } else if control_byte_7 & 0b10 != 0 {
+            ConsoleType::PlayChoice10
+        } else {
+            ConsoleType::Nes
+        };
+
         let four_screen = (control_byte_6 & 0b1000) != 0;
 
         let vertical_mirroring = (control_byte_6 & 0b1) != 0;
@@ -60,55 +141,233 @@ impl Cartridge {
 
         let skip_trainer = raw[6] & 0b100 != 0;
 
-        let prg_rom_start = 16 + if skip_trainer { 512 } else { 0 };
+        let prg_rom_start = HEADER_SIZE + if skip_trainer { TRAINER_SIZE } else { 0 };
         let chr_rom_start = prg_rom_start + prg_rom_size;
 
+        if prg_rom_pages == 0 {
+            return Err(NesError::new("Cartridge has no PRG ROM"));
+        }
+
+        if raw.len() < chr_rom_start + chr_rom_size {
+            return Err(NesError::new(&format!(
+                "Cartridge is {} bytes but the header describes {}",
+                raw.len(),
+                chr_rom_start + chr_rom_size
+            )));
+        }
+
         let mapper = match mapper_type {
             0 => Mapper::Mapper000 {
                 mirror_bank: prg_rom_pages == 1,
             },
+            4 => Mapper::Mmc3(Mmc3::new(prg_rom_pages, Mmc3Board::Standard)),
+            118 => Mapper::Mmc3(Mmc3::new(prg_rom_pages, Mmc3Board::TxSrom)),
+            119 => Mapper::Mmc3(Mmc3::new(prg_rom_pages, Mmc3Board::Tqrom)),
+            24 | 26 => Mapper::Vrc6(Vrc6::new(prg_rom_pages, mapper_type == 26)),
+            157 => Mapper::Mapper157(Datach::new(prg_rom_pages)),
             _ => {
-                panic!("Mapper {} not defined", mapper_type)
+                return Err(NesError::new(&format!(
+                    "Mapper {} not defined",
+                    mapper_type
+                )))
             }
         };
 
-        Cartridge {
+        let battery = control_byte_6 & 0b10 != 0;
+
+        let save_regions = match (ines_byte, battery) {
+            (0b10, true) => nes2_save_regions(raw),
+            (_, true) => vec![SaveRegion {
+                memory: SaveMemory::PrgRam,
+                size: PRG_RAM_SIZE,
+            }],
+            (_, false) => vec![],
+        };
+
+        let chr_ram_size = save_regions
+            .iter()
+            .filter(|region| region.memory == SaveMemory::ChrRam)
+            .map(|region| region.size)
+            .chain((chr_rom_size == 0 || mapper_type == 119).then_some(CHR_RAM_SIZE))
+            .max()
+            .unwrap_or(0);
+
+        // NES 2.0 headers give the volatile and battery backed PRG RAM sizes separately, and the
+        // board has both. Anything else gets the usual 8KB.
+        let prg_ram_size = match ines_byte {
+            0b10 => nes2_prg_ram_size(raw),
+            _ => 0,
+        }
+        .max(PRG_RAM_SIZE);
+
+        Ok(Cartridge {
             prg_rom: raw[prg_rom_start..(prg_rom_start + prg_rom_size)].to_vec(),
             chr_rom: raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec(),
+            chr_ram: vec![0; chr_ram_size],
             mapper,
             mirroring_type: screen_mirroring,
-        }
+            console_type,
+            prg_ram: vec![0; prg_ram_size],
+            battery,
+            save_regions,
+            chr_dirty: DirtyTiles::new(),
+        })
+    }
+
+    /// Load a ROM file, unpacking it first if it's a .zip or .7z archive (with the `zip` or
+    /// `sevenz` feature).
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, NesError> {
+        let path = path.as_ref();
+        let raw = fs::read(path).map_err(|error| {
+            NesError::new(&format!("Could not read {}: {}", path.display(), error))
+        })?;
+
+        Cartridge::new(&extract_rom(&raw)?)
+    }
+}
+
+/// Only the sizes of the ROM and RAM are shown, not their contents.
+impl fmt::Debug for Cartridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cartridge")
+            .field("prg_rom", &self.prg_rom.len())
+            .field("chr_rom", &self.chr_rom.len())
+            .field("chr_ram", &self.chr_ram.len())
+            .field("mapper", &self.mapper)
+            .field("mirroring_type", &self.mirroring_type)
+            .field("console_type", &self.console_type)
+            .field("prg_ram", &self.prg_ram.len())
+            .field("battery", &self.battery)
+            .field("save_regions", &self.save_regions)
+            .finish()
     }
 }
 
 impl Cartridge {
-    pub fn cpu_write(&mut self, address: u16, data: u8) {
-        let mapper_address = self.mapper.get_pgr_address(address);
-        self.prg_rom[mapper_address as usize] = data;
+    /// A write to $8000-$FFFF, which goes to the mapper's registers. Boards without any reject
+    /// it.
+    pub fn cpu_write(&mut self, address: u16, data: u8) -> Result<(), FaultKind> {
+        if let Some(mirroring) = self.mapper.write(address, data)? {
+            self.mirroring_type = mirroring;
+        }
+        Ok(())
     }
 
     pub fn cpu_read(&self, address: u16) -> u8 {
-        let mapper_address = self.mapper.get_pgr_address(address);
-        self.prg_rom[mapper_address as usize]
+        masked_read(&self.prg_rom, self.mapper.get_pgr_address(address))
+    }
+
+    /// A read from $6000-$7FFF, which is PRG RAM unless the board puts something else there.
+    pub fn prg_ram_read(&self, address: u16) -> u8 {
+        self.mapper
+            .read(address)
+            .unwrap_or_else(|| masked_read(&self.prg_ram, address as usize))
+    }
+
+    /// The Datach barcode reader, if this is a Datach cartridge.
+    pub fn barcode_reader(&mut self) -> Option<&mut BarcodeReader> {
+        match &mut self.mapper {
+            Mapper::Mapper157(datach) => Some(&mut datach.barcode),
+            _ => None,
+        }
+    }
+
+    /// Advance anything on the board that runs by itself by `cycles` CPU cycles.
+    pub fn clock(&mut self, cycles: u64) {
+        if let Mapper::Mapper157(datach) = &mut self.mapper {
+            datach.barcode.clock(cycles);
+        }
+    }
+
+    /// The PRG ROM bank that a CPU address is currently mapped to, counted in the mapper's own
+    /// bank size for that address (see `prg_bank_size`), so MMC3 banks are numbered in 8KB.
+    pub fn prg_bank(&self, address: u16) -> usize {
+        self.mapper.get_pgr_address(address) / self.prg_bank_size(address)
+    }
+
+    /// The size of the banks `prg_bank` counts in at a CPU address.
+    pub fn prg_bank_size(&self, address: u16) -> usize {
+        self.mapper.prg_bank_size(address)
+    }
+
+    /// The mapper's banking state followed by the current mirroring.
+    pub fn debug_state(&self) -> Vec<(&'static str, String)> {
+        let mut state = vec![("mapper", self.mapper.name().to_string())];
+        state.extend(self.mapper.debug_state());
+        state.push(("mirroring", format!("{:?}", self.mirroring_type)));
+        state
+    }
+
+    /// Write the PRG ROM out on its own, without a header.
+    pub fn export_prg<P: AsRef<Path>>(&self, path: P) -> Result<(), NesError> {
+        write_file(path.as_ref(), &self.prg_rom)
     }
 
+    /// Write the CHR ROM out on its own, e.g. for a tile editor. Cartridges with CHR RAM have
+    /// nothing to write and produce an empty file.
+    pub fn export_chr<P: AsRef<Path>>(&self, path: P) -> Result<(), NesError> {
+        write_file(path.as_ref(), &self.chr_rom)
+    }
+
+    /// A write to the pattern tables, which only lands if it is mapped to CHR RAM.
     pub fn ppu_write(&mut self, address: u16, data: u8) {
-        let mapper_address = self.mapper.get_chr_address(address);
-        self.chr_rom[mapper_address as usize] = data;
+        if let ChrAddress::Ram(address) = self.chr_address(address) {
+            if let Some(byte) = self.chr_ram.get_mut(address) {
+                *byte = data;
+                self.chr_dirty.mark(address);
+            }
+        }
+    }
+
+    /// The CHR RAM tiles that have been written since the last call, for viewers that update
+    /// incrementally as games stream tiles in.
+    pub fn take_dirty_tiles(&mut self) -> Vec<usize> {
+        self.chr_dirty.take()
     }
 
     pub fn ppu_read(&self, address: u16) -> u8 {
-        let mapper_address = self.mapper.get_chr_address(address);
-        self.chr_rom[mapper_address as usize]
+        match self.chr_address(address) {
+            ChrAddress::Rom(address) => masked_read(&self.chr_rom, address),
+            ChrAddress::Ram(address) => masked_read(&self.chr_ram, address),
+        }
+    }
+
+    /// The mapper's CHR address, sent to whichever of ROM and RAM the board really has and
+    /// wrapped to its size.
+    fn chr_address(&self, address: u16) -> ChrAddress {
+        let address = match self.mapper.get_chr_address(address) {
+            ChrAddress::Rom(address) if self.chr_rom.is_empty() => ChrAddress::Ram(address),
+            ChrAddress::Ram(address) if self.chr_ram.is_empty() => ChrAddress::Rom(address),
+            address => address,
+        };
+
+        match address {
+            ChrAddress::Rom(address) => ChrAddress::Rom(address % self.chr_rom.len().max(1)),
+            ChrAddress::Ram(address) => ChrAddress::Ram(address % self.chr_ram.len().max(1)),
+        }
     }
 }
 
+/// A byte of ROM or RAM with the address wrapped to its size, like the chip's unconnected upper
+/// address lines would. Empty memory reads as 0.
+fn masked_read(memory: &[u8], address: usize) -> u8 {
+    match memory.len() {
+        0 => 0,
+        length => memory[address % length],
+    }
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), NesError> {
+    fs::write(path, data)
+        .map_err(|error| NesError::new(&format!("Could not write {}: {}", path.display(), error)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[test]
-    fn test_new() {
+    /// An NROM-256 image with vertical mirroring, PRG ROM filled with $01 and CHR ROM with $02.
+    fn nrom_256() -> Vec<u8> {
         let mut contents: Vec<u8> = vec![
             0x4e,
             0x45,
@@ -125,11 +384,87 @@ mod test {
         contents.extend([0; 6]);
         contents.extend([0x01; PRG_ROM_PAGE_SIZE * 2]);
         contents.extend([0x02; CHR_ROM_PAGE_SIZE * 2]);
+        contents
+    }
 
-        let cartridge = Cartridge::new(&contents);
+    #[test]
+    fn test_new() {
+        let cartridge = Cartridge::new(&nrom_256()).unwrap();
 
-        assert_eq!(cartridge.mapper, Mapper::Mapper000 { mirror_bank: true });
+        assert_eq!(cartridge.mapper, Mapper::Mapper000 { mirror_bank: false });
         assert_eq!(cartridge.prg_rom, [0x01; PRG_ROM_PAGE_SIZE * 2]);
         assert_eq!(cartridge.chr_rom, [0x02; CHR_ROM_PAGE_SIZE * 2]);
+        assert_eq!(cartridge.console_type, ConsoleType::Nes);
+    }
+
+    #[test]
+    fn test_export_chr() {
+        let cartridge = Cartridge::new(&nrom_256()).unwrap();
+
+        // Unique to this process, so parallel runs of the suite don't share the file
+        let chr_path = std::env::temp_dir().join(format!(
+            "nes_emulator_test_export_chr_{}.chr",
+            std::process::id()
+        ));
+        cartridge.export_chr(&chr_path).unwrap();
+        let exported = fs::read(&chr_path);
+        fs::remove_file(&chr_path).unwrap();
+
+        assert_eq!(exported.unwrap(), cartridge.chr_rom);
+    }
+
+    #[test]
+    fn test_debug_state() {
+        let cartridge = Cartridge::new(&nrom_256()).unwrap();
+
+        assert_eq!(
+            cartridge.debug_state(),
+            [
+                ("mapper", "NROM".to_string()),
+                ("prg_banks", "0, 1".to_string()),
+                ("chr_bank", "0".to_string()),
+                ("mirroring", "Vertical".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_console_type() {
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0b0000_1001];
+        contents.extend([0, 0, 0, 0, 0, 0x12, 0, 0]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        let cartridge = Cartridge::new(&contents).unwrap();
+
+        assert_eq!(
+            cartridge.console_type,
+            ConsoleType::VsSystem {
+                ppu_type: 2,
+                hardware_type: 1
+            }
+        );
+
+        contents[7] = 0b0000_0010;
+
+        let cartridge = Cartridge::new(&contents).unwrap();
+
+        assert_eq!(cartridge.console_type, ConsoleType::PlayChoice10);
+    }
+
+    #[test]
+    fn test_new_rejects_malformed() {
+        assert!(Cartridge::new(&[]).is_err());
+        assert!(Cartridge::new(&[0x4e, 0x45, 0x53]).is_err());
+        assert!(Cartridge::new(&[0; 16]).is_err());
+
+        // A valid header claiming one PRG page, but with no data after it.
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        contents.extend([0; 8]);
+
+        assert!(Cartridge::new(&contents).is_err());
+
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        assert!(Cartridge::new(&contents).is_ok());
     }
 }
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::errors::NesError;
+use crate::hash::crc32;
+
+/// Pixels to hide around the edge of the picture, where many games draw garbage.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Overscan {
+    pub top: u8,
+    pub bottom: u8,
+    pub left: u8,
+    pub right: u8,
+}
+
+/// What a game expects in controller port 2 (or the expansion port) to be playable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControllerType {
+    Joypad,
+    Paddle,
+    PowerPad,
+    FamilyBasicKeyboard,
+}
+
+/// Per title quirks. Anything left as None means "use the header / frontend default".
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GameProfile {
+    pub crop: Option<Overscan>,
+    pub mirroring: Option<Mirroring>,
+    /// The iNES mapper number, for dumps whose header has the wrong one. The board is built when
+    /// the header is parsed, so this only takes effect through `header::repair`.
+    pub mapper: Option<u8>,
+    pub controller: Option<ControllerType>,
+}
+
+impl GameProfile {
+    /// This profile with anything set in `overrides` taking priority.
+    pub fn with_overrides(&self, overrides: &GameProfile) -> GameProfile {
+        GameProfile {
+            crop: overrides.crop.or(self.crop),
+            mirroring: overrides.mirroring.or(self.mirroring),
+            mapper: overrides.mapper.or(self.mapper),
+            controller: overrides.controller.or(self.controller),
+        }
+    }
+
+    /// Apply the parts of the profile the cartridge is responsible for. Crop and controller are up
+    /// to the frontend.
+    pub fn apply(&self, cartridge: &mut Cartridge) {
+        if let Some(mirroring) = self.mirroring {
+            cartridge.mirroring_type = mirroring;
+        }
+    }
+}
+
+/// Profiles keyed by the CRC-32 of a cartridge's PRG and CHR ROM, as in most ROM databases.
+///
+/// Profiles are written one game per line, as the CRC followed by any of the settings:
+/// ```text
+/// 1A2B3C4D crop=8,8,0,0 mirroring=four-screen mapper=4 controller=paddle   # comment
+/// ```
+/// The same format works for a shipped database and for a user's own overrides, which are layered
+/// on top with `profile_for`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProfileDatabase {
+    profiles: HashMap<u32, GameProfile>,
+}
+
+impl ProfileDatabase {
+    pub fn new() -> Self {
+        ProfileDatabase::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, NesError> {
+        let mut database = ProfileDatabase::new();
+
+        for line in text.lines() {
+            let mut fields = line.split('#').next().unwrap_or("").split_whitespace();
+
+            let Some(crc) = fields.next() else {
+                continue;
+            };
+
+            let crc = u32::from_str_radix(crc, 16)
+                .map_err(|_| NesError::new(&format!("Invalid CRC {}", crc)))?;
+
+            let mut profile = GameProfile::default();
+
+            for field in fields {
+                parse_setting(&mut profile, field)?;
+            }
+
+            database.insert(crc, profile);
+        }
+
+        Ok(database)
+    }
+
+    pub fn insert(&mut self, crc: u32, profile: GameProfile) {
+        self.profiles.insert(crc, profile);
+    }
+
+    pub fn lookup(&self, cartridge: &Cartridge) -> Option<&GameProfile> {
+        self.profiles.get(&rom_crc(cartridge))
+    }
+}
+
+/// The profile for a cartridge: the database's entry with the user's overrides on top.
+pub fn profile_for(
+    cartridge: &Cartridge,
+    database: &ProfileDatabase,
+    overrides: &ProfileDatabase,
+) -> GameProfile {
+    let profile = database.lookup(cartridge).copied().unwrap_or_default();
+
+    match overrides.lookup(cartridge) {
+        Some(overrides) => profile.with_overrides(overrides),
+        None => profile,
+    }
+}
+
+fn rom_crc(cartridge: &Cartridge) -> u32 {
+    let mut rom = cartridge.prg_rom.clone();
+    rom.extend(&cartridge.chr_rom);
+    crc32(&rom)
+}
+
+fn parse_setting(profile: &mut GameProfile, field: &str) -> Result<(), NesError> {
+    let invalid = || NesError::new(&format!("Invalid profile setting {}", field));
+
+    let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+
+    match key {
+        "crop" => {
+            let sides: Vec<u8> = value
+                .split(',')
+                .map(|side| side.parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?;
+
+            let [top, bottom, left, right] = sides[..] else {
+                return Err(invalid());
+            };
+
+            profile.crop = Some(Overscan {
+                top,
+                bottom,
+                left,
+                right,
+            });
+        }
+        "mirroring" => {
+            profile.mirroring = Some(match value {
+                "horizontal" => Mirroring::Horizontal,
+                "vertical" => Mirroring::Vertical,
+                "four-screen" => Mirroring::FourScreen,
+                _ => return Err(invalid()),
+            });
+        }
+        "mapper" => {
+            profile.mapper = Some(value.parse().map_err(|_| invalid())?);
+        }
+        "controller" => {
+            profile.controller = Some(match value {
+                "joypad" => ControllerType::Joypad,
+                "paddle" => ControllerType::Paddle,
+                "power-pad" => ControllerType::PowerPad,
+                "keyboard" => ControllerType::FamilyBasicKeyboard,
+                _ => return Err(invalid()),
+            });
+        }
+        _ => return Err(invalid()),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::PRG_ROM_PAGE_SIZE;
+
+    #[test]
+    fn test_profile_for() {
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        contents.extend([0; 8]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        let mut cartridge = Cartridge::new(&contents).unwrap();
+        let crc = rom_crc(&cartridge);
+
+        let database = ProfileDatabase::parse(&format!(
+            "# shipped\n{:08X} crop=8,8,0,0 mirroring=four-screen mapper=4\n",
+            crc
+        ))
+        .unwrap();
+        let overrides =
+            ProfileDatabase::parse(&format!("{:08x} crop=0,0,0,0 controller=paddle", crc)).unwrap();
+
+        let profile = profile_for(&cartridge, &database, &overrides);
+
+        assert_eq!(profile.crop, Some(Overscan::default()));
+        assert_eq!(profile.mirroring, Some(Mirroring::FourScreen));
+        assert_eq!(profile.mapper, Some(4));
+        assert_eq!(profile.controller, Some(ControllerType::Paddle));
+
+        profile.apply(&mut cartridge);
+        assert_eq!(cartridge.mirroring_type, Mirroring::FourScreen);
+
+        assert!(ProfileDatabase::parse("1234 crop=1,2").is_err());
+        assert!(ProfileDatabase::parse("xyz").is_err());
+        assert!(ProfileDatabase::parse("1234 mapper=256").is_err());
+    }
+}
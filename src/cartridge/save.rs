@@ -0,0 +1,147 @@
+use crate::cartridge::Cartridge;
+
+/// The cartridge memory a save region is kept in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveMemory {
+    PrgRam,
+    ChrRam,
+}
+
+/// A battery backed part of the cartridge's RAM. A save file is each region's bytes in turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaveRegion {
+    pub memory: SaveMemory,
+    pub size: usize,
+}
+
+/// The save regions an NES 2.0 header describes, from the PRG and CHR NVRAM shift counts in the
+/// high nibbles of bytes 10 and 11.
+pub fn nes2_save_regions(raw: &[u8]) -> Vec<SaveRegion> {
+    [(SaveMemory::PrgRam, raw[10]), (SaveMemory::ChrRam, raw[11])]
+        .into_iter()
+        .filter_map(|(memory, byte)| {
+            let size = nes2_ram_size(byte >> 4);
+            (size != 0).then_some(SaveRegion { memory, size })
+        })
+        .collect()
+}
+
+/// All the PRG RAM an NES 2.0 header describes: the volatile RAM in the low nibble of byte 10 and
+/// the NVRAM in the high nibble.
+pub fn nes2_prg_ram_size(raw: &[u8]) -> usize {
+    nes2_ram_size(raw[10] & 0b1111) + nes2_ram_size(raw[10] >> 4)
+}
+
+/// The RAM size an NES 2.0 shift count stands for.
+fn nes2_ram_size(shift: u8) -> usize {
+    match shift {
+        0 => 0,
+        shift => 64 << shift,
+    }
+}
+
+/// The shift count an NES 2.0 header uses for a RAM size, or 0 for none.
+pub fn nes2_shift_count(size: usize) -> u8 {
+    match size {
+        0 => 0,
+        size => (size.max(128).next_power_of_two().trailing_zeros() - 6) as u8,
+    }
+}
+
+impl Cartridge {
+    /// The contents of one save region. Regions larger than their memory are cut short.
+    pub fn save_region_data(&self, region: SaveRegion) -> &[u8] {
+        let memory = match region.memory {
+            SaveMemory::PrgRam => &self.prg_ram,
+            SaveMemory::ChrRam => &self.chr_ram,
+        };
+
+        &memory[..region.size.min(memory.len())]
+    }
+
+    /// Everything that should go in the save file, or nothing if the cartridge has no battery.
+    pub fn save_data(&self) -> Vec<u8> {
+        self.save_regions
+            .iter()
+            .flat_map(|region| self.save_region_data(*region))
+            .copied()
+            .collect()
+    }
+
+    /// The total size of a save file for this cartridge.
+    pub fn save_size(&self) -> usize {
+        self.save_regions.iter().map(|region| region.size).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::CpuBus;
+    use crate::cartridge::{CHR_RAM_SIZE, CHR_ROM_PAGE_SIZE, PRG_RAM_SIZE, PRG_ROM_PAGE_SIZE};
+    use crate::memory::Mem;
+
+    #[test]
+    fn test_save_regions() {
+        // NES 2.0, battery, no CHR ROM, 8KB PRG NVRAM and 8KB CHR NVRAM
+        let mut contents = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0b10, 0b1000];
+        contents.extend([0, 0, 0x70, 0x70, 0, 0, 0, 0]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        let cartridge = Cartridge::new(&contents).unwrap();
+
+        assert_eq!(
+            cartridge.save_regions,
+            [
+                SaveRegion {
+                    memory: SaveMemory::PrgRam,
+                    size: PRG_RAM_SIZE
+                },
+                SaveRegion {
+                    memory: SaveMemory::ChrRam,
+                    size: CHR_RAM_SIZE
+                },
+            ]
+        );
+
+        let mut bus = CpuBus::new(cartridge);
+        let mut save = vec![0x11; PRG_RAM_SIZE];
+        save.extend([0x22; CHR_RAM_SIZE]);
+
+        assert!(bus.load_save(&save[1..]).is_err());
+        bus.load_save(&save).unwrap();
+
+        assert_eq!(bus.mem_read(0x6000).unwrap(), 0x11);
+        assert_eq!(bus.cartridge.ppu_read(0x1fff), 0x22);
+        assert_eq!(bus.cartridge.save_data(), save);
+    }
+
+    #[test]
+    fn test_large_prg_nvram() {
+        // NES 2.0, battery, 32KB PRG NVRAM
+        let mut contents = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x01, 0b10, 0b1000];
+        contents.extend([0, 0, 0x90, 0, 0, 0, 0, 0]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+        contents.extend([0; CHR_ROM_PAGE_SIZE]);
+
+        let cartridge = Cartridge::new(&contents).unwrap();
+        assert_eq!(cartridge.prg_ram.len(), 0x8000);
+        assert_eq!(cartridge.save_size(), 0x8000);
+
+        let mut bus = CpuBus::new(cartridge);
+        let save: Vec<u8> = (0..0x8000)
+            .map(|index| (index / PRG_RAM_SIZE) as u8)
+            .collect();
+
+        bus.load_save(&save).unwrap();
+
+        assert_eq!(bus.cartridge.save_data(), save);
+    }
+
+    #[test]
+    fn test_nes2_shift_count() {
+        assert_eq!(nes2_shift_count(0), 0);
+        assert_eq!(nes2_shift_count(PRG_RAM_SIZE), 7);
+        assert_eq!(nes2_shift_count(100), 1);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::cartridge::{Mirroring, PRG_ROM_PAGE_SIZE};
+
+const PRG_RAM_ENABLE: u8 = 0b1000_0000;
+
+/// Konami's VRC6: a 16KB PRG bank at $8000, an 8KB bank at $C000, the last 8KB fixed at $E000 and
+/// eight 1KB CHR banks.
+///
+/// The two boards that use it wire the register select lines the other way round: VRC6a (mapper
+/// 24, Akumajou Densetsu) connects CPU A0 and A1 to the chip's A0 and A1, while VRC6b (mapper 26,
+/// Madara and Esper Dream 2) swaps them. Both are this one mapper with `swap_address_lines` set
+/// for VRC6b.
+///
+/// Only the normal CHR/nametable mode of $B003 is handled. The expansion audio and IRQ registers
+/// are accepted and ignored, as there is no APU and the IRQ counter isn't emulated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vrc6 {
+    pub swap_address_lines: bool,
+    prg_banks: usize,
+    prg_bank_16k: usize,
+    prg_bank_8k: usize,
+    chr_banks: [u8; 8],
+    ppu_banking: u8,
+}
+
+impl Vrc6 {
+    pub fn new(prg_banks: usize, swap_address_lines: bool) -> Self {
+        Vrc6 {
+            swap_address_lines,
+            prg_banks: prg_banks.max(1),
+            prg_bank_16k: 0,
+            prg_bank_8k: 0,
+            chr_banks: [0; 8],
+            ppu_banking: 0,
+        }
+    }
+
+    /// The board's iNES mapper number, which is how its wiring is told apart.
+    pub fn number(&self) -> u16 {
+        if self.swap_address_lines {
+            26
+        } else {
+            24
+        }
+    }
+
+    /// The registers as bytes, for save states.
+    pub(crate) fn state(&self) -> Vec<u8> {
+        let mut state = vec![self.prg_bank_16k as u8, self.prg_bank_8k as u8];
+        state.extend(self.chr_banks);
+        state.push(self.ppu_banking);
+        state
+    }
+
+    /// Put back registers saved by `state`. The caller checks the length.
+    pub(crate) fn restore(&mut self, state: &[u8]) {
+        self.prg_bank_16k = state[0] as usize;
+        self.prg_bank_8k = state[1] as usize;
+        self.chr_banks.copy_from_slice(&state[2..10]);
+        self.ppu_banking = state[10];
+    }
+
+    pub fn prg_banks(&self) -> (usize, usize) {
+        (self.prg_bank_16k, self.prg_bank_8k)
+    }
+
+    pub fn chr_banks(&self) -> [u8; 8] {
+        self.chr_banks
+    }
+
+    pub fn prg_ram_enabled(&self) -> bool {
+        self.ppu_banking & PRG_RAM_ENABLE != 0
+    }
+
+    pub fn prg_address(&self, address: u16) -> usize {
+        let offset = address as usize & 0x1fff;
+        let banks_8k = self.prg_banks * 2;
+
+        let bank_8k = match address {
+            0x8000..=0xbfff => self.prg_bank_16k * 2 + ((address as usize >> 13) & 1),
+            0xc000..=0xdfff => self.prg_bank_8k,
+            _ => banks_8k - 1,
+        };
+
+        (bank_8k % banks_8k) * PRG_ROM_PAGE_SIZE / 2 + offset
+    }
+
+    pub fn chr_address(&self, address: u16) -> usize {
+        let bank = self.chr_banks[(address as usize >> 10) & 0b111] as usize;
+        bank * 0x400 + (address as usize & 0x3ff)
+    }
+
+    /// A write to $8000-$FFFF, returning the new mirroring if it changed.
+    pub fn write(&mut self, address: u16, data: u8) -> Option<Mirroring> {
+        let register = (address & 0xf000) | self.register_select(address);
+
+        match register {
+            0x8000..=0x8003 => self.prg_bank_16k = (data & 0x0f) as usize,
+            0xb003 => {
+                self.ppu_banking = data;
+
+                return Some(match (data >> 2) & 0b11 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenLower,
+                    _ => Mirroring::SingleScreenUpper,
+                });
+            }
+            0xc000..=0xc003 => self.prg_bank_8k = (data & 0x1f) as usize,
+            0xd000..=0xd003 => self.chr_banks[(register & 0b11) as usize] = data,
+            0xe000..=0xe003 => self.chr_banks[4 + (register & 0b11) as usize] = data,
+            // Expansion audio at $9000-$B002 and the IRQ at $F000-$F002
+            _ => {}
+        }
+
+        None
+    }
+
+    /// The part of the chip a write to `address` drives that isn't emulated, if any.
+    pub fn unsupported_feature(&self, address: u16, data: u8) -> Option<&'static str> {
+        match (address & 0xf000) | self.register_select(address) {
+            0x9000..=0x9003 | 0xa000..=0xa002 | 0xb000..=0xb002 => Some("expansion audio"),
+            0xf001 if data & 0b10 != 0 => Some("IRQ counter"),
+            _ => None,
+        }
+    }
+
+    /// The chip's A1 and A0 for a CPU address, after the board's wiring.
+    fn register_select(&self, address: u16) -> u16 {
+        let a0 = address & 0b01;
+        let a1 = (address >> 1) & 0b01;
+
+        if self.swap_address_lines {
+            (a0 << 1) | a1
+        } else {
+            (a1 << 1) | a0
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_register_select() {
+        for (swap_address_lines, register_address) in [(false, 0xd001), (true, 0xd002)] {
+            let mut vrc6 = Vrc6::new(8, swap_address_lines);
+
+            vrc6.write(register_address, 0x15);
+            assert_eq!(vrc6.chr_banks()[1], 0x15);
+
+            // $B003 is the same address on both boards
+            assert_eq!(vrc6.write(0xb003, 0b1000_0100), Some(Mirroring::Horizontal));
+            assert!(vrc6.prg_ram_enabled());
+        }
+    }
+
+    #[test]
+    fn test_banking() {
+        let mut vrc6 = Vrc6::new(8, false);
+
+        vrc6.write(0x8000, 0x02);
+        vrc6.write(0xc000, 0x09);
+
+        assert_eq!(vrc6.prg_address(0x8000), 2 * PRG_ROM_PAGE_SIZE);
+        assert_eq!(vrc6.prg_address(0xa001), 2 * PRG_ROM_PAGE_SIZE + 0x2001);
+        assert_eq!(vrc6.prg_address(0xc000), 9 * 0x2000);
+        assert_eq!(vrc6.prg_address(0xffff), 8 * PRG_ROM_PAGE_SIZE - 1);
+
+        vrc6.write(0xe003, 0x20);
+        assert_eq!(vrc6.chr_address(0x1c05), 0x20 * 0x400 + 5);
+    }
+}
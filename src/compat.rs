@@ -0,0 +1,225 @@
+use crate::bus::{BusFault, CpuBus, UnsupportedFeature};
+use crate::cartridge::Cartridge;
+use crate::debugger::mmio::Access;
+use crate::errors::NesError;
+use crate::hash::crc32;
+use crate::json;
+use crate::memory::Mem;
+use crate::opcodes::OpCode;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueKind {
+    UnsupportedMapperFeature,
+    /// A read or write the bus had nothing mapped for.
+    UnmappedRegister,
+    IllegalOpcode,
+}
+
+impl IssueKind {
+    fn name(&self) -> &'static str {
+        match self {
+            IssueKind::UnsupportedMapperFeature => "unsupported_mapper_feature",
+            IssueKind::UnmappedRegister => "unmapped_register",
+            IssueKind::IllegalOpcode => "illegal_opcode",
+        }
+    }
+}
+
+/// Something that went wrong while playing a game, and how often.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub address: Option<u16>,
+    pub detail: String,
+    pub count: u64,
+}
+
+/// The problems one ROM ran into during a session, keyed by the ROM's CRC-32 so that reports from
+/// different players can be collected into a compatibility list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatReport {
+    pub rom_crc32: u32,
+    pub mapper: u16,
+    issues: Vec<Issue>,
+}
+
+impl CompatReport {
+    pub fn new(cartridge: &Cartridge) -> Self {
+        let mut rom = cartridge.prg_rom.clone();
+        rom.extend(&cartridge.chr_rom);
+
+        CompatReport {
+            rom_crc32: crc32(&rom),
+            mapper: cartridge.mapper.number(),
+            issues: vec![],
+        }
+    }
+
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// Record an issue. Repeats of the same kind at the same address are counted rather than
+    /// listed again.
+    pub fn record(&mut self, kind: IssueKind, address: Option<u16>, detail: &str) {
+        let existing = self
+            .issues
+            .iter_mut()
+            .find(|issue| issue.kind == kind && issue.address == address);
+
+        match existing {
+            Some(issue) => issue.count += 1,
+            None => self.issues.push(Issue {
+                kind,
+                address,
+                detail: detail.to_string(),
+                count: 1,
+            }),
+        }
+    }
+
+    /// Collect the faults the bus has recorded while running with `ErrorPolicy::Continue`, and
+    /// the writes to mapper features that aren't emulated.
+    pub fn record_faults(&mut self, bus: &CpuBus) {
+        for UnsupportedFeature { address, feature } in bus.take_unsupported_features() {
+            self.record(
+                IssueKind::UnsupportedMapperFeature,
+                Some(address),
+                &format!("{} {}", bus.cartridge.mapper.name(), feature),
+            );
+        }
+
+        for BusFault {
+            address,
+            access,
+            kind,
+        } in bus.take_faults()
+        {
+            let access = match access {
+                Access::Read => "read",
+                Access::Write => "write",
+            };
+
+            self.record(
+                IssueKind::UnmappedRegister,
+                Some(address),
+                &format!("{}: {}", access, kind),
+            );
+        }
+    }
+
+    /// Record why a run stopped with an error, if it was an opcode the CPU doesn't know.
+    pub fn record_run_error<M: Mem>(&mut self, bus: &M, program_counter: u16, error: &NesError) {
+        if let Ok(code) = bus.mem_peek(program_counter) {
+            if OpCode::from_code(&code).is_err() {
+                self.record(
+                    IssueKind::IllegalOpcode,
+                    Some(program_counter),
+                    &format!("{:02X}: {}", code, error),
+                );
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let issues: Vec<String> = self
+            .issues
+            .iter()
+            .map(|issue| {
+                let address = match issue.address {
+                    Some(address) => address.to_string(),
+                    None => "null".to_string(),
+                };
+
+                format!(
+                    "{{\"kind\":\"{}\",\"address\":{},\"detail\":\"{}\",\"count\":{}}}",
+                    issue.kind.name(),
+                    address,
+                    json::escape(&issue.detail),
+                    issue.count
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"rom_crc32\":\"{:08X}\",\"mapper\":{},\"issues\":[{}]}}",
+            self.rom_crc32,
+            self.mapper,
+            issues.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::ErrorPolicy;
+    use crate::cartridge::PRG_ROM_PAGE_SIZE;
+    use crate::cpu::test::cpu_with_program;
+
+    #[test]
+    fn test_report() {
+        // LDA $2002; LDA $2002; .byte $02
+        let mut cpu = cpu_with_program(&[0xad, 0x02, 0x20, 0xad, 0x02, 0x20, 0x02]);
+        cpu.bus.error_policy = ErrorPolicy::Continue;
+
+        let mut report = CompatReport::new(&cpu.bus.cartridge);
+
+        let error = cpu.run().unwrap_err();
+        report.record_faults(&cpu.bus);
+        report.record_run_error(&cpu.bus, cpu.program_counter, &error);
+
+        assert_eq!(report.issues().len(), 2);
+        assert_eq!(report.issues()[0].count, 2);
+        assert_eq!(
+            report.to_json(),
+            format!(
+                "{{\"rom_crc32\":\"{:08X}\",\"mapper\":0,\"issues\":[{},{}]}}",
+                report.rom_crc32,
+                "{\"kind\":\"unmapped_register\",\"address\":8194,\"detail\":\"read: PPU not implemented yet.\",\"count\":2}",
+                "{\"kind\":\"illegal_opcode\",\"address\":1542,\"detail\":\"02: Unknown OpCode: 2\",\"count\":1}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_unsupported_mapper_feature() {
+        // VRC6a with 64KB of PRG ROM
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x04, 0x00, 0x80, 0x10];
+        contents.extend([0; 8]);
+        contents.extend([0; 4 * PRG_ROM_PAGE_SIZE]);
+
+        let mut bus = CpuBus::new(Cartridge::new(&contents).unwrap());
+        let mut report = CompatReport::new(&bus.cartridge);
+
+        // Pulse 1 volume twice, a PRG bank, then the IRQ turned off and on
+        for (address, data) in [
+            (0x9000, 0x0f),
+            (0x9000, 0x0f),
+            (0x8000, 0x01),
+            (0xf001, 0x00),
+            (0xf001, 0x02),
+        ] {
+            bus.mem_write(address, data).unwrap();
+        }
+        report.record_faults(&bus);
+
+        assert_eq!(
+            report.issues(),
+            [
+                Issue {
+                    kind: IssueKind::UnsupportedMapperFeature,
+                    address: Some(0x9000),
+                    detail: "VRC6a expansion audio".to_string(),
+                    count: 2,
+                },
+                Issue {
+                    kind: IssueKind::UnsupportedMapperFeature,
+                    address: Some(0xf001),
+                    detail: "VRC6a IRQ counter".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use crate::cpu::bus::Bus;
+use crate::cpu::CPU;
+use crate::errors::NesError;
+use crate::opcodes::{AddressingMode, Instruction};
+
+/// How closely the emulator follows the hardware's bus activity, trading speed for accuracy.
+///
+/// None of these change what an instruction computes, only the extra reads and writes the real
+/// 6502 makes along the way, which matter when they land on registers with side effects.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Accuracy {
+    /// Only the reads and writes an instruction needs for its result.
+    Fast,
+    /// Read-modify-write instructions (ASL, INC, DCP and the rest) write the unmodified value back
+    /// before the result, so the bus sees two writes to the same address. Mappers can tell: MMC1
+    /// ignores the second of two writes on back to back cycles, and games reset it that way. This
+    /// is the default as it only costs one extra write per read-modify-write instruction.
+    #[default]
+    Balanced,
+    /// Everything from `Balanced` plus the dummy reads indexed addressing modes make from the
+    /// address before the carry into the high byte is fixed up.
+    Cycle,
+}
+
+impl<B: Bus> CPU<B> {
+    /// Write the result of a read-modify-write instruction back to memory.
+    pub(crate) fn write_modified(
+        &mut self,
+        address: u16,
+        original: u8,
+        result: u8,
+    ) -> Result<(), NesError> {
+        if self.accuracy != Accuracy::Fast {
+            self.bus.mem_write(address, original)?;
+        }
+
+        self.bus.mem_write(address, result)
+    }
+
+    /// When indexing carries into the high byte of an address the 6502 first reads from the
+    /// address before the carry is fixed up. Stores and read-modify-write instructions always make
+    /// that read, while plain reads only make it when the page is crossed.
+    ///
+    /// The value is thrown away, so only the read's side effects matter. An unmapped address just
+    /// gives open bus on hardware, so an error from the read is ignored too.
+    pub(crate) fn dummy_read(
+        &self,
+        instruction: &Instruction,
+        mode: &AddressingMode,
+    ) -> Result<(), NesError> {
+        if self.accuracy != Accuracy::Cycle {
+            return Ok(());
+        }
+
+        let program_counter = self.program_counter.wrapping_add(1);
+
+        let (base, index) = match mode {
+            AddressingMode::AbsoluteX => (self.bus.mem_read_u16(program_counter)?, self.register_x),
+            AddressingMode::AbsoluteY => (self.bus.mem_read_u16(program_counter)?, self.register_y),
+            AddressingMode::IndirectY => {
+                let pointer = self.bus.mem_read(program_counter)?;
+                (self.read_zero_page_pointer(pointer)?, self.register_y)
+            }
+            _ => return Ok(()),
+        };
+
+        let address = base.wrapping_add(index as u16);
+        let uncorrected_address = (base & 0xff00) | (address & 0x00ff);
+
+        if writes_operand(instruction) || uncorrected_address != address {
+            let _ = self.bus.mem_read(uncorrected_address);
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores and read-modify-write instructions, which can't write until the address is fixed up, so
+/// always spend the extra cycle on indexing that plain reads only spend when crossing a page.
+pub(crate) fn writes_operand(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::STA
+            | Instruction::STX
+            | Instruction::STY
+            | Instruction::ASL
+            | Instruction::LSR
+            | Instruction::ROL
+            | Instruction::ROR
+            | Instruction::INC
+            | Instruction::DEC
+            | Instruction::SAX
+            | Instruction::DCP
+            | Instruction::ISB
+            | Instruction::SLO
+            | Instruction::RLA
+            | Instruction::SRE
+            | Instruction::RRA
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::debugger::mmio::MmioLogger;
+    use crate::opcodes::{OpCode, OpCodeDetail};
+
+    #[test]
+    fn test_dummy_read_on_page_cross() {
+        // LDA $00F0,X reads $0110, but first reads $0010 before fixing up the carry.
+        let mut cpu = cpu_with_program(&[0xbd, 0xf0, 0x00]);
+        cpu.register_x = 0x20;
+
+        let reads = Rc::new(RefCell::new(vec![]));
+        let recorded = reads.clone();
+        let mut logger = MmioLogger::new(Box::new(move |event| {
+            recorded.borrow_mut().push(event.address)
+        }));
+        logger.add_range(0x0000, 0x01ff);
+        cpu.bus.enable_mmio_log(logger);
+
+        let opcode = OpCodeDetail::from_opcode(&OpCode::Xbd);
+
+        cpu.accuracy = Accuracy::Balanced;
+        cpu.run_opcode(&opcode).unwrap();
+        assert_eq!(*reads.borrow(), [0x0110]);
+
+        reads.borrow_mut().clear();
+        cpu.program_counter = 0x0600;
+        cpu.accuracy = Accuracy::Cycle;
+        cpu.run_opcode(&opcode).unwrap();
+        assert_eq!(*reads.borrow(), [0x0010, 0x0110]);
+    }
+
+    #[test]
+    fn test_dummy_read_of_unmapped_address() {
+        // LDA $5FF0,X crosses into PRG RAM, but first reads the unmapped $5F10.
+        let mut cpu = cpu_with_program(&[0xbd, 0xf0, 0x5f]);
+        cpu.register_x = 0x20;
+        cpu.accuracy = Accuracy::Cycle;
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::Xbd))
+            .unwrap();
+
+        assert_eq!(cpu.program_counter, 0x0603);
+    }
+
+    #[test]
+    fn test_no_dummy_read_without_page_cross() {
+        // LDA $0010,X
+        let mut cpu = cpu_with_program(&[0xbd, 0x10, 0x00]);
+        cpu.register_x = 0x01;
+        cpu.accuracy = Accuracy::Cycle;
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::Xbd))
+            .unwrap();
+
+        assert_eq!(cpu.program_counter, 0x0603);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::bus::{CpuBus, MemoryRegion};
+use crate::cartridge::Cartridge;
+use crate::cpu::CPU;
+use crate::errors::NesError;
+use crate::memory::Mem;
+
+const RESET_VECTOR: u16 = 0xfffc;
+const FALLBACK_START: u16 = 0x8000;
+
+impl CPU<CpuBus> {
+    /// Reset, but only after checking the reset vector points into PRG ROM. A vector of $0000 or
+    /// one into RAM or the registers usually means a broken homebrew build or a misread header,
+    /// and running it just fails later with a read error that says nothing about why.
+    ///
+    /// A bad vector is an error describing the cartridge, unless `fall_back` is set, in which case
+    /// the CPU starts at $8000 instead and the same description comes back as a warning.
+    pub fn boot(&mut self, fall_back: bool) -> Result<Option<NesError>, NesError> {
+        let vector = self.bus.mem_peek_u16(RESET_VECTOR)?;
+
+        let Some(problem) = reset_vector_problem(self.bus.region_of(vector)) else {
+            self.reset()?;
+            return Ok(None);
+        };
+
+        let diagnostic = NesError::new(&format!(
+            "Reset vector ${:04X} points {}. The cartridge is {}",
+            vector,
+            problem,
+            header_summary(&self.bus.cartridge)
+        ));
+
+        if !fall_back {
+            return Err(diagnostic);
+        }
+
+        self.reset()?;
+        self.program_counter = FALLBACK_START;
+
+        Ok(Some(diagnostic))
+    }
+}
+
+/// Why code can't start in `region`, or None if it can.
+fn reset_vector_problem(region: MemoryRegion) -> Option<&'static str> {
+    match region {
+        MemoryRegion::PrgRom { .. } => None,
+        MemoryRegion::InternalRam => Some("into internal RAM, which holds nothing at power on"),
+        MemoryRegion::PrgRam => Some("into PRG RAM, which holds nothing at power on"),
+        MemoryRegion::PpuRegister(_) | MemoryRegion::ApuIo(_) => Some("at a hardware register"),
+        MemoryRegion::OpenBus => Some("into unmapped space"),
+    }
+}
+
+fn header_summary(cartridge: &Cartridge) -> String {
+    format!(
+        "mapper {} ({}) with {} KB of PRG ROM and {} KB of CHR ROM, {:?} mirroring.",
+        cartridge.mapper.number(),
+        cartridge.mapper.name(),
+        cartridge.prg_rom.len() / 1024,
+        cartridge.chr_rom.len() / 1024,
+        cartridge.mirroring_type
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::PRG_ROM_PAGE_SIZE;
+
+    fn cpu_with_reset_vector(vector: u16) -> CPU {
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        contents.extend([0; 8]);
+
+        let mut prg_rom = [0; PRG_ROM_PAGE_SIZE];
+        prg_rom[0x3ffc..].copy_from_slice(&[vector as u8, (vector >> 8) as u8, 0, 0]);
+        contents.extend(prg_rom);
+
+        CPU::new(CpuBus::new(Cartridge::new(&contents).unwrap()))
+    }
+
+    #[test]
+    fn test_good_reset_vector() {
+        let mut cpu = cpu_with_reset_vector(0xc004);
+
+        assert!(cpu.boot(false).unwrap().is_none());
+        assert_eq!(cpu.program_counter, 0xc004);
+    }
+
+    #[test]
+    fn test_bad_reset_vector() {
+        let mut cpu = cpu_with_reset_vector(0x0000);
+
+        assert_eq!(
+            cpu.boot(false).unwrap_err().message,
+            "Reset vector $0000 points into internal RAM, which holds nothing at power on. The \
+             cartridge is mapper 0 (NROM) with 16 KB of PRG ROM and 0 KB of CHR ROM, Horizontal \
+             mirroring."
+        );
+
+        assert!(cpu.boot(true).unwrap().is_some());
+        assert_eq!(cpu.program_counter, 0x8000);
+    }
+}
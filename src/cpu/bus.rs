@@ -0,0 +1,31 @@
+use crate::cpu::interrupts::IrqSource;
+use crate::cpu::WatchHit;
+use crate::memory::{Mem, RAM};
+
+/// What the CPU needs from the system it's in besides memory: its interrupt lines, and hooks for
+/// debuggers. Every method has a default, so a bus that is only memory needs nothing more than an
+/// empty `impl`.
+pub trait Bus: Mem {
+    /// Called by the CPU before each instruction, for buses that want to know what is making
+    /// their accesses or need to keep in step with the CPU clock.
+    fn begin_instruction(&mut self, _program_counter: u16, _instruction: u64, _cycle: u64) {}
+
+    /// Whether something on the bus, like the PPU entering VBlank, has asked for a non-maskable
+    /// interrupt since the last call.
+    fn take_nmi(&mut self) -> bool {
+        false
+    }
+
+    /// Which device is holding the IRQ line, if any. Unlike NMI the line is level triggered, so
+    /// this keeps answering until the device is acknowledged.
+    fn irq_source(&self) -> Option<IrqSource> {
+        None
+    }
+
+    /// The first watched write since the last call, for buses that support watchpoints.
+    fn take_watch_hit(&mut self) -> Option<WatchHit> {
+        None
+    }
+}
+
+impl Bus for RAM {}
@@ -0,0 +1,85 @@
+use crate::cpu::CPU;
+use crate::memory::Mem;
+use crate::opcodes::{OpCode, OpCodeDetail, OPCODES};
+
+/// Counts how often each opcode runs, to find the parts of the opcode table that no test or test
+/// ROM exercises.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpcodeCoverage {
+    counts: [u64; 256],
+}
+
+impl Default for OpcodeCoverage {
+    fn default() -> Self {
+        OpcodeCoverage { counts: [0; 256] }
+    }
+}
+
+impl OpcodeCoverage {
+    pub fn new() -> Self {
+        OpcodeCoverage::default()
+    }
+
+    pub fn record(&mut self, code: u8) {
+        self.counts[code as usize] += 1;
+    }
+
+    /// Record the instruction the CPU is about to run, e.g. from a `run_with_callback` callback.
+    pub fn record_cpu<B: Mem>(&mut self, cpu: &CPU<B>) {
+        if let Ok(code) = cpu.bus.mem_peek(cpu.program_counter) {
+            self.record(code);
+        }
+    }
+
+    pub fn count(&self, code: u8) -> u64 {
+        self.counts[code as usize]
+    }
+
+    /// The opcodes in the table that never ran.
+    pub fn missing(&self) -> Vec<u8> {
+        OPCODES
+            .iter()
+            .copied()
+            .filter(|code| self.count(*code) == 0)
+            .collect()
+    }
+
+    /// One line per missing opcode, e.g. `58 CLI Implied`.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+
+        for code in self.missing() {
+            if let Ok(opcode) = OpCode::from_code(&code) {
+                let detail = OpCodeDetail::from_opcode(&opcode);
+                report.push_str(&format!(
+                    "{:02X} {} {:?}\n",
+                    code,
+                    detail.instruction.to_string(),
+                    detail.address_mode
+                ));
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+
+    #[test]
+    fn test_coverage() {
+        // LDX #$01; DEX
+        let mut cpu = cpu_with_program(&[0xa2, 0x01, 0xca]);
+        let mut coverage = OpcodeCoverage::new();
+
+        cpu.run_with_callback(|cpu| coverage.record_cpu(cpu))
+            .unwrap();
+
+        assert_eq!(coverage.count(0xa2), 1);
+        assert_eq!(coverage.missing().len(), OPCODES.len() - 2);
+        assert!(coverage.report().starts_with("00 BRK Implied\n"));
+    }
+}
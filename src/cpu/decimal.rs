@@ -0,0 +1,100 @@
+use crate::cpu::bus::Bus;
+use crate::cpu::CPU;
+use crate::status::Flag;
+
+impl<B: Bus> CPU<B> {
+    /// Whether ADC and SBC should work in binary coded decimal. The 2A03 has the 6502's decimal
+    /// circuitry disconnected, so on an NES the D flag can be set but never changes arithmetic.
+    pub(crate) fn in_decimal_mode(&self) -> bool {
+        self.decimal_mode && self.status.read_flag(Flag::Decimal)
+    }
+
+    /// ADC on an NMOS 6502 with the D flag set. Zero comes from the binary sum, while negative and
+    /// overflow come from the sum after only the low digit has been adjusted.
+    pub(crate) fn decimal_add(&mut self, value: u8) {
+        let a = self.register_a as u16;
+        let value = value as u16;
+        let carry = self.status.read_flag(Flag::Carry) as u16;
+
+        let mut lo = (a & 0x0f) + (value & 0x0f) + carry;
+        let mut hi = (a & 0xf0) + (value & 0xf0);
+
+        if lo > 0x09 {
+            lo += 0x06;
+        }
+        if lo > 0x0f {
+            hi += 0x10;
+        }
+
+        let intermediate = (hi | (lo & 0x0f)) as u8;
+        let overflow = (self.register_a ^ intermediate) & !(self.register_a ^ value as u8) & 0x80;
+
+        self.status.set_zero_flag((a + value + carry) as u8);
+        self.status.set_negative_flag(intermediate);
+        self.status.set_flag(Flag::Overflow, overflow != 0);
+
+        if hi > 0x90 {
+            hi += 0x60;
+        }
+
+        self.status.set_flag(Flag::Carry, hi > 0xff);
+        self.register_a = ((hi & 0xf0) | (lo & 0x0f)) as u8;
+    }
+
+    /// SBC on an NMOS 6502 with the D flag set. The flags are exactly those of a binary SBC, which
+    /// the caller has already set, so only the result in the accumulator changes.
+    pub(crate) fn decimal_subtract(&mut self, a: u8, value: u8, borrow: bool) {
+        let mut lo = (a & 0x0f) as i16 - (value & 0x0f) as i16 - borrow as i16;
+        let mut hi = (a & 0xf0) as i16 - (value & 0xf0) as i16;
+
+        if lo < 0 {
+            lo -= 0x06;
+            hi -= 0x10;
+        }
+        if hi < 0 {
+            hi -= 0x60;
+        }
+
+        self.register_a = ((hi & 0xf0) | (lo & 0x0f)) as u8;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::test::cpu_with_program;
+    use crate::status::Flag;
+
+    #[test]
+    fn test_2a03_ignores_decimal_flag() {
+        // SED, LDA #$09, CLC, ADC #$01
+        let mut cpu = cpu_with_program(&[0xf8, 0xa9, 0x09, 0x18, 0x69, 0x01]);
+
+        cpu.run().unwrap();
+
+        assert_eq!(cpu.register_a, 0x0a);
+    }
+
+    #[test]
+    fn test_decimal_add() {
+        // SED, LDA #$99, CLC, ADC #$01
+        let mut cpu = cpu_with_program(&[0xf8, 0xa9, 0x99, 0x18, 0x69, 0x01]);
+        cpu.decimal_mode = true;
+
+        cpu.run().unwrap();
+
+        assert_eq!(cpu.register_a, 0x00);
+        assert!(cpu.status.read_flag(Flag::Carry));
+    }
+
+    #[test]
+    fn test_decimal_subtract() {
+        // SED, LDA #$10, SEC, SBC #$01
+        let mut cpu = cpu_with_program(&[0xf8, 0xa9, 0x10, 0x38, 0xe9, 0x01]);
+        cpu.decimal_mode = true;
+
+        cpu.run().unwrap();
+
+        assert_eq!(cpu.register_a, 0x09);
+        assert!(cpu.status.read_flag(Flag::Carry));
+    }
+}
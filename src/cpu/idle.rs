@@ -0,0 +1,190 @@
+use crate::cpu::bus::Bus;
+use crate::cpu::CPU;
+use crate::errors::NesError;
+use crate::opcodes::{AddressingMode, Instruction, OpCode, OpCodeDetail};
+
+/// The longest loop body (in bytes) that we will consider as a possible idle loop.
+const MAX_IDLE_LOOP_BYTES: u16 = 16;
+
+/// A loop which only reads memory and branches back on itself, for example waiting on $2002:
+/// ```text
+/// wait: BIT $2002
+///       BPL wait
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleLoop {
+    pub start: u16,
+    pub end: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LoopState {
+    program_counter: u16,
+    register_a: u8,
+    register_x: u8,
+    register_y: u8,
+    status: u8,
+    stack_pointer: u8,
+}
+
+/// Watches backward jumps for loops that cannot make progress on their own. If the CPU arrives back
+/// at the start of a read-only loop with exactly the same registers as the last time round, then
+/// only something outside the CPU (the PPU, an interrupt) can ever let it out.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IdleLoopDetector {
+    last_state: Option<LoopState>,
+    idle_loop: Option<IdleLoop>,
+}
+
+impl IdleLoopDetector {
+    pub fn new() -> Self {
+        IdleLoopDetector {
+            last_state: None,
+            idle_loop: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.last_state = None;
+        self.idle_loop = None;
+    }
+
+    /// The loop the CPU is currently spinning in, if any.
+    pub fn idle_loop(&self) -> Option<IdleLoop> {
+        self.idle_loop
+    }
+}
+
+impl<B: Bus> CPU<B> {
+    /// Called after an instruction at `jump_address` has moved the program counter. Returns true
+    /// if the CPU is now known to be spinning in an idle loop.
+    pub(crate) fn check_idle_loop(&mut self, jump_address: u16) -> Result<bool, NesError> {
+        let start = self.program_counter;
+
+        if start > jump_address || jump_address - start > MAX_IDLE_LOOP_BYTES {
+            self.idle_loop_detector.reset();
+            return Ok(false);
+        }
+
+        if !self.is_read_only_loop(start, jump_address)? {
+            self.idle_loop_detector.reset();
+            return Ok(false);
+        }
+
+        let state = LoopState {
+            program_counter: start,
+            register_a: self.register_a,
+            register_x: self.register_x,
+            register_y: self.register_y,
+            status: self.status.get_status_byte(),
+            stack_pointer: self.stack_pointer,
+        };
+
+        let detector = &mut self.idle_loop_detector;
+
+        if detector.last_state == Some(state) {
+            detector.idle_loop = Some(IdleLoop {
+                start,
+                end: jump_address,
+            });
+            return Ok(true);
+        }
+
+        detector.last_state = Some(state);
+        detector.idle_loop = None;
+
+        Ok(false)
+    }
+
+    /// Decode each instruction between `start` and `end` (inclusive) and check none of them can
+    /// change memory or the stack.
+    fn is_read_only_loop(&self, start: u16, end: u16) -> Result<bool, NesError> {
+        let mut address = start;
+
+        while address <= end {
+            let code = self.bus.mem_peek(address)?;
+            let opcode = OpCodeDetail::from_opcode(&OpCode::from_code(&code)?);
+
+            let writes = match opcode.instruction {
+                Instruction::STA
+                | Instruction::STX
+                | Instruction::STY
+                | Instruction::INC
+                | Instruction::DEC
+                | Instruction::PHA
+                | Instruction::PHP
+                | Instruction::PLA
+                | Instruction::PLP
+                | Instruction::JSR
+                | Instruction::RTS
+                | Instruction::RTI
+                | Instruction::BRK
+                | Instruction::TXS
+                | Instruction::SAX
+                | Instruction::DCP
+                | Instruction::ISB
+                | Instruction::SLO
+                | Instruction::RLA
+                | Instruction::SRE
+                | Instruction::RRA => true,
+                Instruction::ASL | Instruction::LSR | Instruction::ROL | Instruction::ROR => {
+                    !matches!(opcode.address_mode, AddressingMode::Accumulator)
+                }
+                _ => false,
+            };
+
+            if writes {
+                return Ok(false);
+            }
+
+            address = address.wrapping_add(opcode.bytes as u16);
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::test::cpu_with_program;
+    use crate::cpu::StopReason;
+
+    #[test]
+    fn test_detects_read_only_loop() {
+        // LDA $10; BEQ -4
+        let mut cpu = cpu_with_program(&[0xa5, 0x10, 0xf0, 0xfc]);
+        cpu.skip_idle_loops = true;
+
+        assert_eq!(cpu.run().unwrap(), StopReason::IdleLoop);
+
+        let idle_loop = cpu
+            .idle_loop_detector
+            .idle_loop()
+            .expect("No idle loop found");
+
+        assert_eq!(idle_loop.start, 0x0600);
+        assert_eq!(idle_loop.end, 0x0602);
+    }
+
+    #[test]
+    fn test_ignores_counting_loop() {
+        // LDX #$05; DEX; BNE -3
+        let mut cpu = cpu_with_program(&[0xa2, 0x05, 0xca, 0xd0, 0xfd]);
+        cpu.skip_idle_loops = true;
+
+        cpu.run().expect("Error running cpu");
+
+        assert_eq!(cpu.idle_loop_detector.idle_loop(), None);
+        assert_eq!(cpu.register_x, 0);
+    }
+
+    #[test]
+    fn test_ignores_loop_with_write() {
+        // LDA $10; STA $11; BEQ -6
+        let mut cpu = cpu_with_program(&[0xa5, 0x10, 0x85, 0x11, 0xf0, 0xfa]);
+
+        assert!(!cpu.check_idle_loop(0x0604).unwrap());
+        assert!(!cpu.check_idle_loop(0x0604).unwrap());
+        assert_eq!(cpu.idle_loop_detector.idle_loop(), None);
+    }
+}
@@ -0,0 +1,431 @@
+use std::collections::VecDeque;
+
+use crate::cpu::bus::Bus;
+use crate::cpu::CPU;
+use crate::errors::NesError;
+use crate::status::Flag;
+
+const NMI_VECTOR: u16 = 0xfffa;
+const IRQ_VECTOR: u16 = 0xfffe;
+/// Taking an NMI or IRQ costs as much as a BRK.
+const INTERRUPT_CYCLES: u64 = 7;
+
+/// Bit 4 of the pushed status, set for BRK and PHP but clear for hardware interrupts.
+const BREAK_BIT: u8 = 0b0001_0000;
+/// Bit 5 of the pushed status, which always reads as set.
+const IGNORED_BIT: u8 = 0b0010_0000;
+
+/// Where a maskable interrupt request came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IrqSource {
+    ApuFrameCounter,
+    Dmc,
+    Mapper,
+}
+
+impl IrqSource {
+    fn bit(self) -> u8 {
+        match self {
+            IrqSource::ApuFrameCounter => 0b001,
+            IrqSource::Dmc => 0b010,
+            IrqSource::Mapper => 0b100,
+        }
+    }
+}
+
+/// The IRQ line. Several sources can hold it at once, and it stays asserted until every one of
+/// them lets go, so a handler that doesn't acknowledge its source is interrupted again as soon as
+/// it returns.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct IrqLine {
+    sources: u8,
+}
+
+impl IrqLine {
+    pub fn new() -> Self {
+        IrqLine::default()
+    }
+
+    pub fn set(&mut self, source: IrqSource, asserted: bool) {
+        if asserted {
+            self.sources |= source.bit();
+        } else {
+            self.sources &= !source.bit();
+        }
+    }
+
+    pub fn is_asserted(&self) -> bool {
+        self.sources != 0
+    }
+
+    /// Which sources hold the line, one bit each.
+    #[cfg_attr(not(feature = "nes"), allow(dead_code))]
+    pub(crate) fn sources(&self) -> u8 {
+        self.sources
+    }
+
+    /// One of the sources holding the line, if any, for the interrupt log.
+    pub fn source(&self) -> Option<IrqSource> {
+        [
+            IrqSource::ApuFrameCounter,
+            IrqSource::Dmc,
+            IrqSource::Mapper,
+        ]
+        .into_iter()
+        .find(|source| self.sources & source.bit() != 0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InterruptKind {
+    Nmi,
+    Irq(IrqSource),
+    Brk,
+}
+
+/// A single interrupt serviced by the CPU.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterruptEvent {
+    pub kind: InterruptKind,
+    /// The number of instructions the CPU had run when the interrupt happened.
+    pub instruction: u64,
+    /// The number of CPU cycles that had run when the interrupt was taken.
+    pub cycle: u64,
+    /// The program counter the CPU was at when the interrupt was taken.
+    pub program_counter: u16,
+    /// Where the CPU jumped to handle the interrupt.
+    pub handler: u16,
+}
+
+/// How many events the log holds before it starts dropping the oldest.
+pub const INTERRUPT_LOG_CAPACITY: usize = 1024;
+
+/// Records interrupts as they are serviced so that questions like "why did my NMI fire twice" can
+/// be answered after the fact. Nothing is recorded unless the log has been enabled.
+///
+/// The log keeps the most recent `INTERRUPT_LOG_CAPACITY` events in storage allocated when it is
+/// enabled, so recording never allocates while emulating.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InterruptLog {
+    enabled: bool,
+    events: VecDeque<InterruptEvent>,
+}
+
+impl InterruptLog {
+    pub fn new() -> Self {
+        InterruptLog::default()
+    }
+
+    pub fn enable(&mut self) {
+        self.enabled = true;
+        self.events.reserve_exact(INTERRUPT_LOG_CAPACITY);
+    }
+
+    pub fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    pub fn record(&mut self, event: InterruptEvent) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.events.len() == INTERRUPT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(event);
+    }
+
+    /// The recorded events, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &InterruptEvent> {
+        self.events.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<B: Bus> CPU<B> {
+    /// Take a non-maskable interrupt before the next instruction.
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
+
+    /// Take any interrupt that is waiting, between two instructions. An NMI wins over an IRQ,
+    /// and an IRQ is only taken while the I flag is clear.
+    ///
+    /// CLI, SEI and PLP change I after the 6502 has polled for the next interrupt, so the
+    /// instruction after them still sees the old value. RTI's change is seen straight away.
+    pub(crate) fn poll_interrupts(&mut self) -> Result<(), NesError> {
+        let irq_disabled = self
+            .delayed_interrupt_flag
+            .take()
+            .unwrap_or(self.status.read_flag(Flag::Interrupt));
+
+        if self.bus.take_nmi() {
+            self.nmi_pending = true;
+        }
+
+        if std::mem::take(&mut self.nmi_pending) {
+            return self.interrupt(InterruptKind::Nmi, NMI_VECTOR);
+        }
+
+        if irq_disabled {
+            return Ok(());
+        }
+
+        if let Some(source) = self.irq.source().or(self.bus.irq_source()) {
+            self.interrupt(InterruptKind::Irq(source), IRQ_VECTOR)?;
+        }
+
+        Ok(())
+    }
+
+    /// The interrupt state a save state doesn't keep: a pending NMI, the I flag held back for the
+    /// next poll and the sources holding the CPU's IRQ line.
+    #[cfg_attr(not(feature = "nes"), allow(dead_code))]
+    pub(crate) fn interrupt_state(&self) -> [u8; 3] {
+        let delayed = match self.delayed_interrupt_flag {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        };
+
+        [self.nmi_pending as u8, delayed, self.irq.sources()]
+    }
+
+    /// Where a BRK jumps to. An NMI that arrives before BRK has fetched its vector hijacks it: the
+    /// NMI handler runs instead, sees B set in the pushed status, and the BRK is never handled.
+    pub(crate) fn brk_vector(&mut self) -> (InterruptKind, u16) {
+        if self.bus.take_nmi() {
+            self.nmi_pending = true;
+        }
+
+        if std::mem::take(&mut self.nmi_pending) {
+            (InterruptKind::Nmi, NMI_VECTOR)
+        } else {
+            (InterruptKind::Brk, IRQ_VECTOR)
+        }
+    }
+
+    /// Keep the current I flag for the next interrupt poll, before an instruction that changes it
+    /// late.
+    pub(crate) fn delay_interrupt_flag(&mut self) {
+        self.delayed_interrupt_flag = Some(self.status.read_flag(Flag::Interrupt));
+    }
+
+    /// Push the program counter and status and jump through `vector`, as the 6502 does for a
+    /// hardware interrupt.
+    fn interrupt(&mut self, kind: InterruptKind, vector: u16) -> Result<(), NesError> {
+        self.push_to_stack_u16(self.program_counter)?;
+
+        let status = (self.status.get_status_byte() & !BREAK_BIT) | IGNORED_BIT;
+        self.push_to_stack(status)?;
+
+        self.status.set_flag(Flag::Interrupt, true);
+
+        let handler = self.bus.mem_read_u16(vector)?;
+
+        self.record_interrupt(InterruptEvent {
+            kind,
+            instruction: self.instruction_count,
+            cycle: self.cycles,
+            program_counter: self.program_counter,
+            handler,
+        });
+
+        self.program_counter = handler;
+        self.cycles += INTERRUPT_CYCLES;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+    use crate::opcodes::{OpCode, OpCodeDetail};
+
+    #[test]
+    fn test_log_brk() {
+        // INX; BRK
+        let mut cpu = cpu_with_program(&[0xe8, 0x00]);
+        cpu.interrupt_log.enable();
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::Xe8))
+            .unwrap();
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::X00))
+            .unwrap();
+
+        assert_eq!(
+            cpu.interrupt_log.events().collect::<Vec<_>>(),
+            vec![&InterruptEvent {
+                kind: InterruptKind::Brk,
+                instruction: 1,
+                cycle: 2,
+                program_counter: 0x0601,
+                handler: cpu.program_counter,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nmi() {
+        // INX; INX, with an INY; RTI handler at $0000 since the NMI vector reads $0000
+        let mut cpu = cpu_with_program(&[0xe8, 0xe8]);
+        cpu.bus.mem_write(0x0000, 0xc8).unwrap();
+        cpu.bus.mem_write(0x0001, 0x40).unwrap();
+        cpu.interrupt_log.enable();
+
+        cpu.step().unwrap();
+        cpu.trigger_nmi();
+
+        // The NMI's 7 cycles and INY's 2
+        assert_eq!(cpu.step().unwrap(), 9);
+        assert_eq!(cpu.register_y, 1);
+        assert_eq!(cpu.program_counter, 0x0001);
+        assert!(cpu.status.read_flag(Flag::Interrupt));
+        assert_eq!(cpu.stack_pointer, 0xfa);
+        assert_eq!(cpu.bus.mem_read(0x01fb).unwrap() & BREAK_BIT, 0);
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.program_counter, 0x0601);
+
+        assert_eq!(
+            cpu.interrupt_log.events().copied().collect::<Vec<_>>(),
+            vec![InterruptEvent {
+                kind: InterruptKind::Nmi,
+                instruction: 1,
+                cycle: 2,
+                program_counter: 0x0601,
+                handler: 0x0000,
+            }]
+        );
+
+        // The bus can pull the line too
+        cpu.bus.request_nmi();
+        cpu.step().unwrap();
+
+        assert_eq!(cpu.register_y, 2);
+        assert_eq!(cpu.stats().nmis, 2);
+    }
+
+    #[test]
+    fn test_nmi_hijacks_brk() {
+        // BRK, padding, with an INY handler at $0000 since the NMI vector reads $0000
+        let mut cpu = cpu_with_program(&[0x00, 0x00]);
+        cpu.bus.mem_write(0x0000, 0xc8).unwrap();
+        cpu.interrupt_log.enable();
+
+        // The NMI arrives while BRK is running, after the poll before it
+        cpu.trigger_nmi();
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::X00))
+            .unwrap();
+
+        assert_eq!(cpu.program_counter, 0x0000);
+        assert_eq!(cpu.pull_from_stack().unwrap() & BREAK_BIT, BREAK_BIT);
+        assert_eq!(cpu.pull_from_stack_u16().unwrap(), 0x0602);
+
+        assert_eq!(cpu.stats().nmis, 1);
+        assert_eq!(cpu.stats().brks, 0);
+        assert_eq!(
+            cpu.interrupt_log
+                .events()
+                .map(|event| event.kind)
+                .collect::<Vec<_>>(),
+            vec![InterruptKind::Nmi]
+        );
+
+        // Taken once only
+        cpu.step().unwrap();
+        assert_eq!(cpu.register_y, 1);
+        assert_eq!(cpu.stats().nmis, 1);
+    }
+
+    #[test]
+    fn test_irq() {
+        // CLI; INX; INX, with an INY; RTI handler at $0000 since the IRQ vector reads $0000
+        let mut cpu = cpu_with_program(&[0x58, 0xe8, 0xe8]);
+        cpu.bus.mem_write(0x0000, 0xc8).unwrap();
+        cpu.bus.mem_write(0x0001, 0x40).unwrap();
+        cpu.interrupt_log.enable();
+        cpu.irq.set(IrqSource::Mapper, true);
+
+        // Masked for CLI itself and, as the change comes late, for the INX after it too
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register_x, 1);
+        assert_eq!(cpu.register_y, 0);
+
+        cpu.step().unwrap();
+        assert_eq!(cpu.register_y, 1);
+        assert!(cpu.status.read_flag(Flag::Interrupt));
+
+        // The line is still held, so the handler has to acknowledge the source before returning
+        cpu.irq.set(IrqSource::Mapper, false);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.register_x, 2);
+
+        assert_eq!(
+            cpu.interrupt_log
+                .events()
+                .map(|event| event.kind)
+                .collect::<Vec<_>>(),
+            vec![InterruptKind::Irq(IrqSource::Mapper)]
+        );
+
+        // From the bus, and masked again once I is set
+        cpu.bus.irq.set(IrqSource::ApuFrameCounter, true);
+        cpu.status.set_flag(Flag::Interrupt, true);
+        cpu.step().unwrap();
+        assert_eq!(cpu.stats().irqs, 1);
+    }
+
+    #[test]
+    fn test_irq_line() {
+        let mut line = IrqLine::new();
+        line.set(IrqSource::Dmc, true);
+        line.set(IrqSource::Mapper, true);
+        line.set(IrqSource::Dmc, false);
+
+        assert!(line.is_asserted());
+        assert_eq!(line.source(), Some(IrqSource::Mapper));
+
+        line.set(IrqSource::Mapper, false);
+        assert!(!line.is_asserted());
+    }
+
+    #[test]
+    fn test_log_disabled() {
+        let mut cpu = cpu_with_program(&[0x00]);
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::X00))
+            .unwrap();
+
+        assert_eq!(cpu.interrupt_log.events().count(), 0);
+    }
+
+    #[test]
+    fn test_log_drops_oldest() {
+        let mut log = InterruptLog::new();
+        log.enable();
+
+        for instruction in 0..(INTERRUPT_LOG_CAPACITY as u64 + 1) {
+            log.record(InterruptEvent {
+                kind: InterruptKind::Nmi,
+                instruction,
+                cycle: 0,
+                program_counter: 0,
+                handler: 0,
+            });
+        }
+
+        assert_eq!(log.events().count(), INTERRUPT_LOG_CAPACITY);
+        assert_eq!(log.events().next().unwrap().instruction, 1);
+    }
+}
@@ -1,30 +1,169 @@
-use sdl2::sys::wchar_t;
+use std::fmt;
 use std::ops::Add;
 
+#[cfg(feature = "nes")]
 use crate::bus::CpuBus;
 use crate::errors::NesError;
-use crate::memory::Mem;
 use crate::opcodes::{AddressingMode, Instruction, OpCode, OpCodeDetail};
 use crate::status;
 use crate::status::Flag;
+use accuracy::{writes_operand, Accuracy};
+use bus::Bus;
+use idle::IdleLoopDetector;
+use interrupts::{InterruptEvent, InterruptLog, IrqLine};
+use stack::StackWrapListener;
+use stats::Stats;
 
 // TODO the program counter will be implemented incorrectly when using brk and the jmp commands because it always will increase by 1 afterwards but it should ignore it. Need to find best place to define.
 
+pub mod accuracy;
+#[cfg(feature = "nes")]
+pub mod boot;
+pub mod bus;
+pub mod coverage;
+pub mod decimal;
+pub mod idle;
+pub mod interrupts;
 pub mod stack;
+pub mod stats;
+#[cfg(feature = "nes")]
 pub mod trace;
 
-pub struct CPU {
+/// A reset takes as long as an interrupt, though it only pretends to push onto the stack.
+const RESET_CYCLES: u64 = 7;
+
+/// The bus a CPU is on unless told otherwise: the NES's, or when built without the `nes` feature,
+/// plain RAM.
+#[cfg(feature = "nes")]
+type DefaultBus = CpuBus;
+#[cfg(not(feature = "nes"))]
+type DefaultBus = crate::memory::RAM;
+
+/// A write that hit a watchpoint. `address` is the one the CPU actually wrote, which may be a
+/// mirror of the watched address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchHit {
+    pub address: u16,
+    pub value: u8,
+}
+
+/// Why `run_with_callback` handed control back to the caller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    /// The next instruction is a BRK.
+    Break,
+    /// The CPU is stuck in an idle loop, see `skip_idle_loops`.
+    IdleLoop,
+    /// The run used up its `instruction_budget`.
+    Timeout,
+    /// The last instruction wrote to a watched address, see `CpuBus::watch_writes`.
+    Watchpoint(WatchHit),
+    /// The condition given to `run_until` holds.
+    Condition,
+}
+
+/// A 6502 core. It is generic over the bus it is attached to, so it can be used with something
+/// other than the NES's `CpuBus`; anything implementing [`Bus`] will do.
+///
+/// Cloning a CPU copies the whole machine but not its debugger listeners.
+pub struct CPU<B = DefaultBus> {
     pub register_a: u8,
     pub register_x: u8,
     pub register_y: u8,
-    pub status: status::Status,
+    pub(crate) status: status::Status,
     pub program_counter: u16,
     pub stack_pointer: u8,
-    pub bus: CpuBus,
+    pub bus: B,
+    pub accuracy: Accuracy,
+    /// The number of instructions run since the CPU was created.
+    pub instruction_count: u64,
+    /// The number of CPU cycles run since the CPU was created, including resets and interrupts,
+    /// for keeping other components in step with the CPU clock.
+    pub cycles: u64,
+    pub interrupt_log: InterruptLog,
+    /// Stop `run_with_callback` once the CPU is spinning in a loop it can never leave on its own,
+    /// so the caller can fast-forward the rest of the system (e.g. to the next VBlank).
+    pub skip_idle_loops: bool,
+    pub idle_loop_detector: IdleLoopDetector,
+    /// The most instructions a single call to `run_with_callback` may run before giving up, so a
+    /// broken ROM can't hang a headless run forever.
+    pub instruction_budget: Option<u64>,
+    /// Honour the D flag in ADC and SBC like a generic NMOS 6502. Leave this off to emulate the
+    /// NES's 2A03, which has no decimal mode.
+    pub decimal_mode: bool,
+    pub(crate) stack_wrap_listeners: Vec<StackWrapListener>,
+    stats: Stats,
+    nmi_pending: bool,
+    /// An IRQ line of the CPU's own, for buses that don't have one.
+    pub irq: IrqLine,
+    /// The I flag from before the last CLI, SEI or PLP, which the next interrupt poll still sees.
+    delayed_interrupt_flag: Option<bool>,
+}
+
+impl<B: Clone> Clone for CPU<B> {
+    fn clone(&self) -> Self {
+        CPU {
+            register_a: self.register_a,
+            register_x: self.register_x,
+            register_y: self.register_y,
+            status: self.status,
+            program_counter: self.program_counter,
+            stack_pointer: self.stack_pointer,
+            bus: self.bus.clone(),
+            accuracy: self.accuracy,
+            instruction_count: self.instruction_count,
+            cycles: self.cycles,
+            interrupt_log: self.interrupt_log.clone(),
+            skip_idle_loops: self.skip_idle_loops,
+            idle_loop_detector: self.idle_loop_detector.clone(),
+            instruction_budget: self.instruction_budget,
+            decimal_mode: self.decimal_mode,
+            stack_wrap_listeners: vec![],
+            stats: self.stats,
+            nmi_pending: self.nmi_pending,
+            irq: self.irq,
+            delayed_interrupt_flag: self.delayed_interrupt_flag,
+        }
+    }
+}
+
+/// Two CPUs are equal when the machines are in the same state: registers, memory and instruction
+/// count. Settings and debugging aids aren't compared.
+impl<B: PartialEq> PartialEq for CPU<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.register_a == other.register_a
+            && self.register_x == other.register_x
+            && self.register_y == other.register_y
+            && self.status == other.status
+            && self.program_counter == other.program_counter
+            && self.stack_pointer == other.stack_pointer
+            && self.instruction_count == other.instruction_count
+            && self.cycles == other.cycles
+            && self.nmi_pending == other.nmi_pending
+            && self.irq == other.irq
+            && self.bus == other.bus
+    }
 }
 
-impl CPU {
-    pub fn new(bus: CpuBus) -> Self {
+impl<B: fmt::Debug> fmt::Debug for CPU<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CPU")
+            .field("register_a", &self.register_a)
+            .field("register_x", &self.register_x)
+            .field("register_y", &self.register_y)
+            .field("status", &self.status)
+            .field("program_counter", &self.program_counter)
+            .field("stack_pointer", &self.stack_pointer)
+            .field("instruction_count", &self.instruction_count)
+            .field("cycles", &self.cycles)
+            .field("accuracy", &self.accuracy)
+            .field("bus", &self.bus)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<B: Bus> CPU<B> {
+    pub fn new(bus: B) -> Self {
         CPU {
             register_a: 0,
             register_x: 0,
@@ -33,6 +172,19 @@ impl CPU {
             program_counter: 0,
             stack_pointer: 0xfd,
             bus,
+            accuracy: Accuracy::default(),
+            instruction_count: 0,
+            cycles: 0,
+            interrupt_log: InterruptLog::new(),
+            skip_idle_loops: false,
+            idle_loop_detector: IdleLoopDetector::new(),
+            instruction_budget: None,
+            decimal_mode: false,
+            stack_wrap_listeners: vec![],
+            stats: Stats::new(),
+            nmi_pending: false,
+            irq: IrqLine::new(),
+            delayed_interrupt_flag: None,
         }
     }
 
@@ -43,15 +195,37 @@ impl CPU {
         self.register_y = 0;
         self.stack_pointer = 0xfd;
         self.status.reset();
+        self.idle_loop_detector.reset();
 
         self.program_counter = self.bus.mem_read_u16(0xfffc)?;
+        self.cycles += RESET_CYCLES;
 
         Ok(())
     }
 
     /// We get the address in the memory that the address mode refers to.
-    pub fn get_operand_address(&self, mode: &AddressingMode) -> Result<u16, NesError> {
-        let program_counter = self.program_counter + 1;
+    pub(crate) fn get_operand_address(&self, mode: &AddressingMode) -> Result<u16, NesError> {
+        self.operand_address(mode, |bus, address| bus.mem_read(address))
+    }
+
+    /// The address the instruction would use, fetching its operand and any pointer without side
+    /// effects on the hardware they are read from. For traces and debuggers.
+    #[cfg_attr(not(feature = "nes"), allow(dead_code))]
+    pub(crate) fn peek_operand_address(&self, mode: &AddressingMode) -> Result<u16, NesError> {
+        self.operand_address(mode, |bus, address| bus.mem_peek(address))
+    }
+
+    fn operand_address<F>(&self, mode: &AddressingMode, read: F) -> Result<u16, NesError>
+    where
+        F: Fn(&B, u16) -> Result<u8, NesError>,
+    {
+        let program_counter = self.program_counter.wrapping_add(1);
+        let read_u16 = |address: u16| -> Result<u16, NesError> {
+            Ok(u16::from_le_bytes([
+                read(&self.bus, address)?,
+                read(&self.bus, address.wrapping_add(1))?,
+            ]))
+        };
 
         match mode {
             AddressingMode::Immediate => {
@@ -65,7 +239,7 @@ impl CPU {
                 // LDA $a9
                 // ```
                 // In this case what we would like this function to return is 0xa9. We have the program counter which may be 0x0002 and we know that the value at 0x0002 is 0xa9, so we just need to read the value at the program counter.
-                Ok(self.bus.mem_read(program_counter)? as u16)
+                Ok(read(&self.bus, program_counter)? as u16)
             }
             AddressingMode::ZeroPageX => {
                 // Here we have something like:
@@ -74,38 +248,37 @@ impl CPU {
                 // LDA $a1,X
                 // ```
                 // In this case we want to return 0xa2, because we take the 0xa1 and we add X to it (which is 0x01) to get 0xa2. Just like with zero page addressing we have the program counter like 0x0004, and if we read the value in memory at 0x0004 it is 0xa1, so we need to take the value at the program counter and add x to it.
-                Ok(self
-                    .bus
-                    .mem_read(program_counter)?
-                    .wrapping_add(self.register_x) as u16)
-            }
-            AddressingMode::ZeroPageY => Ok(self
-                .bus
-                .mem_read(program_counter)?
-                .wrapping_add(self.register_y) as u16),
-            AddressingMode::Absolute => Ok(self.bus.mem_read_u16(program_counter)?),
-            AddressingMode::AbsoluteX => Ok(self
-                .bus
-                .mem_read_u16(program_counter)?
-                .wrapping_add(self.register_x as u16)),
-            AddressingMode::AbsoluteY => Ok(self
-                .bus
-                .mem_read_u16(program_counter)?
-                .wrapping_add(self.register_y as u16)),
+                Ok(read(&self.bus, program_counter)?.wrapping_add(self.register_x) as u16)
+            }
+            AddressingMode::ZeroPageY => {
+                Ok(read(&self.bus, program_counter)?.wrapping_add(self.register_y) as u16)
+            }
+            AddressingMode::Absolute => Ok(read_u16(program_counter)?),
+            AddressingMode::AbsoluteX => {
+                Ok(read_u16(program_counter)?.wrapping_add(self.register_x as u16))
+            }
+            AddressingMode::AbsoluteY => {
+                Ok(read_u16(program_counter)?.wrapping_add(self.register_y as u16))
+            }
             AddressingMode::Indirect => {
-                let address = self.bus.mem_read_u16(program_counter)?;
-                Ok(self.bus.mem_read_u16_wrapping_boundary(address)?)
+                let pointer = read_u16(program_counter)?;
+
+                // The 6502 only increments the low byte of the pointer, so JMP ($02FF) takes its
+                // high byte from $0200 rather than $0300.
+                let hi_pointer = (pointer & 0xff00) | (pointer.wrapping_add(1) & 0x00ff);
+
+                Ok(u16::from_le_bytes([
+                    read(&self.bus, pointer)?,
+                    read(&self.bus, hi_pointer)?,
+                ]))
             }
             AddressingMode::IndirectX => {
-                let address = self
-                    .bus
-                    .mem_read(program_counter)?
-                    .wrapping_add(self.register_x) as u16;
-                Ok(self.bus.mem_read_u16_wrapping_boundary(address)?)
+                let pointer = read(&self.bus, program_counter)?.wrapping_add(self.register_x);
+                self.zero_page_pointer(pointer, &read)
             }
             AddressingMode::IndirectY => {
-                let base = self.bus.mem_read(program_counter)? as u16;
-                let address = self.bus.mem_read_u16_wrapping_boundary(base)?;
+                let pointer = read(&self.bus, program_counter)?;
+                let address = self.zero_page_pointer(pointer, &read)?;
                 Ok(address.wrapping_add(self.register_y as u16))
             }
             AddressingMode::Relative => Ok(program_counter),
@@ -113,7 +286,42 @@ impl CPU {
         }
     }
 
-    pub fn get_operand_address_value(&self, mode: &AddressingMode) -> Result<u8, NesError> {
+    /// A pointer stored in the zero page, which wraps from $FF back to $00 for its high byte.
+    pub(crate) fn read_zero_page_pointer(&self, pointer: u8) -> Result<u16, NesError> {
+        self.zero_page_pointer(pointer, |bus, address| bus.mem_read(address))
+    }
+
+    /// `read_zero_page_pointer` without side effects, for traces and debuggers.
+    #[cfg_attr(not(feature = "nes"), allow(dead_code))]
+    pub(crate) fn peek_zero_page_pointer(&self, pointer: u8) -> Result<u16, NesError> {
+        self.zero_page_pointer(pointer, |bus, address| bus.mem_peek(address))
+    }
+
+    fn zero_page_pointer<F>(&self, pointer: u8, read: F) -> Result<u16, NesError>
+    where
+        F: Fn(&B, u16) -> Result<u8, NesError>,
+    {
+        Ok(u16::from_le_bytes([
+            read(&self.bus, pointer as u16)?,
+            read(&self.bus, pointer.wrapping_add(1) as u16)?,
+        ]))
+    }
+
+    pub(crate) fn get_operand_address_value(&self, mode: &AddressingMode) -> Result<u8, NesError> {
+        self.operand_value(mode, |bus, address| bus.mem_read(address))
+    }
+
+    /// The operand the instruction would read, without side effects on the hardware it reads
+    /// from. For traces and debuggers.
+    #[cfg_attr(not(feature = "nes"), allow(dead_code))]
+    pub(crate) fn peek_operand_address_value(&self, mode: &AddressingMode) -> Result<u8, NesError> {
+        self.operand_value(mode, |bus, address| bus.mem_peek(address))
+    }
+
+    fn operand_value<F>(&self, mode: &AddressingMode, read: F) -> Result<u8, NesError>
+    where
+        F: Fn(&B, u16) -> Result<u8, NesError>,
+    {
         match mode {
             AddressingMode::Accumulator => {
                 return Ok(self.register_a);
@@ -124,9 +332,24 @@ impl CPU {
             _ => (),
         };
 
-        let address = self.get_operand_address(mode)?;
+        let address = self.operand_address(mode, &read)?;
 
-        Ok(self.bus.mem_read(address)?)
+        read(&self.bus, address)
+    }
+
+    /// Whether a branch instruction's condition holds.
+    fn branch_taken(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::BCC => !self.status.read_flag(Flag::Carry),
+            Instruction::BCS => self.status.read_flag(Flag::Carry),
+            Instruction::BEQ => self.status.read_flag(Flag::Zero),
+            Instruction::BMI => self.status.read_flag(Flag::Negative),
+            Instruction::BNE => !self.status.read_flag(Flag::Zero),
+            Instruction::BPL => !self.status.read_flag(Flag::Negative),
+            Instruction::BVC => !self.status.read_flag(Flag::Overflow),
+            Instruction::BVS => self.status.read_flag(Flag::Overflow),
+            _ => false,
+        }
     }
 
     fn move_pointer_on_branch(&mut self, mode: &AddressingMode, bytes: u8) -> Result<(), NesError> {
@@ -176,9 +399,36 @@ impl CPU {
         self.status.set_flag(Flag::Overflow, overflow);
     }
 
+    /// ADC, in decimal if the CPU has decimal mode and the D flag is set.
+    fn add_to_register_a(&mut self, value: u8) {
+        if self.in_decimal_mode() {
+            self.decimal_add(value);
+        } else {
+            self.addition_with_register_a(value as u16);
+        }
+    }
+
+    /// SBC, in decimal if the CPU has decimal mode and the D flag is set.
+    fn subtract_from_register_a(&mut self, value: u8) {
+        let register_a = self.register_a;
+        let borrow = !self.status.read_flag(Flag::Carry);
+
+        self.addition_with_register_a(!value as u16);
+
+        if self.in_decimal_mode() {
+            self.decimal_subtract(register_a, value, borrow);
+        }
+    }
+
     fn compare_to_memory(&mut self, value: u8, mode: &AddressingMode) -> Result<(), NesError> {
         let memory_value = self.get_operand_address_value(mode)?;
 
+        self.compare(value, memory_value);
+
+        Ok(())
+    }
+
+    fn compare(&mut self, value: u8, memory_value: u8) {
         let inverse_memory_value = (!memory_value as u16).wrapping_add(1);
 
         let result = inverse_memory_value.wrapping_add(value as u16);
@@ -188,97 +438,184 @@ impl CPU {
         self.status.set_zero_flag(lo);
         self.status.set_negative_flag(lo);
         self.status.set_flag(Flag::Carry, hi > 0);
-
-        Ok(())
     }
 
-    fn check_boundary_crossed(&mut self, address: u16, value: u8) -> bool {
+    fn check_boundary_crossed(&self, address: u16, value: u8) -> bool {
         let updated_address = address.wrapping_add(value as u16);
 
         let [_start_address_lo, start_address_hi] = u16::to_le_bytes(address);
         let [_updated_address_lo, updated_address_hi] = u16::to_le_bytes(updated_address);
 
-        let crossed_page = updated_address_hi != start_address_hi;
-        crossed_page
+        updated_address_hi != start_address_hi
     }
 
-    fn major_cycles(&mut self, mode: &AddressingMode) -> Result<u8, NesError> {
-        match mode {
-            AddressingMode::Immediate => Ok(2),
-            AddressingMode::ZeroPage => Ok(3),
-            AddressingMode::ZeroPageX => Ok(4),
-            AddressingMode::Absolute => Ok(4),
-            AddressingMode::AbsoluteX => {
-                let address = self.bus.mem_read_u16(self.program_counter)?;
-
-                let crossed_page = self.check_boundary_crossed(address, self.register_x);
+    /// The cycles the instruction at the program counter will take: the base count from the
+    /// opcode table, plus one when an indexed read crosses a page. Stores and read-modify-write
+    /// instructions always take that cycle, so their base count already includes it.
+    ///
+    /// A branch takes one more when it's taken, and another when it lands on a different page from
+    /// the next instruction.
+    fn major_cycles(&self, opcode: &OpCodeDetail) -> Result<u8, NesError> {
+        let cycles = opcode.cycles as u8;
 
-                if crossed_page {
-                    Ok(5)
-                } else {
-                    Ok(4)
-                }
+        if let AddressingMode::Relative = opcode.address_mode {
+            if !self.branch_taken(&opcode.instruction) {
+                return Ok(cycles);
             }
-            AddressingMode::AbsoluteY => {
-                let address = self.bus.mem_read_u16(self.program_counter)?;
 
-                let crossed_page = self.check_boundary_crossed(address, self.register_y);
+            let next = self.program_counter.wrapping_add(opcode.bytes as u16);
+            let offset = self.bus.mem_peek(self.program_counter.wrapping_add(1))? as i8;
+            let target = next.wrapping_add(offset as u16);
 
-                if crossed_page {
-                    Ok(5)
-                } else {
-                    Ok(4)
-                }
+            return Ok(cycles + 1 + (next & 0xff00 != target & 0xff00) as u8);
+        }
+
+        if writes_operand(&opcode.instruction) {
+            return Ok(cycles);
+        }
+
+        let program_counter = self.program_counter.wrapping_add(1);
+
+        let crossed_page = match opcode.address_mode {
+            AddressingMode::AbsoluteX => {
+                let address = self.bus.mem_peek_u16(program_counter)?;
+                self.check_boundary_crossed(address, self.register_x)
+            }
+            AddressingMode::AbsoluteY => {
+                let address = self.bus.mem_peek_u16(program_counter)?;
+                self.check_boundary_crossed(address, self.register_y)
             }
-            AddressingMode::IndirectX => Ok(6),
             AddressingMode::IndirectY => {
-                let address = self.bus.mem_read(self.program_counter)?;
+                let pointer = self.bus.mem_peek(program_counter)?;
+                let address = u16::from_le_bytes([
+                    self.bus.mem_peek(pointer as u16)?,
+                    self.bus.mem_peek(pointer.wrapping_add(1) as u16)?,
+                ]);
+                self.check_boundary_crossed(address, self.register_y)
+            }
+            _ => false,
+        };
+
+        Ok(cycles + crossed_page as u8)
+    }
 
-                let address = self.bus.mem_read_u16(address as u16)?;
+    /// The status register P as a byte, as traces show it.
+    pub fn status(&self) -> u8 {
+        self.status.get_status_byte()
+    }
 
-                let crossed_page = self.check_boundary_crossed(address, self.register_y);
+    /// Run the next instruction, whatever it is, without any of `run_with_callback`'s stopping
+    /// checks. Returns the cycles it took, including any interrupt taken first.
+    pub fn step(&mut self) -> Result<u64, NesError> {
+        let cycles = self.cycles;
 
-                if crossed_page {
-                    Ok(6)
-                } else {
-                    Ok(5)
-                }
+        self.poll_interrupts()?;
+
+        let code = self.bus.mem_read(self.program_counter)?;
+        let opcode = OpCodeDetail::from_opcode(&OpCode::from_code(&code)?);
+
+        self.run_opcode(&opcode)?;
+
+        Ok(self.cycles - cycles)
+    }
+
+    pub fn run(&mut self) -> Result<StopReason, NesError> {
+        self.run_with_callback(|_| {})
+    }
+
+    /// Step until `condition` holds, checking it before every instruction, e.g. until a blargg
+    /// test ROM writes its result to $6000. Gives up with `StopReason::Timeout` once `budget`
+    /// cycles have run, finishing the instruction that crosses it. BRK is run like any other
+    /// instruction, and a watchpoint still stops the run.
+    pub fn run_until<P>(&mut self, budget: u64, mut condition: P) -> Result<StopReason, NesError>
+    where
+        P: FnMut(&CPU<B>) -> bool,
+    {
+        let deadline = self.cycles.saturating_add(budget);
+
+        while self.cycles < deadline {
+            if condition(self) {
+                return Ok(StopReason::Condition);
+            }
+
+            self.step()?;
+
+            if let Some(hit) = self.bus.take_watch_hit() {
+                return Ok(StopReason::Watchpoint(hit));
             }
-            _ => Err(NesError::new(
-                "Trying to calculate cycles of an unsupported mode.",
-            )),
+        }
+
+        if condition(self) {
+            Ok(StopReason::Condition)
+        } else {
+            Ok(StopReason::Timeout)
         }
     }
 
-    pub fn run(&mut self) -> Result<(), NesError> {
-        self.run_with_callback(|_| {})?;
-        Ok(())
+    pub fn run_with_callback<F>(&mut self, mut callback: F) -> Result<StopReason, NesError>
+    where
+        F: FnMut(&mut CPU<B>),
+    {
+        self.try_run_with_callback(|cpu| {
+            callback(cpu);
+            Ok(())
+        })
     }
 
-    pub fn run_with_callback<F>(&mut self, mut callback: F) -> Result<(), NesError>
+    /// `run_with_callback` with a callback that can fail, which stops the run with its error, e.g.
+    /// when a trace can't be written.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(program_counter = self.program_counter),
+            ret
+        )
+    )]
+    pub fn try_run_with_callback<F>(&mut self, mut callback: F) -> Result<StopReason, NesError>
     where
-        F: FnMut(&mut CPU),
+        F: FnMut(&mut CPU<B>) -> Result<(), NesError>,
     {
-        let mut not_break = true;
+        let mut instructions: u64 = 0;
+
+        loop {
+            self.poll_interrupts()?;
 
-        while not_break {
             let code = self.bus.mem_read(self.program_counter)?;
             let opcode = OpCodeDetail::from_opcode(&OpCode::from_code(&code)?);
 
-            match opcode.instruction {
-                Instruction::BRK => break,
-                _ => {}
+            if let Instruction::BRK = opcode.instruction {
+                return Ok(StopReason::Break);
             };
 
-            callback(self);
+            if self.instruction_budget == Some(instructions) {
+                return Ok(StopReason::Timeout);
+            }
+
+            instructions += 1;
+
+            callback(self)?;
+
+            let program_counter = self.program_counter;
 
             self.run_opcode(&opcode)?;
-        }
 
-        Ok(())
+            if let Some(hit) = self.bus.take_watch_hit() {
+                return Ok(StopReason::Watchpoint(hit));
+            }
+
+            if self.skip_idle_loops
+                && self.program_counter <= program_counter
+                && self.check_idle_loop(program_counter)?
+            {
+                return Ok(StopReason::IdleLoop);
+            }
+        }
     }
 
-    pub fn run_opcode(&mut self, opcode: &OpCodeDetail) -> Result<(), NesError> {
+    /// Run one instruction and return the cycles it took.
+    pub(crate) fn run_opcode(&mut self, opcode: &OpCodeDetail) -> Result<u8, NesError> {
         let OpCodeDetail {
             instruction,
             bytes,
@@ -287,17 +624,23 @@ impl CPU {
         } = opcode;
 
         let bytes = *bytes;
+        let cycles = self.major_cycles(opcode)?;
+
+        self.bus
+            .begin_instruction(self.program_counter, self.instruction_count, self.cycles);
+
+        self.dummy_read(instruction, mode)?;
 
         match instruction {
             Instruction::ADC => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
-                self.addition_with_register_a(value as u16);
+                self.add_to_register_a(value);
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::AND => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let result = self.register_a & value;
 
@@ -309,7 +652,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::ASL => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let result = (value as u16) << 1;
 
@@ -320,9 +663,9 @@ impl CPU {
                         self.register_a = lo;
                     }
                     _ => {
-                        let address = self.get_operand_address(&mode)?;
+                        let address = self.get_operand_address(mode)?;
 
-                        self.bus.mem_write(address, lo)?;
+                        self.write_modified(address, value, lo)?;
                     }
                 }
 
@@ -332,35 +675,22 @@ impl CPU {
 
                 self.apply_bytes_to_program_counter(bytes);
             }
-            Instruction::BCC => {
-                let carry = self.status.read_flag(Flag::Carry);
-
-                if carry {
-                    self.apply_bytes_to_program_counter(bytes);
+            Instruction::BCC
+            | Instruction::BCS
+            | Instruction::BEQ
+            | Instruction::BMI
+            | Instruction::BNE
+            | Instruction::BPL
+            | Instruction::BVC
+            | Instruction::BVS => {
+                if self.branch_taken(instruction) {
+                    self.move_pointer_on_branch(mode, bytes)?;
                 } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
-            Instruction::BCS => {
-                let carry = self.status.read_flag(Flag::Carry);
-
-                if !carry {
                     self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
-            Instruction::BEQ => {
-                let zero = self.status.read_flag(Flag::Zero);
-
-                if !zero {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
                 }
             }
             Instruction::BIT => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let and_result = self.register_a & value;
 
@@ -372,35 +702,9 @@ impl CPU {
 
                 self.apply_bytes_to_program_counter(bytes);
             }
-            Instruction::BMI => {
-                let negative = self.status.read_flag(Flag::Negative);
-
-                if !negative {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
-            Instruction::BNE => {
-                let zero = self.status.read_flag(Flag::Zero);
-
-                if zero {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
-            Instruction::BPL => {
-                let negative = self.status.read_flag(Flag::Negative);
-
-                if negative {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
             Instruction::BRK => {
-                self.push_to_stack_u16(self.program_counter + 2)?;
+                // BRK is followed by a padding byte which the return address skips over.
+                self.push_to_stack_u16(self.program_counter.wrapping_add(bytes as u16))?;
 
                 let break_flag = self.status.read_flag(Flag::Break);
 
@@ -409,25 +713,18 @@ impl CPU {
 
                 self.status.set_flag(Flag::Break, break_flag);
 
-                self.program_counter = self.bus.mem_read_u16(0xfffe)?;
-            }
-            Instruction::BVC => {
-                let overflow = self.status.read_flag(Flag::Overflow);
+                let (kind, vector) = self.brk_vector();
+                let handler = self.bus.mem_read_u16(vector)?;
 
-                if overflow {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
-            }
-            Instruction::BVS => {
-                let overflow = self.status.read_flag(Flag::Overflow);
+                self.record_interrupt(InterruptEvent {
+                    kind,
+                    instruction: self.instruction_count,
+                    cycle: self.cycles,
+                    program_counter: self.program_counter,
+                    handler,
+                });
 
-                if !overflow {
-                    self.apply_bytes_to_program_counter(bytes);
-                } else {
-                    self.move_pointer_on_branch(&mode, bytes)?;
-                }
+                self.program_counter = handler;
             }
             Instruction::CLC => {
                 self.status.set_flag(Flag::Carry, false);
@@ -438,6 +735,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::CLI => {
+                self.delay_interrupt_flag();
                 self.status.set_flag(Flag::Interrupt, false);
                 self.apply_bytes_to_program_counter(bytes);
             }
@@ -447,30 +745,30 @@ impl CPU {
             }
             Instruction::CMP => {
                 let accumulator = self.register_a;
-                self.compare_to_memory(accumulator, &mode)?;
+                self.compare_to_memory(accumulator, mode)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::CPX => {
                 let accumulator = self.register_x;
-                self.compare_to_memory(accumulator, &mode)?;
+                self.compare_to_memory(accumulator, mode)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::CPY => {
                 let accumulator = self.register_y;
-                self.compare_to_memory(accumulator, &mode)?;
+                self.compare_to_memory(accumulator, mode)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::DEC => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let result = self.status.set_decrement_flags(value);
 
-                let address = self.get_operand_address(&mode)?;
+                let address = self.get_operand_address(mode)?;
 
-                self.bus.mem_write(address, result)?;
+                self.write_modified(address, value, result)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
@@ -493,7 +791,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::EOR => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let accumulator = self.register_a;
 
@@ -507,13 +805,13 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::INC => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let result = self.status.set_increment_flags(value);
 
-                let address = self.get_operand_address(&mode)?;
+                let address = self.get_operand_address(mode)?;
 
-                self.bus.mem_write(address, result)?;
+                self.write_modified(address, value, result)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
@@ -536,15 +834,15 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::JMP => {
-                self.jmp(&mode)?;
+                self.jmp(mode)?;
             }
             Instruction::JSR => {
                 self.push_to_stack_u16(self.program_counter.wrapping_add(2))?;
 
-                self.jmp(&mode)?;
+                self.jmp(mode)?;
             }
             Instruction::LDA => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 self.register_a = value;
                 self.status.set_zero_flag(value);
@@ -553,7 +851,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::LDX => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 self.register_x = value;
                 let result = self.register_x;
@@ -563,7 +861,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::LDY => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 self.register_y = value;
                 let result = self.register_y;
@@ -573,7 +871,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::LSR => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let carry_flag = value & 0b0000_0001;
                 let result = value >> 1;
@@ -583,9 +881,9 @@ impl CPU {
                         self.register_a = result;
                     }
                     _ => {
-                        let address = self.get_operand_address(&mode)?;
+                        let address = self.get_operand_address(mode)?;
 
-                        self.bus.mem_write(address, result)?;
+                        self.write_modified(address, value, result)?;
                     }
                 }
 
@@ -599,7 +897,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::ORA => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let result = self.register_a | value;
 
@@ -640,12 +938,13 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::PLP => {
+                self.delay_interrupt_flag();
                 self.plp()?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::ROL => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let carry_flag = value & 0b1000_0000;
                 let result = (value << 1) | (self.status.read_flag(Flag::Carry) as u8);
@@ -655,9 +954,9 @@ impl CPU {
                         self.register_a = result;
                     }
                     _ => {
-                        let address = self.get_operand_address(&mode)?;
+                        let address = self.get_operand_address(mode)?;
 
-                        self.bus.mem_write(address, result)?;
+                        self.write_modified(address, value, result)?;
                     }
                 }
 
@@ -668,7 +967,7 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::ROR => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
                 let carry_flag = value & 0b0000_0001;
                 let result = (value >> 1) | ((self.status.read_flag(Flag::Carry) as u8) << 7);
@@ -678,9 +977,9 @@ impl CPU {
                         self.register_a = result;
                     }
                     _ => {
-                        let address = self.get_operand_address(&mode)?;
+                        let address = self.get_operand_address(mode)?;
 
-                        self.bus.mem_write(address, result)?;
+                        self.write_modified(address, value, result)?;
                     }
                 }
 
@@ -700,14 +999,12 @@ impl CPU {
             Instruction::RTS => {
                 let program_counter = self.pull_from_stack_u16()?;
 
-                self.program_counter = program_counter + 1
+                self.program_counter = program_counter.wrapping_add(1)
             }
             Instruction::SBC => {
-                let value = self.get_operand_address_value(&mode)?;
+                let value = self.get_operand_address_value(mode)?;
 
-                let value = !value;
-
-                self.addition_with_register_a(value as u16);
+                self.subtract_from_register_a(value);
 
                 self.apply_bytes_to_program_counter(bytes);
             }
@@ -720,25 +1017,26 @@ impl CPU {
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::SEI => {
+                self.delay_interrupt_flag();
                 self.status.set_flag(Flag::Interrupt, true);
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::STA => {
-                let address = self.get_operand_address(&mode)?;
+                let address = self.get_operand_address(mode)?;
 
                 self.bus.mem_write(address, self.register_a)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::STX => {
-                let address = self.get_operand_address(&mode)?;
+                let address = self.get_operand_address(mode)?;
 
                 self.bus.mem_write(address, self.register_x)?;
 
                 self.apply_bytes_to_program_counter(bytes);
             }
             Instruction::STY => {
-                let address = self.get_operand_address(&mode)?;
+                let address = self.get_operand_address(mode)?;
 
                 self.bus.mem_write(address, self.register_y)?;
 
@@ -802,11 +1100,109 @@ impl CPU {
                 self.status.set_zero_flag(result);
                 self.status.set_negative_flag(result);
 
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::LAX => {
+                let value = self.get_operand_address_value(mode)?;
+
+                self.register_a = value;
+                self.register_x = value;
+                self.status.set_zero_flag(value);
+                self.status.set_negative_flag(value);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::SAX => {
+                let address = self.get_operand_address(mode)?;
+
+                self.bus
+                    .mem_write(address, self.register_a & self.register_x)?;
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::DCP => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = value.wrapping_sub(1);
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                self.compare(self.register_a, result);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::ISB => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = value.wrapping_add(1);
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                self.subtract_from_register_a(result);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::SLO => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = value << 1;
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                self.register_a |= result;
+                self.status.set_zero_flag(self.register_a);
+                self.status.set_negative_flag(self.register_a);
+                self.status.set_flag(Flag::Carry, value & 0b1000_0000 > 0);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::RLA => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = (value << 1) | (self.status.read_flag(Flag::Carry) as u8);
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                self.register_a &= result;
+                self.status.set_zero_flag(self.register_a);
+                self.status.set_negative_flag(self.register_a);
+                self.status.set_flag(Flag::Carry, value & 0b1000_0000 > 0);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::SRE => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = value >> 1;
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                self.register_a ^= result;
+                self.status.set_zero_flag(self.register_a);
+                self.status.set_negative_flag(self.register_a);
+                self.status.set_flag(Flag::Carry, value & 0b0000_0001 > 0);
+
+                self.apply_bytes_to_program_counter(bytes);
+            }
+            Instruction::RRA => {
+                let value = self.get_operand_address_value(mode)?;
+                let result = (value >> 1) | ((self.status.read_flag(Flag::Carry) as u8) << 7);
+
+                let address = self.get_operand_address(mode)?;
+                self.write_modified(address, value, result)?;
+
+                // The carry out of the rotate is the carry into the add
+                self.status.set_flag(Flag::Carry, value & 0b0000_0001 > 0);
+                self.add_to_register_a(result);
+
                 self.apply_bytes_to_program_counter(bytes);
             }
         };
 
-        Ok(())
+        self.instruction_count += 1;
+        self.cycles += cycles as u64;
+
+        Ok(cycles)
     }
 
     fn plp(&mut self) -> Result<(), NesError> {
@@ -824,10 +1220,223 @@ impl CPU {
     }
 
     fn jmp(&mut self, mode: &AddressingMode) -> Result<(), NesError> {
-        let address = self.get_operand_address(&mode)?;
+        let address = self.get_operand_address(mode)?;
 
         self.program_counter = address;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::cartridge::{Cartridge, PRG_ROM_PAGE_SIZE};
+    use crate::memory::{Mem, RAM};
+
+    /// Build a CPU with an empty NROM cartridge and `program` loaded into RAM at 0x0600.
+    pub fn cpu_with_program(program: &[u8]) -> CPU {
+        let mut contents: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+
+        contents.extend([0; 8]);
+        contents.extend([0; PRG_ROM_PAGE_SIZE]);
+
+        let mut bus = CpuBus::new(Cartridge::new(&contents).expect("Error making cartridge"));
+
+        for (index, byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + index as u16, *byte)
+                .expect("Error loading program");
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu
+    }
+
+    #[test]
+    fn test_instruction_budget() {
+        // LDA $10; BEQ -4
+        let mut cpu = cpu_with_program(&[0xa5, 0x10, 0xf0, 0xfc]);
+        cpu.instruction_budget = Some(100);
+
+        assert_eq!(cpu.run().unwrap(), StopReason::Timeout);
+        assert_eq!(cpu.instruction_count, 100);
+
+        assert_eq!(cpu.run().unwrap(), StopReason::Timeout);
+        assert_eq!(cpu.instruction_count, 200);
+    }
+
+    #[test]
+    fn test_run_until() {
+        // INC $10; JMP $0600
+        let mut cpu = cpu_with_program(&[0xe6, 0x10, 0x4c, 0x00, 0x06]);
+
+        assert_eq!(
+            cpu.run_until(100, |cpu| cpu.bus.ram()[0x10] == 5).unwrap(),
+            StopReason::Condition
+        );
+        // Five INCs of 5 cycles and four JMPs of 3
+        assert_eq!(cpu.instruction_count, 9);
+        assert_eq!(cpu.cycles, 37);
+
+        assert_eq!(
+            cpu.run_until(100, |cpu| cpu.bus.ram()[0x10] == 0).unwrap(),
+            StopReason::Timeout
+        );
+        // The budget runs out partway through the INC that starts on cycle 136
+        assert_eq!(cpu.cycles, 141);
+        assert_eq!(cpu.instruction_count, 35);
+    }
+
+    #[test]
+    fn test_callback_error_stops_run() {
+        // INC $10; JMP $0600
+        let mut cpu = cpu_with_program(&[0xe6, 0x10, 0x4c, 0x00, 0x06]);
+
+        let result = cpu.try_run_with_callback(|cpu| match cpu.instruction_count {
+            3 => Err(NesError::new("Disk full")),
+            _ => Ok(()),
+        });
+
+        assert_eq!(result.unwrap_err().message, "Disk full");
+        assert_eq!(cpu.instruction_count, 3);
+        assert_eq!(cpu.bus.ram()[0x10], 2);
+    }
+
+    #[test]
+    fn test_page_cross_cycles() {
+        // LDA $00F0,X; LDA $0000,X; STA $00F0,X; LDA ($10),Y
+        let mut cpu = cpu_with_program(&[
+            0xbd, 0xf0, 0x00, 0xbd, 0x00, 0x00, 0x9d, 0xf0, 0x00, 0xb1, 0x10,
+        ]);
+        cpu.register_x = 0x20;
+        cpu.register_y = 0x01;
+        cpu.bus.mem_write(0x0010, 0xff).unwrap();
+
+        assert_eq!(cpu.step().unwrap(), 5);
+        assert_eq!(cpu.step().unwrap(), 4);
+        assert_eq!(cpu.step().unwrap(), 5);
+        assert_eq!(cpu.step().unwrap(), 6);
+        assert_eq!(cpu.cycles, 20);
+    }
+
+    #[test]
+    fn test_branch_cycles() {
+        // BNE +0; BEQ +0; BNE -7, which lands on $05FF
+        let mut cpu = cpu_with_program(&[0xd0, 0x00, 0xf0, 0x00, 0xd0, 0xf9]);
+        cpu.status.set_flag(Flag::Zero, false);
+
+        assert_eq!(cpu.step().unwrap(), 3);
+        assert_eq!(cpu.step().unwrap(), 2);
+        assert_eq!(cpu.step().unwrap(), 4);
+        assert_eq!(cpu.program_counter, 0x05ff);
+    }
+
+    #[test]
+    fn test_indirect_jump_page_wrap() {
+        // JMP ($02FF)
+        let mut cpu = cpu_with_program(&[0x6c, 0xff, 0x02]);
+        cpu.bus.mem_write(0x02ff, 0x34).unwrap();
+        cpu.bus.mem_write(0x0200, 0x12).unwrap();
+        cpu.bus.mem_write(0x0300, 0x56).unwrap();
+
+        assert_eq!(
+            cpu.get_operand_address(&AddressingMode::Indirect).unwrap(),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn test_zero_page_pointer_wrap() {
+        // LDA ($FF),Y then LDA ($FE,X)
+        let mut cpu = cpu_with_program(&[0xb1, 0xff]);
+        cpu.bus.mem_write(0x00ff, 0x34).unwrap();
+        cpu.bus.mem_write(0x0000, 0x12).unwrap();
+        cpu.bus.mem_write(0x0100, 0x56).unwrap();
+        cpu.register_y = 0x01;
+
+        assert_eq!(
+            cpu.get_operand_address(&AddressingMode::IndirectY).unwrap(),
+            0x1235
+        );
+
+        cpu.bus.mem_write(0x0601, 0xfe).unwrap();
+        cpu.register_x = 0x01;
+
+        assert_eq!(
+            cpu.get_operand_address(&AddressingMode::IndirectX).unwrap(),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn test_absolute_operand_crosses_page() {
+        // LDA $1234 with its operand split across $06FF and $0700
+        let mut cpu = cpu_with_program(&[]);
+        cpu.program_counter = 0x06fe;
+        cpu.bus.mem_write(0x06ff, 0x34).unwrap();
+        cpu.bus.mem_write(0x0700, 0x12).unwrap();
+
+        assert_eq!(
+            cpu.get_operand_address(&AddressingMode::Absolute).unwrap(),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn test_clone_snapshot() {
+        // LDA #$01, STA $10
+        let mut cpu = cpu_with_program(&[0xa9, 0x01, 0x85, 0x10]);
+        let snapshot = cpu.clone();
+
+        assert_eq!(cpu, snapshot);
+
+        cpu.run().unwrap();
+
+        assert_ne!(cpu, snapshot);
+        assert_eq!(snapshot.bus.mem_read(0x0010).unwrap(), 0x00);
+        assert_eq!(cpu.bus.mem_read(0x0010).unwrap(), 0x01);
+    }
+
+    #[test]
+    fn test_generic_bus() {
+        let mut ram = RAM::new(0x10000);
+
+        // LDX #$05, DEX, BNE -3, STX $8000
+        for (index, byte) in [0xa2, 0x05, 0xca, 0xd0, 0xfd, 0x8e, 0x00, 0x80]
+            .iter()
+            .enumerate()
+        {
+            ram.mem_write(0x0200 + index as u16, *byte).unwrap();
+        }
+        ram.mem_write(0x8000, 0xff).unwrap();
+
+        let mut cpu = CPU::new(ram);
+        cpu.program_counter = 0x0200;
+
+        assert_eq!(cpu.run().unwrap(), StopReason::Break);
+        assert_eq!(cpu.bus.mem_read(0x8000).unwrap(), 0x00);
+    }
+
+    #[test]
+    fn test_brk_skips_padding_byte() {
+        // BRK, padding
+        let mut cpu = cpu_with_program(&[0x00, 0xff]);
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::X00))
+            .unwrap();
+        cpu.pull_from_stack().unwrap();
+
+        assert_eq!(cpu.pull_from_stack_u16().unwrap(), 0x0602);
+    }
+
+    #[test]
+    fn test_stops_on_brk() {
+        // INX; BRK
+        let mut cpu = cpu_with_program(&[0xe8, 0x00]);
+        cpu.instruction_budget = Some(100);
+
+        assert_eq!(cpu.run().unwrap(), StopReason::Break);
+        assert_eq!(cpu.register_x, 1);
+    }
+}
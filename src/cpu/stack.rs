@@ -1,8 +1,34 @@
+use crate::cpu::bus::Bus;
 use crate::cpu::CPU;
 use crate::errors::NesError;
-use crate::memory::Mem;
 
-impl CPU {
+/// Which way the stack pointer wrapped around page 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackWrap {
+    /// A push at $0100 wrapped the stack pointer to $FF.
+    Overflow,
+    /// A pull at $01FF wrapped the stack pointer to $00.
+    Underflow,
+}
+
+/// Called with the direction and the program counter of the instruction that wrapped the stack.
+pub type StackWrapListener = Box<dyn FnMut(StackWrap, u16)>;
+
+impl<B: Bus> CPU<B> {
+    /// Get told whenever the stack pointer wraps. Hardware carries on regardless, and so does the
+    /// emulator, but it usually means the game has gone wrong.
+    pub fn on_stack_wrap(&mut self, listener: StackWrapListener) {
+        self.stack_wrap_listeners.push(listener);
+    }
+
+    pub(crate) fn notify_stack_wrap(&mut self, wrap: StackWrap) {
+        let program_counter = self.program_counter;
+
+        for listener in self.stack_wrap_listeners.iter_mut() {
+            listener(wrap, program_counter);
+        }
+    }
+
     pub fn get_stack_address(&self) -> u16 {
         u16::from_le_bytes([self.stack_pointer, 0x01])
     }
@@ -11,7 +37,12 @@ impl CPU {
         let stack_address = self.get_stack_address();
 
         self.bus.mem_write(stack_address, data)?;
-        self.stack_pointer = self.stack_pointer - 1;
+
+        if self.stack_pointer == 0x00 {
+            self.notify_stack_wrap(StackWrap::Overflow);
+        }
+
+        self.stack_pointer = self.stack_pointer.wrapping_sub(1);
         Ok(())
     }
 
@@ -24,10 +55,14 @@ impl CPU {
     }
 
     pub fn pull_from_stack(&mut self) -> Result<u8, NesError> {
-        self.stack_pointer = self.stack_pointer + 1;
+        if self.stack_pointer == 0xff {
+            self.notify_stack_wrap(StackWrap::Underflow);
+        }
+
+        self.stack_pointer = self.stack_pointer.wrapping_add(1);
         let stack_address = self.get_stack_address();
 
-        Ok(self.bus.mem_read(stack_address)?)
+        self.bus.mem_read(stack_address)
     }
 
     pub fn pull_from_stack_u16(&mut self) -> Result<u16, NesError> {
@@ -37,3 +72,32 @@ impl CPU {
         Ok(u16::from_le_bytes([lo, hi]))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+
+    #[test]
+    fn test_stack_wraps() {
+        let mut cpu = cpu_with_program(&[]);
+
+        let wraps = Rc::new(RefCell::new(vec![]));
+        let recorded = wraps.clone();
+        cpu.on_stack_wrap(Box::new(move |wrap, _| recorded.borrow_mut().push(wrap)));
+
+        cpu.stack_pointer = 0x00;
+        cpu.push_to_stack(0x12).unwrap();
+
+        assert_eq!(cpu.stack_pointer, 0xff);
+        assert_eq!(cpu.bus.mem_read(0x0100).unwrap(), 0x12);
+
+        assert_eq!(cpu.pull_from_stack().unwrap(), 0x12);
+        assert_eq!(cpu.stack_pointer, 0x00);
+        assert_eq!(*wraps.borrow(), [StackWrap::Overflow, StackWrap::Underflow]);
+    }
+}
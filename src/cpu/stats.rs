@@ -0,0 +1,74 @@
+use crate::cpu::bus::Bus;
+use crate::cpu::interrupts::{InterruptEvent, InterruptKind};
+use crate::cpu::CPU;
+
+/// Running totals for profiling dashboards, see `CPU::stats`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub instructions_retired: u64,
+    pub nmis: u64,
+    pub irqs: u64,
+    pub brks: u64,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats::default()
+    }
+
+    pub(crate) fn count(&mut self, kind: InterruptKind) {
+        match kind {
+            InterruptKind::Nmi => self.nmis += 1,
+            InterruptKind::Irq(_) => self.irqs += 1,
+            InterruptKind::Brk => self.brks += 1,
+        }
+    }
+}
+
+impl<B: Bus> CPU<B> {
+    /// The totals since the CPU was created.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            instructions_retired: self.instruction_count,
+            ..self.stats
+        }
+    }
+
+    /// Count an interrupt and add it to the interrupt log.
+    pub(crate) fn record_interrupt(&mut self, event: InterruptEvent) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            kind = ?event.kind,
+        cycle = event.cycle,
+            program_counter = event.program_counter,
+            handler = event.handler,
+            "interrupt"
+        );
+
+        self.stats.count(event.kind);
+        self.interrupt_log.record(event);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::test::cpu_with_program;
+    use crate::opcodes::{OpCode, OpCodeDetail};
+
+    #[test]
+    fn test_stats() {
+        // NOP, BRK
+        let mut cpu = cpu_with_program(&[0xea, 0x00]);
+
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::Xea))
+            .unwrap();
+        cpu.run_opcode(&OpCodeDetail::from_opcode(&OpCode::X00))
+            .unwrap();
+
+        let stats = cpu.stats();
+
+        assert_eq!(stats.instructions_retired, 2);
+        assert_eq!(stats.brks, 1);
+        assert_eq!(stats.nmis, 0);
+    }
+}
@@ -1,9 +1,193 @@
+use std::io::Write;
+use std::ops::RangeInclusive;
+
 use crate::cpu::CPU;
+use crate::debugger::bank::BankedAddress;
 use crate::errors::NesError;
+use crate::json;
 use crate::memory::Mem;
 use crate::opcodes::{AddressingMode, Instruction, OpCode, OpCodeDetail};
 
+/// Decides which instructions get traced, so that long runs only log the region of interest.
+///
+/// With no ranges or banks added every instruction passes. A start trigger holds tracing off until
+/// the program counter first reaches it, and a stop trigger turns it off again for good. Triggers
+/// can be tied to a PRG ROM bank so that they only fire in the right copy of banked code.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TraceFilter {
+    ranges: Vec<RangeInclusive<u16>>,
+    banks: Vec<usize>,
+    start_trigger: Option<Trigger>,
+    stop_trigger: Option<Trigger>,
+    started: bool,
+    stopped: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Trigger {
+    address: u16,
+    /// None matches the address in any bank.
+    bank: Option<usize>,
+}
+
+impl Trigger {
+    fn matches(&self, address: BankedAddress) -> bool {
+        self.address == address.address && (self.bank.is_none() || self.bank == address.bank)
+    }
+}
+
+impl TraceFilter {
+    pub fn new() -> Self {
+        TraceFilter::default()
+    }
+
+    /// Only trace instructions with a program counter inside `start..=end`.
+    pub fn add_range(&mut self, start: u16, end: u16) {
+        self.ranges.push(start..=end);
+    }
+
+    /// Only trace instructions running from the given PRG ROM bank.
+    pub fn add_bank(&mut self, bank: usize) {
+        self.banks.push(bank);
+    }
+
+    /// Start tracing when the program counter hits `address`.
+    pub fn start_at(&mut self, address: u16) {
+        self.start_trigger = Some(Trigger {
+            address,
+            bank: None,
+        });
+    }
+
+    /// Start tracing when the program counter hits `address` while `bank` is mapped there.
+    pub fn start_at_bank(&mut self, bank: usize, address: u16) {
+        self.start_trigger = Some(Trigger {
+            address,
+            bank: Some(bank),
+        });
+    }
+
+    /// Stop tracing when the program counter hits `address`.
+    pub fn stop_at(&mut self, address: u16) {
+        self.stop_trigger = Some(Trigger {
+            address,
+            bank: None,
+        });
+    }
+
+    /// Stop tracing when the program counter hits `address` while `bank` is mapped there.
+    pub fn stop_at_bank(&mut self, bank: usize, address: u16) {
+        self.stop_trigger = Some(Trigger {
+            address,
+            bank: Some(bank),
+        });
+    }
+
+    /// Check the instruction the CPU is about to run, updating the triggers as we go.
+    pub fn should_trace(&mut self, cpu: &CPU) -> bool {
+        let program_counter = cpu.program_counter;
+        let banked = cpu.bus.banked_address(program_counter);
+
+        if self
+            .start_trigger
+            .is_some_and(|trigger| trigger.matches(banked))
+        {
+            self.started = true;
+        }
+
+        if self
+            .stop_trigger
+            .is_some_and(|trigger| trigger.matches(banked))
+        {
+            self.stopped = true;
+        }
+
+        if self.stopped || (self.start_trigger.is_some() && !self.started) {
+            return false;
+        }
+
+        let in_range = self.ranges.is_empty()
+            || self
+                .ranges
+                .iter()
+                .any(|range| range.contains(&program_counter));
+
+        let in_bank =
+            self.banks.is_empty() || banked.bank.is_some_and(|bank| self.banks.contains(&bank));
+
+        in_range && in_bank
+    }
+}
+
+/// Follows JSR/RTS, BRK/RTI and hardware interrupts through a run so each trace line can show how
+/// deeply nested in subroutines and interrupt handlers it is. It has to see every instruction the
+/// CPU runs, traced or not, or the depth drifts.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CallDepth {
+    depth: usize,
+    /// NMIs and IRQs the CPU had taken when it last looked.
+    interrupts: Option<u64>,
+}
+
+impl CallDepth {
+    pub fn new() -> Self {
+        CallDepth::default()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The depth of the instruction the CPU is about to run. A JSR is at the caller's depth and an
+    /// RTS at the subroutine's, so the two line up around the body.
+    pub fn observe(&mut self, cpu: &CPU) -> Result<usize, NesError> {
+        // A hardware interrupt is taken between instructions, so the first sign of one is its
+        // handler about to run
+        let stats = cpu.stats();
+        let interrupts = stats.nmis + stats.irqs;
+
+        if let Some(previous) = self.interrupts {
+            self.depth += interrupts.saturating_sub(previous) as usize;
+        }
+        self.interrupts = Some(interrupts);
+
+        let depth = self.depth;
+        let code = cpu.bus.mem_peek(cpu.program_counter)?;
+
+        match OpCodeDetail::from_opcode(&OpCode::from_code(&code)?).instruction {
+            Instruction::JSR | Instruction::BRK => self.depth += 1,
+            // A game that pops its return address to jump elsewhere can return more than it called
+            Instruction::RTS | Instruction::RTI => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+
+        Ok(depth)
+    }
+}
+
+/// A trace line indented two spaces for each level of `depth`.
+pub fn indent(line: &str, depth: usize) -> String {
+    format!("{}{}", "  ".repeat(depth), line)
+}
+
+/// A `format_trace_json` line with a `depth` field added to the front.
+pub fn with_depth(json: &str, depth: usize) -> String {
+    match json.strip_prefix('{') {
+        Some(fields) => format!("{{\"depth\":{},{}", depth, fields),
+        None => json.to_string(),
+    }
+}
+
 pub fn trace(cpu: &CPU) -> Result<String, NesError> {
+    let full_trace = format_trace(cpu)?;
+
+    println!("{}", full_trace);
+
+    Ok(full_trace)
+}
+
+/// The nestest.log style line for the instruction the CPU is about to run, without printing it.
+pub fn format_trace(cpu: &CPU) -> Result<String, NesError> {
     let mut full_trace = String::new();
 
     let program_counter = program_counter_string(cpu);
@@ -16,15 +200,62 @@ pub fn trace(cpu: &CPU) -> Result<String, NesError> {
     full_trace.push_str(&cpu_assembly);
     full_trace.push_str(&registers);
 
-    println!("{}", full_trace);
-
     Ok(full_trace)
 }
 
+/// The same information as `trace` as a single line of JSON, for tools that would rather not parse
+/// the nestest text format. The line is written to `output`.
+pub fn trace_json<W: Write>(cpu: &CPU, output: &mut W) -> Result<String, NesError> {
+    let json = format_trace_json(cpu)?;
+
+    writeln!(output, "{}", json).map_err(|error| NesError::new(&error.to_string()))?;
+
+    Ok(json)
+}
+
+pub fn format_trace_json(cpu: &CPU) -> Result<String, NesError> {
+    let code = cpu.bus.mem_peek(cpu.program_counter)?;
+    let opcode_detail = OpCodeDetail::from_opcode(&OpCode::from_code(&code)?);
+
+    let mut operands: Vec<String> = vec![];
+
+    for offset in 1..opcode_detail.bytes {
+        let operand = cpu
+            .bus
+            .mem_peek(cpu.program_counter.wrapping_add(offset as u16))?;
+        operands.push(operand.to_string());
+    }
+
+    let assembly = cpu_opcode_assembly_string(cpu)?;
+
+    let bank = match cpu.bus.prg_bank(cpu.program_counter) {
+        Some(bank) => bank.to_string(),
+        None => "null".to_string(),
+    };
+
+    let json = format!(
+        "{{\"pc\":{},\"bank\":{},\"opcode\":{},\"operands\":[{}],\"instruction\":\"{}\",\"assembly\":\"{}\",\"a\":{},\"x\":{},\"y\":{},\"p\":{},\"sp\":{},\"cycles\":{}}}",
+        cpu.program_counter,
+        bank,
+        code,
+        operands.join(","),
+        json::escape(opcode_detail.instruction.to_string()),
+        json::escape(assembly.trim_end()),
+        cpu.register_a,
+        cpu.register_x,
+        cpu.register_y,
+        cpu.status.get_status_byte(),
+        cpu.stack_pointer,
+        cpu.cycles,
+    );
+
+    Ok(json)
+}
+
 fn pad_string(string: String, length: usize) -> String {
     let mut extended_str = string;
     while extended_str.len() < length {
-        extended_str.push_str(" ")
+        extended_str.push(' ')
     }
 
     extended_str
@@ -37,7 +268,7 @@ fn program_counter_string(cpu: &CPU) -> String {
 fn cpu_opcode_string(cpu: &CPU) -> Result<String, NesError> {
     let mut opcode_string = "".to_string();
 
-    let opcode = cpu.bus.mem_read(cpu.program_counter)?;
+    let opcode = cpu.bus.mem_peek(cpu.program_counter)?;
     opcode_string.push_str(&format!("{:02X}", opcode));
 
     let opcode = OpCode::from_code(&opcode)?;
@@ -49,8 +280,8 @@ fn cpu_opcode_string(cpu: &CPU) -> Result<String, NesError> {
         | AddressingMode::AbsoluteY
         | AddressingMode::Indirect => opcode_string.push_str(&format!(
             " {:02X} {:02X}",
-            cpu.bus.mem_read(cpu.program_counter + 1)?,
-            cpu.bus.mem_read(cpu.program_counter + 2)?
+            cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
+            cpu.bus.mem_peek(cpu.program_counter.wrapping_add(2))?
         )),
         AddressingMode::ZeroPage
         | AddressingMode::ZeroPageX
@@ -60,18 +291,23 @@ fn cpu_opcode_string(cpu: &CPU) -> Result<String, NesError> {
         | AddressingMode::IndirectY
         | AddressingMode::Immediate => opcode_string.push_str(&format!(
             " {:02X}",
-            cpu.bus.mem_read(cpu.program_counter + 1)?
+            cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?
         )),
         AddressingMode::Implied | AddressingMode::Accumulator => {}
     };
 
-    Ok(pad_string(opcode_string, 10))
+    // nestest.log marks unofficial opcodes with a * in the last column of the bytes
+    if opcode_detail.unofficial {
+        Ok(pad_string(opcode_string, 9) + "*")
+    } else {
+        Ok(pad_string(opcode_string, 10))
+    }
 }
 
 fn cpu_opcode_assembly_string(cpu: &CPU) -> Result<String, NesError> {
     let mut opcode_string = "".to_string();
 
-    let opcode = cpu.bus.mem_read(cpu.program_counter)?;
+    let opcode = cpu.bus.mem_peek(cpu.program_counter)?;
     let opcode = OpCode::from_code(&opcode)?;
     let opcode_detail = OpCodeDetail::from_opcode(&opcode);
 
@@ -80,113 +316,115 @@ fn cpu_opcode_assembly_string(cpu: &CPU) -> Result<String, NesError> {
     match opcode_detail.address_mode {
         AddressingMode::Accumulator => opcode_string.push_str(" A"),
         AddressingMode::Absolute => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             match opcode_detail.instruction {
                 Instruction::JMP | Instruction::JSR => opcode_string.push_str(&format!(
                     " ${:04X}",
-                    cpu.bus.mem_read_u16(cpu.program_counter + 1)?
+                    cpu.bus.mem_peek_u16(cpu.program_counter.wrapping_add(1))?
                 )),
                 _ => opcode_string.push_str(&format!(" ${:04X} = {:02X}", address, value,)),
             }
         }
         AddressingMode::AbsoluteX => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " ${:04X},X @ {:04X} = {:02X}",
-                cpu.bus.mem_read_u16(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek_u16(cpu.program_counter.wrapping_add(1))?,
                 address,
                 value
             ))
         }
         AddressingMode::AbsoluteY => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " ${:04X},Y @ {:04X} = {:02X}",
-                cpu.bus.mem_read_u16(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek_u16(cpu.program_counter.wrapping_add(1))?,
                 address,
                 value
             ))
         }
         AddressingMode::Immediate => opcode_string.push_str(&format!(
             " #${:02X}",
-            cpu.bus.mem_read(cpu.program_counter + 1)?
+            cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?
         )),
         AddressingMode::Implied => {}
         AddressingMode::Indirect => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
             opcode_string.push_str(&format!(
                 " (${:04X}) = {:04X}",
-                cpu.bus.mem_read_u16(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek_u16(cpu.program_counter.wrapping_add(1))?,
                 address
             ))
         }
         AddressingMode::IndirectX => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " (${:02X},X) @ {:02X} = {:04X} = {:02X}",
-                cpu.bus.mem_read(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
                 cpu.bus
-                    .mem_read(cpu.program_counter + 1)?
+                    .mem_peek(cpu.program_counter.wrapping_add(1))?
                     .wrapping_add(cpu.register_x),
                 address,
                 value
             ))
         }
         AddressingMode::IndirectY => {
-            let address = cpu.get_operand_address(&opcode_detail.address_mode)?;
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let address = cpu.peek_operand_address(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " (${:02X}),Y = {:04X} @ {:04X} = {:02X}",
-                cpu.bus.mem_read(cpu.program_counter + 1)?,
-                cpu.bus.mem_read_u16_wrapping_boundary(
-                    cpu.bus.mem_read(cpu.program_counter + 1)? as u16
-                )?,
+                cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
+                cpu.peek_zero_page_pointer(cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?)?,
                 address,
                 value
             ))
         }
         AddressingMode::Relative => {
-            let offset = cpu.bus.mem_read(cpu.program_counter + 1)? as u16;
-            opcode_string.push_str(&format!(" ${:02X}", cpu.program_counter + 2 + offset))
+            let offset = cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))? as i8;
+            let target = cpu
+                .program_counter
+                .wrapping_add(2)
+                .wrapping_add(offset as u16);
+            opcode_string.push_str(&format!(" ${:04X}", target))
         }
         AddressingMode::ZeroPage => {
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " ${:02X} = {:02X}",
-                cpu.bus.mem_read(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
                 value
             ))
         }
         AddressingMode::ZeroPageX => {
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " ${:02X},X @ {:02X} = {:02X}",
-                cpu.bus.mem_read(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
                 cpu.bus
-                    .mem_read(cpu.program_counter + 1)?
+                    .mem_peek(cpu.program_counter.wrapping_add(1))?
                     .wrapping_add(cpu.register_x),
                 value
             ))
         }
         AddressingMode::ZeroPageY => {
-            let value = cpu.get_operand_address_value(&opcode_detail.address_mode)?;
+            let value = cpu.peek_operand_address_value(&opcode_detail.address_mode)?;
 
             opcode_string.push_str(&format!(
                 " ${:02X},Y @ {:02X} = {:02X}",
-                cpu.bus.mem_read(cpu.program_counter + 1)?,
+                cpu.bus.mem_peek(cpu.program_counter.wrapping_add(1))?,
                 cpu.bus
-                    .mem_read(cpu.program_counter + 1)?
+                    .mem_peek(cpu.program_counter.wrapping_add(1))?
                     .wrapping_add(cpu.register_y),
                 value
             ))
@@ -209,9 +447,172 @@ fn registers_string(cpu: &CPU) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
     use super::*;
-    use crate::bus::CpuBus;
-    use crate::cartridge::Cartridge;
+    use crate::cpu::test::cpu_with_program;
+    use crate::debugger::mmio::MmioLogger;
+    use crate::joypad::{Button, Joypad};
+
+    #[test]
+    fn test_trace_json() {
+        // LDX #$01
+        let mut cpu = cpu_with_program(&[0xa2, 0x01]);
+        cpu.cycles = 7;
+
+        let mut output = vec![];
+        let json = trace_json(&cpu, &mut output).expect("Error making trace");
+
+        assert_eq!(
+            json,
+            "{\"pc\":1536,\"bank\":null,\"opcode\":162,\"operands\":[1],\"instruction\":\"LDX\",\"assembly\":\"LDX #$01\",\"a\":0,\"x\":0,\"y\":0,\"p\":36,\"sp\":253,\"cycles\":7}"
+        );
+        assert_eq!(output, format!("{}\n", json).into_bytes());
+    }
+
+    #[test]
+    fn test_trace_does_not_read_pointers() {
+        // LDA ($10),Y; JMP ($0010)
+        let mut cpu = cpu_with_program(&[0xb1, 0x10, 0x6c, 0x10, 0x00]);
+        cpu.bus.mem_write(0x0010, 0x00).unwrap();
+        cpu.bus.mem_write(0x0011, 0x02).unwrap();
+
+        let reads = Rc::new(RefCell::new(0));
+        let counted = reads.clone();
+
+        let mut logger = MmioLogger::new(Box::new(move |_| *counted.borrow_mut() += 1));
+        logger.add_range(0x0000, 0x07ff);
+        cpu.bus.enable_mmio_log(logger);
+
+        assert!(format_trace(&cpu)
+            .unwrap()
+            .contains("($10),Y = 0200 @ 0200"));
+
+        cpu.program_counter = 0x0602;
+        assert!(format_trace(&cpu).unwrap().contains("($0010) = 0200"));
+
+        assert_eq!(*reads.borrow(), 0);
+    }
+
+    #[test]
+    fn test_trace_does_not_clock_controller() {
+        // LDA #$01, STA $4016, LSR A, STA $4016, LDA $4016
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x01, 0x8d, 0x16, 0x40, 0x4a, 0x8d, 0x16, 0x40, 0xad, 0x16, 0x40,
+        ]);
+        let joypad: &mut Joypad = cpu.bus.controller_mut(0).unwrap();
+        joypad.buttons.set(Button::B, true);
+
+        cpu.run_with_callback(|cpu| {
+            format_trace(cpu).unwrap();
+        })
+        .unwrap();
+
+        assert_eq!(cpu.register_a, 0x40);
+        assert_eq!(cpu.bus.mem_read(0x4016).unwrap(), 0x41);
+    }
+
+    #[test]
+    fn test_call_depth() {
+        // JSR $0604; BRK; INX; RTS
+        let mut cpu = cpu_with_program(&[0x20, 0x04, 0x06, 0x00, 0xe8, 0x60]);
+
+        let mut call_depth = CallDepth::new();
+        let mut lines: Vec<String> = vec![];
+        cpu.run_with_callback(|cpu| {
+            let depth = call_depth.observe(cpu).unwrap();
+            lines.push(indent(&format!("{:04X}", cpu.program_counter), depth));
+        })
+        .unwrap();
+
+        assert_eq!(lines, vec!["0600", "  0604", "  0605"]);
+        assert_eq!(call_depth.depth(), 0);
+
+        // An NMI with its handler at $0000: RTI
+        cpu.bus.mem_write(0x0000, 0x40).unwrap();
+        cpu.program_counter = 0x0604;
+        cpu.trigger_nmi();
+
+        lines.clear();
+        cpu.run_with_callback(|cpu| {
+            let depth = call_depth.observe(cpu).unwrap();
+            lines.push(indent(&format!("{:04X}", cpu.program_counter), depth));
+        })
+        .unwrap();
+
+        assert_eq!(lines, vec!["  0000", "0604", "0605"]);
+
+        assert_eq!(with_depth("{\"pc\":1536}", 2), "{\"depth\":2,\"pc\":1536}");
+    }
+
+    #[test]
+    fn test_filter_range() {
+        // LDX #$01; DEX; DEY
+        let mut cpu = cpu_with_program(&[0xa2, 0x01, 0xca, 0x88]);
+
+        let mut filter = TraceFilter::new();
+        filter.add_range(0x0602, 0x0602);
+
+        let mut traced: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| {
+            if filter.should_trace(cpu) {
+                traced.push(cpu.program_counter);
+            }
+        })
+        .expect("Error running cpu");
+
+        assert_eq!(traced, vec![0x0602]);
+    }
+
+    #[test]
+    fn test_filter_triggers() {
+        // LDX #$01; DEX; DEY; INY
+        let mut cpu = cpu_with_program(&[0xa2, 0x01, 0xca, 0x88, 0xc8]);
+
+        let mut filter = TraceFilter::new();
+        filter.start_at(0x0602);
+        filter.stop_at(0x0604);
+
+        let mut traced: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| {
+            if filter.should_trace(cpu) {
+                traced.push(cpu.program_counter);
+            }
+        })
+        .expect("Error running cpu");
+
+        assert_eq!(traced, vec![0x0602, 0x0603]);
+    }
+
+    #[test]
+    fn test_filter_bank() {
+        let mut cpu = cpu_with_program(&[]);
+
+        let mut filter = TraceFilter::new();
+        filter.add_bank(0);
+
+        assert!(!filter.should_trace(&cpu));
+
+        cpu.program_counter = 0xc000;
+
+        assert!(filter.should_trace(&cpu));
+    }
+
+    #[test]
+    fn test_filter_bank_trigger() {
+        let mut cpu = cpu_with_program(&[]);
+        cpu.program_counter = 0xc000;
+
+        let mut filter = TraceFilter::new();
+        filter.start_at_bank(1, 0xc000);
+
+        assert!(!filter.should_trace(&cpu));
+
+        filter.start_at_bank(0, 0xc000);
+
+        assert!(filter.should_trace(&cpu));
+    }
 
     // #[test]
     // fn test_format_trace() {
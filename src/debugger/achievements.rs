@@ -0,0 +1,91 @@
+//! The hooks a RetroAchievements runtime (rcheevos) needs: a way to read emulated memory, and a
+//! call once per frame to evaluate achievements in. Linking rcheevos itself is up to the frontend.
+
+use std::ffi::c_void;
+
+use crate::bus::CpuBus;
+use crate::debugger::conditions::ConditionEngine;
+use crate::errors::NesError;
+use crate::memory::Mem;
+
+/// Read `num_bytes` (1, 2 or 4) little endian bytes from the CPU address space, the way rcheevos
+/// expects. Reads are peeks so that achievements can't disturb the game, and anything that isn't
+/// readable (or is past $FFFF) reads as 0.
+pub fn peek_memory<M: Mem>(memory: &M, address: u32, num_bytes: u32) -> u32 {
+    let mut value = 0;
+
+    for offset in 0..num_bytes.min(4) {
+        let byte = address
+            .checked_add(offset)
+            .and_then(|address| u16::try_from(address).ok())
+            .and_then(|address| memory.mem_peek(address).ok())
+            .unwrap_or(0);
+
+        value |= (byte as u32) << (offset * 8);
+    }
+
+    value
+}
+
+/// A `rc_runtime_peek_t` for passing to `rc_runtime_do_frame`, with the bus as the user data.
+///
+/// # Safety
+///
+/// `user_data` must point to a `CpuBus` that outlives the call.
+pub unsafe extern "C" fn rc_peek(address: u32, num_bytes: u32, user_data: *mut c_void) -> u32 {
+    let bus = &*(user_data as *const CpuBus);
+
+    peek_memory(bus, address, num_bytes)
+}
+
+/// Called at the end of every frame with the bus, which is where `rc_runtime_do_frame` goes.
+pub type FrameHook = Box<dyn FnMut(&CpuBus)>;
+
+/// Runs the condition engine and any rcheevos runtime together once a frame.
+#[derive(Default)]
+pub struct Achievements {
+    pub conditions: ConditionEngine,
+    frame_hooks: Vec<FrameHook>,
+}
+
+impl Achievements {
+    pub fn new() -> Self {
+        Achievements::default()
+    }
+
+    pub fn on_frame(&mut self, hook: FrameHook) {
+        self.frame_hooks.push(hook);
+    }
+
+    /// Call when the frontend finishes a frame.
+    pub fn do_frame(&mut self, bus: &CpuBus) -> Result<(), NesError> {
+        self.conditions.evaluate(bus)?;
+
+        for hook in self.frame_hooks.iter_mut() {
+            hook(bus);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+
+    #[test]
+    fn test_rc_peek() {
+        let mut cpu = cpu_with_program(&[]);
+        cpu.bus.mem_write(0x0010, 0x34).unwrap();
+        cpu.bus.mem_write(0x0011, 0x12).unwrap();
+
+        let user_data = &mut cpu.bus as *mut CpuBus as *mut c_void;
+
+        assert_eq!(unsafe { rc_peek(0x0010, 1, user_data) }, 0x34);
+        assert_eq!(unsafe { rc_peek(0x0810, 2, user_data) }, 0x1234);
+        assert_eq!(unsafe { rc_peek(0x2000, 1, user_data) }, 0);
+        assert_eq!(unsafe { rc_peek(0x10000, 4, user_data) }, 0);
+        assert_eq!(unsafe { rc_peek(u32::MAX, 4, user_data) }, 0);
+    }
+}
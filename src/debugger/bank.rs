@@ -0,0 +1,96 @@
+use std::fmt::{Display, Formatter};
+
+use crate::bus::CpuBus;
+
+/// A CPU address together with the PRG ROM bank it is mapped to. Once a mapper can switch banks a
+/// bare $8000 could be any of several pieces of code, so traces and triggers need both. The bank is
+/// numbered the way the mapper numbers it, e.g. in 8KB for MMC3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BankedAddress {
+    /// None outside cartridge ROM, where there is only ever one thing at an address.
+    pub bank: Option<usize>,
+    pub address: u16,
+}
+
+impl Display for BankedAddress {
+    /// `02:8000` for ROM, `0600` for everything else.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.bank {
+            Some(bank) => write!(f, "{:02X}:{:04X}", bank, self.address),
+            None => write!(f, "{:04X}", self.address),
+        }
+    }
+}
+
+impl CpuBus {
+    /// Resolve an address through the mapper as it is currently set up.
+    pub fn banked_address(&self, address: u16) -> BankedAddress {
+        BankedAddress {
+            bank: self.prg_bank(address),
+            address,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::{Cartridge, PRG_ROM_PAGE_SIZE};
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+
+    /// A bus with 64KB of PRG ROM on `mapper`.
+    fn bus_with_mapper(mapper: u8) -> CpuBus {
+        let mut raw: Vec<u8> = vec![
+            0x4e,
+            0x45,
+            0x53,
+            0x1a,
+            0x04,
+            0x00,
+            mapper << 4,
+            mapper & 0xf0,
+        ];
+        raw.extend([0; 8]);
+        raw.extend(vec![0; 4 * PRG_ROM_PAGE_SIZE]);
+
+        CpuBus::new(Cartridge::new(&raw).unwrap())
+    }
+
+    #[test]
+    fn test_banked_address() {
+        let cpu = cpu_with_program(&[]);
+
+        assert_eq!(cpu.bus.banked_address(0x0600).to_string(), "0600");
+        assert_eq!(cpu.bus.banked_address(0xc000).to_string(), "00:C000");
+    }
+
+    #[test]
+    fn test_8k_banks() {
+        let mut bus = bus_with_mapper(4);
+
+        // R6 and R7 select the 8KB banks at $8000 and $A000
+        bus.mem_write(0x8000, 6).unwrap();
+        bus.mem_write(0x8001, 4).unwrap();
+        bus.mem_write(0x8000, 7).unwrap();
+        bus.mem_write(0x8001, 5).unwrap();
+
+        assert_eq!(bus.banked_address(0x8000).to_string(), "04:8000");
+        assert_eq!(bus.banked_address(0xa000).to_string(), "05:A000");
+        assert_eq!(bus.banked_address(0xe000).to_string(), "07:E000");
+    }
+
+    #[test]
+    fn test_mixed_bank_sizes() {
+        let mut bus = bus_with_mapper(24);
+
+        // A 16KB bank at $8000 and an 8KB one at $C000
+        bus.mem_write(0x8000, 1).unwrap();
+        bus.mem_write(0xc000, 5).unwrap();
+
+        assert_eq!(bus.cartridge.prg_bank_size(0x8000), PRG_ROM_PAGE_SIZE);
+        assert_eq!(bus.banked_address(0xa000).to_string(), "01:A000");
+        assert_eq!(bus.banked_address(0xc000).to_string(), "05:C000");
+        assert_eq!(bus.banked_address(0xe000).to_string(), "07:E000");
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use crate::errors::NesError;
+use crate::memory::Mem;
+
+/// One side of a comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    /// The byte at an address now.
+    Memory(u16),
+    /// The byte at an address when the conditions were last evaluated.
+    Delta(u16),
+    Value(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn compare(&self, left: u8, right: u8) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A comparison between two operands, e.g. `Memory(0x0075) > Delta(0x0075)` for "the level
+/// counter went up".
+///
+/// With `required_hits` set the condition only holds once the comparison has been true on that
+/// many evaluations, and then stays true until the rule is reset, the same as RetroAchievements
+/// hit counts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+    pub required_hits: u32,
+    hits: u32,
+}
+
+impl Condition {
+    pub fn new(left: Operand, comparison: Comparison, right: Operand) -> Self {
+        Condition {
+            left,
+            comparison,
+            right,
+            required_hits: 0,
+            hits: 0,
+        }
+    }
+
+    pub fn with_hits(mut self, required_hits: u32) -> Self {
+        self.required_hits = required_hits;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    conditions: Vec<Condition>,
+    triggered: bool,
+}
+
+/// Called with the id of a rule when all of its conditions hold.
+pub type RuleListener = Box<dyn FnMut(usize)>;
+
+/// Rules over RAM values, checked once a frame (or whenever the caller likes). A rule fires the
+/// first time all of its conditions hold together and not again until it is reset, which is what
+/// achievements and scripted test assertions both want.
+#[derive(Default)]
+pub struct ConditionEngine {
+    rules: Vec<Rule>,
+    previous: HashMap<u16, u8>,
+    listeners: Vec<RuleListener>,
+}
+
+impl ConditionEngine {
+    pub fn new() -> Self {
+        ConditionEngine::default()
+    }
+
+    /// Add a rule, returning the id its listeners will be called with.
+    pub fn add_rule(&mut self, conditions: Vec<Condition>) -> usize {
+        self.rules.push(Rule {
+            conditions,
+            triggered: false,
+        });
+
+        self.rules.len() - 1
+    }
+
+    /// Let a rule fire again, clearing its hit counts.
+    pub fn reset_rule(&mut self, id: usize) {
+        if let Some(rule) = self.rules.get_mut(id) {
+            rule.triggered = false;
+
+            for condition in rule.conditions.iter_mut() {
+                condition.hits = 0;
+            }
+        }
+    }
+
+    pub fn on_trigger(&mut self, listener: RuleListener) {
+        self.listeners.push(listener);
+    }
+
+    /// Check every rule against memory. Reads are peeks, so devices don't see them.
+    pub fn evaluate<M: Mem>(&mut self, memory: &M) -> Result<(), NesError> {
+        let mut current: HashMap<u16, u8> = HashMap::new();
+
+        for rule in self.rules.iter() {
+            for condition in rule.conditions.iter() {
+                for operand in [condition.left, condition.right] {
+                    if let Operand::Memory(address) | Operand::Delta(address) = operand {
+                        current.insert(address, memory.mem_peek(address)?);
+                    }
+                }
+            }
+        }
+
+        let mut fired = vec![];
+
+        for (id, rule) in self.rules.iter_mut().enumerate() {
+            if rule.triggered {
+                continue;
+            }
+
+            let mut all_true = true;
+
+            for condition in rule.conditions.iter_mut() {
+                let left = resolve(condition.left, &current, &self.previous);
+                let right = resolve(condition.right, &current, &self.previous);
+
+                let mut holds = condition.comparison.compare(left, right);
+
+                if condition.required_hits > 0 {
+                    if holds && condition.hits < condition.required_hits {
+                        condition.hits += 1;
+                    }
+
+                    holds = condition.hits >= condition.required_hits;
+                }
+
+                all_true &= holds;
+            }
+
+            if all_true {
+                rule.triggered = true;
+                fired.push(id);
+            }
+        }
+
+        self.previous = current;
+
+        for id in fired {
+            for listener in self.listeners.iter_mut() {
+                listener(id);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deltas of addresses we haven't seen before read as their current value, so nothing looks like
+/// it changed on the first evaluation.
+fn resolve(operand: Operand, current: &HashMap<u16, u8>, previous: &HashMap<u16, u8>) -> u8 {
+    match operand {
+        Operand::Memory(address) => current[&address],
+        Operand::Delta(address) => *previous.get(&address).unwrap_or(&current[&address]),
+        Operand::Value(value) => value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::memory::RAM;
+
+    #[test]
+    fn test_rules() {
+        let mut ram = RAM::new(0x100);
+
+        let mut engine = ConditionEngine::new();
+        let level_up = engine.add_rule(vec![Condition::new(
+            Operand::Memory(0x10),
+            Comparison::Greater,
+            Operand::Delta(0x10),
+        )]);
+        let held = engine.add_rule(vec![Condition::new(
+            Operand::Memory(0x20),
+            Comparison::Equal,
+            Operand::Value(0x05),
+        )
+        .with_hits(3)]);
+
+        let fired = Rc::new(RefCell::new(vec![]));
+        let recorded = fired.clone();
+        engine.on_trigger(Box::new(move |id| recorded.borrow_mut().push(id)));
+
+        ram.mem_write(0x10, 1).unwrap();
+        engine.evaluate(&ram).unwrap();
+        assert!(fired.borrow().is_empty());
+
+        ram.mem_write(0x10, 2).unwrap();
+        ram.mem_write(0x20, 5).unwrap();
+        engine.evaluate(&ram).unwrap();
+        engine.evaluate(&ram).unwrap();
+        assert_eq!(*fired.borrow(), [level_up]);
+
+        engine.evaluate(&ram).unwrap();
+        engine.evaluate(&ram).unwrap();
+        assert_eq!(*fired.borrow(), [level_up, held]);
+
+        engine.reset_rule(level_up);
+        ram.mem_write(0x10, 3).unwrap();
+        engine.evaluate(&ram).unwrap();
+        assert_eq!(*fired.borrow(), [level_up, held, level_up]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+
+use crate::cpu::CPU;
+use crate::hash::Fnv1a;
+use crate::savestate;
+
+/// One way in which two machine states differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difference {
+    Register {
+        name: &'static str,
+        left: u16,
+        right: u16,
+    },
+    /// A byte of internal RAM, by its address in $0000-$07FF.
+    Ram { address: u16, left: u8, right: u8 },
+    /// A byte of PRG RAM, by its address in $6000-$7FFF.
+    Sram { address: u16, left: u8, right: u8 },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::Register { name, left, right } => {
+                write!(f, "{:<8} {:04X} != {:04X}", name, left, right)
+            }
+            Difference::Ram {
+                address,
+                left,
+                right,
+            }
+            | Difference::Sram {
+                address,
+                left,
+                right,
+            } => write!(f, "${:04X}    {:02X} != {:02X}", address, left, right),
+        }
+    }
+}
+
+/// Every difference between two machines, registers first and then memory in address order.
+/// Useful for finding where two runs that should be identical (netplay, movie playback) desynced.
+pub fn diff(left: &CPU, right: &CPU) -> Vec<Difference> {
+    let registers = [
+        ("A", left.register_a as u16, right.register_a as u16),
+        ("X", left.register_x as u16, right.register_x as u16),
+        ("Y", left.register_y as u16, right.register_y as u16),
+        (
+            "P",
+            left.status.get_status_byte() as u16,
+            right.status.get_status_byte() as u16,
+        ),
+        ("SP", left.stack_pointer as u16, right.stack_pointer as u16),
+        ("PC", left.program_counter, right.program_counter),
+    ];
+
+    let mut differences: Vec<Difference> = registers
+        .into_iter()
+        .filter(|(_, left, right)| left != right)
+        .map(|(name, left, right)| Difference::Register { name, left, right })
+        .collect();
+
+    differences.extend(
+        memory_differences(left.bus.ram(), right.bus.ram(), 0x0000).map(
+            |(address, left, right)| Difference::Ram {
+                address,
+                left,
+                right,
+            },
+        ),
+    );
+
+    differences.extend(
+        memory_differences(left.bus.sram(), right.bus.sram(), 0x6000).map(
+            |(address, left, right)| Difference::Sram {
+                address,
+                left,
+                right,
+            },
+        ),
+    );
+
+    differences
+}
+
+impl CPU {
+    /// A digest of everything a save state keeps, plus pending interrupts and the devices on the
+    /// controller and expansion ports, cheap enough to take every frame. If two machines' hashes
+    /// differ then so do the machines.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+
+        hasher.write(&savestate::cpu_payload(self));
+        hasher.write(&self.interrupt_state());
+        hasher.write(self.bus.ram());
+        hasher.write(&savestate::cartridge_payload(&self.bus.cartridge));
+        hasher.write(&self.bus.interrupt_state());
+
+        // Devices have no serialised form, but their Debug output shows every field.
+        for port in 0..2 {
+            hasher.write(format!("{:?}", self.bus.controller(port)).as_bytes());
+        }
+        hasher.write(format!("{:?}", self.bus.expansion).as_bytes());
+        hasher.write(format!("{:?}", self.bus.vs_system).as_bytes());
+
+        hasher.finish()
+    }
+}
+
+fn memory_differences<'a>(
+    left: &'a [u8],
+    right: &'a [u8],
+    start: u16,
+) -> impl Iterator<Item = (u16, u8, u8)> + 'a {
+    left.iter()
+        .zip(right)
+        .enumerate()
+        .filter(|(_, (left, right))| left != right)
+        .map(move |(offset, (left, right))| (start + offset as u16, *left, *right))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::joypad::{Button, Joypad};
+
+    #[test]
+    fn test_state_hash_covers_hidden_state() {
+        let cpu = cpu_with_program(&[0xea]);
+        let hash = cpu.state_hash();
+
+        let mut later = cpu.clone();
+        later.cycles += 1;
+        assert_ne!(later.state_hash(), hash);
+
+        let mut interrupted = cpu.clone();
+        interrupted.trigger_nmi();
+        assert_ne!(interrupted.state_hash(), hash);
+
+        let mut pressed = cpu.clone();
+        pressed
+            .bus
+            .controller_mut::<Joypad>(0)
+            .unwrap()
+            .buttons
+            .set(Button::A, true);
+        assert_ne!(pressed.state_hash(), hash);
+
+        assert_eq!(cpu.clone().state_hash(), hash);
+    }
+
+    #[test]
+    fn test_diff() {
+        // LDA #$01, STA $10, STA $6000
+        let mut cpu = cpu_with_program(&[0xa9, 0x01, 0x85, 0x10, 0x8d, 0x00, 0x60]);
+        let snapshot = cpu.clone();
+
+        assert!(diff(&cpu, &snapshot).is_empty());
+        assert_eq!(cpu.state_hash(), snapshot.state_hash());
+
+        cpu.run().unwrap();
+
+        assert_ne!(cpu.state_hash(), snapshot.state_hash());
+
+        assert_eq!(
+            diff(&snapshot, &cpu),
+            [
+                Difference::Register {
+                    name: "A",
+                    left: 0x00,
+                    right: 0x01
+                },
+                Difference::Register {
+                    name: "PC",
+                    left: 0x0600,
+                    right: 0x0607
+                },
+                Difference::Ram {
+                    address: 0x0010,
+                    left: 0x00,
+                    right: 0x01
+                },
+                Difference::Sram {
+                    address: 0x6000,
+                    left: 0x00,
+                    right: 0x01
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use crate::bus::{canonical_address, CpuBus};
+use crate::errors::NesError;
+use crate::memory::Mem;
+
+impl CpuBus {
+    /// Lock `address` to `value`, the classic infinite lives trainer. The value is written now,
+    /// and any later write to the address (or one of its mirrors) writes `value` instead.
+    pub fn freeze(&mut self, address: u16, value: u8) -> Result<(), NesError> {
+        self.unfreeze(address);
+        self.frozen.push((canonical_address(address), value));
+        self.mem_write(address, value)
+    }
+
+    /// Let the game write to `address` again. The value it was frozen at stays until it does.
+    pub fn unfreeze(&mut self, address: u16) {
+        let address = canonical_address(address);
+        self.frozen.retain(|(frozen, _)| *frozen != address);
+    }
+
+    /// The frozen addresses and the values they are held at.
+    pub fn frozen(&self) -> &[(u16, u8)] {
+        &self.frozen
+    }
+
+    pub(crate) fn frozen_value(&self, address: u16) -> Option<u8> {
+        let address = canonical_address(address);
+
+        self.frozen
+            .iter()
+            .find(|(frozen, _)| *frozen == address)
+            .map(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+
+    #[test]
+    fn test_freeze() {
+        // LDA #$00; STA $0810; DEC $10
+        let mut cpu = cpu_with_program(&[0xa9, 0x00, 0x8d, 0x10, 0x08, 0xc6, 0x10]);
+        cpu.bus.freeze(0x0010, 0x09).unwrap();
+
+        cpu.run().unwrap();
+
+        assert_eq!(cpu.bus.mem_read(0x0010).unwrap(), 0x09);
+
+        cpu.bus.unfreeze(0x0810);
+        cpu.bus.mem_write(0x0010, 0x01).unwrap();
+
+        assert_eq!(cpu.bus.mem_read(0x0010).unwrap(), 0x01);
+        assert!(cpu.bus.frozen().is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::cpu::CPU;
+use crate::debugger::diff::{diff, Difference};
+use crate::errors::NesError;
+
+/// Where two machines that should have behaved identically first stopped doing so.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// The instruction count after which the states no longer matched.
+    pub instruction: u64,
+    /// The address of the instruction that made them differ, on the left machine.
+    pub program_counter: u16,
+    pub differences: Vec<Difference>,
+    /// The error the left machine stopped with, when only one of them did or they failed
+    /// differently.
+    pub left_error: Option<String>,
+    /// The same for the right machine.
+    pub right_error: Option<String>,
+}
+
+/// Run two machines side by side for up to `instructions` instructions, comparing state hashes
+/// after every one. Used to check that a refactor or a different `Accuracy` doesn't change what a
+/// ROM does.
+///
+/// `input` is called for both machines before each instruction, with the instruction count, so
+/// they can be fed the same controller input.
+///
+/// An error on one machine only is a divergence. The same error on both is returned.
+pub fn run_lockstep<F>(
+    left: &mut CPU,
+    right: &mut CPU,
+    instructions: u64,
+    mut input: F,
+) -> Result<Option<Divergence>, NesError>
+where
+    F: FnMut(u64, &mut CPU),
+{
+    for _ in 0..instructions {
+        let program_counter = left.program_counter;
+
+        input(left.instruction_count, left);
+        input(right.instruction_count, right);
+
+        let (left_error, right_error) = match (left.step(), right.step()) {
+            (Err(error), Err(other)) if error.message == other.message => return Err(error),
+            (left_result, right_result) => (
+                left_result.err().map(|error| error.message),
+                right_result.err().map(|error| error.message),
+            ),
+        };
+
+        if left_error.is_some() || right_error.is_some() || left.state_hash() != right.state_hash()
+        {
+            return Ok(Some(Divergence {
+                instruction: left.instruction_count,
+                program_counter,
+                differences: diff(left, right),
+                left_error,
+                right_error,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::ErrorPolicy;
+    use crate::cpu::accuracy::Accuracy;
+    use crate::cpu::test::cpu_with_program;
+    use crate::joypad::{Button, Joypad};
+
+    #[test]
+    fn test_run_lockstep() {
+        // LDA #$01; STA $4016; LDA #$00; STA $4016; LDA $4016; ASL $10
+        let program = [
+            0xa9, 0x01, 0x8d, 0x16, 0x40, 0xa9, 0x00, 0x8d, 0x16, 0x40, 0xad, 0x16, 0x40, 0x06,
+            0x10,
+        ];
+
+        let mut left = cpu_with_program(&program);
+        let mut right = cpu_with_program(&program);
+        right.accuracy = Accuracy::Fast;
+
+        assert_eq!(
+            run_lockstep(&mut left, &mut right, 6, |_, _| {}).unwrap(),
+            None
+        );
+
+        let mut left = cpu_with_program(&program);
+        let mut right = cpu_with_program(&program);
+        right.accuracy = Accuracy::Fast;
+
+        // Only the right machine has A held. The controller is part of the state hash, so they
+        // diverge on the first instruction, before the read from $4016 shows it.
+        let divergence = run_lockstep(&mut left, &mut right, 6, |_, cpu| {
+            if cpu.accuracy == Accuracy::Fast {
+                let joypad: &mut Joypad = cpu.bus.controller_mut(0).unwrap();
+                joypad.buttons.set(Button::A, true);
+            }
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(divergence.instruction, 1);
+        assert_eq!(divergence.program_counter, 0x0600);
+
+        let mut left = cpu_with_program(&program);
+        let mut right = cpu_with_program(&program);
+        right.accuracy = Accuracy::Fast;
+
+        // Only the right machine has $10 poked before the ASL.
+        let divergence = run_lockstep(&mut left, &mut right, 6, |count, cpu| {
+            if cpu.accuracy == Accuracy::Fast && count == 5 {
+                cpu.bus.ram_mut()[0x10] = 0x01;
+            }
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(divergence.instruction, 6);
+        assert_eq!(divergence.program_counter, 0x060d);
+        assert_eq!(
+            divergence.differences,
+            [
+                Difference::Register {
+                    name: "P",
+                    left: 0x26,
+                    right: 0x24
+                },
+                Difference::Ram {
+                    address: 0x0010,
+                    left: 0x00,
+                    right: 0x02
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_one_sided_error() {
+        // LDA #$01; STA $2000, which only the right machine lets through
+        let program = [0xa9, 0x01, 0x8d, 0x00, 0x20];
+
+        let mut left = cpu_with_program(&program);
+        let mut right = cpu_with_program(&program);
+        right.bus.error_policy = ErrorPolicy::Continue;
+
+        let divergence = run_lockstep(&mut left, &mut right, 2, |_, _| {})
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(divergence.instruction, 1);
+        assert_eq!(divergence.program_counter, 0x0602);
+        assert_eq!(
+            divergence.left_error.as_deref(),
+            Some("PPU not implemented yet.")
+        );
+        assert_eq!(divergence.right_error, None);
+
+        // Both stop the same way
+        let mut left = cpu_with_program(&program);
+        let mut right = cpu_with_program(&program);
+
+        assert_eq!(
+            run_lockstep(&mut left, &mut right, 2, |_, _| {})
+                .unwrap_err()
+                .message,
+            "PPU not implemented yet."
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+
+use crate::bus::CpuBus;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A single CPU access to a logged address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MmioEvent {
+    /// The number of instructions the CPU had run before the one making the access.
+    pub instruction: u64,
+    /// The CPU cycle the instruction making the access started on.
+    pub cycle: u64,
+    /// The address of the instruction making the access.
+    pub program_counter: u16,
+    pub address: u16,
+    pub value: u8,
+    pub access: Access,
+}
+
+pub type MmioSink = Box<dyn FnMut(MmioEvent)>;
+
+/// Sends every CPU read and write in a set of address ranges to a sink, e.g. all PPU register
+/// writes with `add_range(0x2000, 0x2007)`. Peeks from traces and debuggers aren't logged.
+/// [`crate::registers`] has the register addresses by name.
+pub struct MmioLogger {
+    ranges: Vec<RangeInclusive<u16>>,
+    // Reads only have `&self`, so the sink has to be borrowed mutably through a RefCell.
+    sink: RefCell<MmioSink>,
+    instruction: u64,
+    cycle: u64,
+    program_counter: u16,
+}
+
+impl MmioLogger {
+    pub fn new(sink: MmioSink) -> Self {
+        MmioLogger {
+            ranges: vec![],
+            sink: RefCell::new(sink),
+            instruction: 0,
+            cycle: 0,
+            program_counter: 0,
+        }
+    }
+
+    /// Log accesses to `start..=end`.
+    pub fn add_range(&mut self, start: u16, end: u16) {
+        self.ranges.push(start..=end);
+    }
+
+    pub(crate) fn begin_instruction(&mut self, program_counter: u16, instruction: u64, cycle: u64) {
+        self.program_counter = program_counter;
+        self.instruction = instruction;
+        self.cycle = cycle;
+    }
+
+    pub(crate) fn log(&self, address: u16, value: u8, access: Access) {
+        if !self.ranges.iter().any(|range| range.contains(&address)) {
+            return;
+        }
+
+        (self.sink.borrow_mut())(MmioEvent {
+            instruction: self.instruction,
+            cycle: self.cycle,
+            program_counter: self.program_counter,
+            address,
+            value,
+            access,
+        });
+    }
+}
+
+impl CpuBus {
+    /// Start logging memory mapped I/O, replacing any logger already attached.
+    pub fn enable_mmio_log(&mut self, logger: MmioLogger) {
+        self.mmio_logger = Some(logger);
+    }
+
+    /// Stop logging, handing back the logger.
+    pub fn disable_mmio_log(&mut self) -> Option<MmioLogger> {
+        self.mmio_logger.take()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::registers::ApuRegister;
+
+    #[test]
+    fn test_mmio_log() {
+        // LDA #$01, STA $4016, STA $10, LDA $4017
+        let mut cpu =
+            cpu_with_program(&[0xa9, 0x01, 0x8d, 0x16, 0x40, 0x85, 0x10, 0xad, 0x17, 0x40]);
+
+        let events = Rc::new(RefCell::new(vec![]));
+        let recorded = events.clone();
+
+        let mut logger = MmioLogger::new(Box::new(move |event| recorded.borrow_mut().push(event)));
+        logger.add_range(
+            ApuRegister::Joypad1.address(),
+            ApuRegister::Joypad2.address(),
+        );
+        cpu.bus.enable_mmio_log(logger);
+
+        cpu.run_with_callback(|cpu| {
+            crate::cpu::trace::format_trace(cpu).unwrap();
+        })
+        .unwrap();
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                MmioEvent {
+                    instruction: 1,
+                    cycle: 2,
+                    program_counter: 0x0602,
+                    address: ApuRegister::Joypad1.address(),
+                    value: 0x01,
+                    access: Access::Write,
+                },
+                MmioEvent {
+                    instruction: 3,
+                    cycle: 9,
+                    program_counter: 0x0607,
+                    address: ApuRegister::Joypad2.address(),
+                    value: 0x40,
+                    access: Access::Read,
+                },
+            ]
+        );
+    }
+}
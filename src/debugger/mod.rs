@@ -0,0 +1,14 @@
+//! Tools for looking inside and poking at a running machine, for debuggers, save editors and
+//! randomizers.
+
+#[cfg(feature = "rcheevos")]
+pub mod achievements;
+pub mod bank;
+pub mod conditions;
+pub mod diff;
+pub mod freeze;
+pub mod lockstep;
+pub mod mmio;
+pub mod rewind;
+pub mod sram;
+pub mod watch;
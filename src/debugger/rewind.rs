@@ -0,0 +1,116 @@
+use std::collections::VecDeque;
+
+use crate::cpu::CPU;
+use crate::errors::NesError;
+
+/// Snapshots of the machine taken every `interval` instructions, so the debugger can step
+/// backwards by restoring the nearest one and running forwards again.
+///
+/// Re-execution replays whatever the controllers held when the snapshot was taken, so steps back
+/// over instructions that read input the user has since changed won't match the original run.
+pub struct Rewind {
+    interval: u64,
+    capacity: usize,
+    snapshots: VecDeque<CPU>,
+}
+
+impl Rewind {
+    /// Keep at most `capacity` snapshots, one every `interval` instructions.
+    pub fn new(interval: u64, capacity: usize) -> Self {
+        Rewind {
+            interval: interval.max(1),
+            capacity,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    /// Call before every instruction, e.g. from the `run_with_callback` callback.
+    pub fn record(&mut self, cpu: &CPU) {
+        if cpu.instruction_count % self.interval != 0 {
+            return;
+        }
+
+        if let Some(last) = self.snapshots.back() {
+            if last.instruction_count >= cpu.instruction_count {
+                // We've gone back in time, so anything after here is a future that won't happen.
+                self.snapshots
+                    .retain(|snapshot| snapshot.instruction_count < cpu.instruction_count);
+            }
+        }
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+
+        if self.capacity > 0 {
+            self.snapshots.push_back(cpu.clone());
+        }
+    }
+
+    /// Put the CPU back to how it was one instruction ago. Returns false if there is no snapshot
+    /// old enough, in which case the CPU is left alone.
+    ///
+    /// Listeners stay attached to the CPU but don't hear about the instructions being replayed.
+    pub fn step_back(&self, cpu: &mut CPU) -> Result<bool, NesError> {
+        let Some(target) = cpu.instruction_count.checked_sub(1) else {
+            return Ok(false);
+        };
+
+        let Some(snapshot) = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.instruction_count <= target)
+        else {
+            return Ok(false);
+        };
+
+        let mut replay = snapshot.clone();
+
+        while replay.instruction_count < target {
+            replay.step()?;
+        }
+
+        std::mem::swap(
+            &mut replay.stack_wrap_listeners,
+            &mut cpu.stack_wrap_listeners,
+        );
+        std::mem::swap(&mut replay.bus.sram_listeners, &mut cpu.bus.sram_listeners);
+        std::mem::swap(&mut replay.bus.mmio_logger, &mut cpu.bus.mmio_logger);
+
+        *cpu = replay;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+
+    #[test]
+    fn test_step_back() {
+        // LDX #$00; INX; STX $10; JMP $0602
+        let mut cpu = cpu_with_program(&[0xa2, 0x00, 0xe8, 0x86, 0x10, 0x4c, 0x02, 0x06]);
+        cpu.instruction_budget = Some(10);
+
+        let mut rewind = Rewind::new(4, 8);
+        let mut history: Vec<CPU> = vec![];
+
+        cpu.run_with_callback(|cpu| {
+            rewind.record(cpu);
+            history.push(cpu.clone());
+        })
+        .unwrap();
+
+        assert!(rewind.step_back(&mut cpu).unwrap());
+        assert_eq!(cpu, history[9]);
+
+        assert!(rewind.step_back(&mut cpu).unwrap());
+        assert_eq!(cpu, history[8]);
+
+        cpu = history[0].clone();
+        assert!(!rewind.step_back(&mut cpu).unwrap());
+    }
+}
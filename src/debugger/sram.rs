@@ -0,0 +1,101 @@
+use crate::bus::CpuBus;
+use crate::cartridge::save::SaveMemory;
+use crate::errors::NesError;
+
+/// Called with the offset into PRG RAM, the old value and the new value whenever a byte of it
+/// changes.
+pub type SramListener = Box<dyn FnMut(usize, u8, u8)>;
+
+impl CpuBus {
+    /// The cartridge's PRG RAM, mapped at $6000-$7fff.
+    pub fn sram(&self) -> &[u8] {
+        &self.cartridge.prg_ram
+    }
+
+    /// Change one byte of PRG RAM, as if the CPU had written it. Offsets past the end are ignored.
+    pub fn set_sram(&mut self, offset: usize, data: u8) {
+        let Some(byte) = self.cartridge.prg_ram.get_mut(offset) else {
+            return;
+        };
+
+        let old = *byte;
+        *byte = data;
+
+        if old != data {
+            for listener in self.sram_listeners.iter_mut() {
+                listener(offset, old, data);
+            }
+        }
+    }
+
+    /// Replace PRG RAM from the start with the contents of a save file, notifying listeners of
+    /// every byte that changes.
+    pub fn load_sram(&mut self, save: &[u8]) {
+        for (offset, data) in save.iter().enumerate() {
+            self.set_sram(offset, *data);
+        }
+    }
+
+    /// Load a whole save file, laid out as the cartridge's save regions describe. PRG RAM goes
+    /// through `load_sram`, so listeners hear about it.
+    pub fn load_save(&mut self, save: &[u8]) -> Result<(), NesError> {
+        if save.len() != self.cartridge.save_size() {
+            return Err(NesError::new(&format!(
+                "Save is {} bytes but the cartridge keeps {}",
+                save.len(),
+                self.cartridge.save_size()
+            )));
+        }
+
+        let mut rest = save;
+
+        for region in self.cartridge.save_regions.clone() {
+            let (data, remaining) = rest.split_at(region.size);
+            rest = remaining;
+
+            match region.memory {
+                SaveMemory::PrgRam => self.load_sram(data),
+                SaveMemory::ChrRam => {
+                    let length = data.len().min(self.cartridge.chr_ram.len());
+                    self.cartridge.chr_ram[..length].copy_from_slice(&data[..length]);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get told about every change to PRG RAM, whether from the running game or through
+    /// `set_sram`.
+    pub fn on_sram_change(&mut self, listener: SramListener) {
+        self.sram_listeners.push(listener);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+
+    #[test]
+    fn test_sram_change_notifications() {
+        // LDA #$42, STA $6010, STA $6010
+        let mut cpu = cpu_with_program(&[0xa9, 0x42, 0x8d, 0x10, 0x60, 0x8d, 0x10, 0x60]);
+
+        let changes = Rc::new(RefCell::new(vec![]));
+        let recorded = changes.clone();
+        cpu.bus.on_sram_change(Box::new(move |offset, old, new| {
+            recorded.borrow_mut().push((offset, old, new))
+        }));
+
+        cpu.run().unwrap();
+        cpu.bus.set_sram(0x11, 0x99);
+
+        assert_eq!(*changes.borrow(), [(0x10, 0x00, 0x42), (0x11, 0x00, 0x99)]);
+        assert_eq!(cpu.bus.mem_read(0x6011).unwrap(), 0x99);
+        assert_eq!(&cpu.bus.sram()[0x10..0x12], [0x42, 0x99]);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::bus::{canonical_address, CpuBus};
+use crate::cpu::WatchHit;
+
+impl CpuBus {
+    /// Stop `run_with_callback` after any instruction that writes to `address` or one of its
+    /// mirrors, so watching $0005 also catches $0805, $1005 and $1805.
+    pub fn watch_writes(&mut self, address: u16) {
+        let address = canonical_address(address);
+
+        if !self.write_watches.contains(&address) {
+            self.write_watches.push(address);
+        }
+    }
+
+    pub fn unwatch_writes(&mut self, address: u16) {
+        let address = canonical_address(address);
+        self.write_watches.retain(|watched| *watched != address);
+    }
+
+    /// The watched addresses, as their canonical mirror.
+    pub fn write_watches(&self) -> &[u16] {
+        &self.write_watches
+    }
+
+    pub(crate) fn check_write_watch(&mut self, address: u16, value: u8) {
+        if self.watch_hit.is_none() && self.write_watches.contains(&canonical_address(address)) {
+            self.watch_hit = Some(WatchHit { address, value });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::test::cpu_with_program;
+    use crate::cpu::StopReason;
+
+    use super::*;
+
+    #[test]
+    fn test_watch_mirrored_write() {
+        // LDA #$07; STA $0010; STA $1805; STA $0011
+        let mut cpu = cpu_with_program(&[
+            0xa9, 0x07, 0x8d, 0x10, 0x00, 0x8d, 0x05, 0x18, 0x8d, 0x11, 0x00,
+        ]);
+        cpu.bus.watch_writes(0x0805);
+        assert_eq!(cpu.bus.write_watches(), [0x0005]);
+
+        assert_eq!(
+            cpu.run().unwrap(),
+            StopReason::Watchpoint(WatchHit {
+                address: 0x1805,
+                value: 0x07
+            })
+        );
+        assert_eq!(cpu.program_counter, 0x0608);
+
+        cpu.bus.unwatch_writes(0x0005);
+        assert_eq!(cpu.run().unwrap(), StopReason::Break);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::bus::CpuBus;
+use crate::cartridge::Cartridge;
+use crate::cpu::{StopReason, CPU};
+use crate::errors::NesError;
+use crate::joypad::script::InputScript;
+use crate::joypad::Joypad;
+use crate::memory::Mem;
+
+/// Roughly how many instructions the CPU runs in an NTSC frame (29,780 cycles at about 3.5 cycles
+/// an instruction). There is no PPU to mark frames yet, so the harness counts instructions.
+pub const DEFAULT_INSTRUCTIONS_PER_FRAME: u64 = 8500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RamCheck {
+    frame: u64,
+    address: u16,
+    value: u8,
+}
+
+/// Boot a ROM, play scripted input into controller 1, and check RAM at given frames. Meant for
+/// game specific test crates, e.g. checking a randomizer seed gets as far as the title screen:
+/// ```no_run
+/// # use nes_emulator::demo::Demo;
+/// # let rom = vec![];
+/// Demo::load(&rom)?
+///     .with_input("120:Start")?
+///     .expect_ram(300, 0x0770, 0x01)
+///     .run()?;
+/// # Ok::<(), nes_emulator::errors::NesError>(())
+/// ```
+pub struct Demo {
+    pub cpu: CPU,
+    pub instructions_per_frame: u64,
+    script: InputScript,
+    checks: Vec<RamCheck>,
+}
+
+impl Demo {
+    /// Load an iNES file and reset the CPU, ready to play.
+    pub fn load(rom: &[u8]) -> Result<Self, NesError> {
+        let mut cpu = CPU::new(CpuBus::new(Cartridge::new(rom)?));
+        cpu.reset()?;
+
+        Ok(Demo {
+            cpu,
+            instructions_per_frame: DEFAULT_INSTRUCTIONS_PER_FRAME,
+            script: InputScript::default(),
+            checks: vec![],
+        })
+    }
+
+    /// Controller 1's input, in the `InputScript` format.
+    pub fn with_input(mut self, script: &str) -> Result<Self, NesError> {
+        self.script = InputScript::parse(script)?;
+        Ok(self)
+    }
+
+    /// Check that `address` holds `value` once `frame` has finished.
+    pub fn expect_ram(mut self, frame: u64, address: u16, value: u8) -> Self {
+        self.checks.push(RamCheck {
+            frame,
+            address,
+            value,
+        });
+        self
+    }
+
+    /// Play until the last check (or scripted input), failing on the first check that doesn't
+    /// hold. Returns the CPU so that callers can look around afterwards.
+    pub fn run(mut self) -> Result<CPU, NesError> {
+        let last_check = self.checks.iter().map(|check| check.frame).max();
+        let last_frame = last_check.max(self.script.last_frame()).unwrap_or(0);
+
+        let budget = self.cpu.instruction_budget;
+        self.cpu.instruction_budget = Some(self.instructions_per_frame);
+
+        for frame in 0..=last_frame {
+            if let Some(joypad) = self.cpu.bus.controller_mut::<Joypad>(0) {
+                joypad.buttons = self.script.buttons_for_frame(frame);
+            }
+
+            if let StopReason::Break = self.cpu.run()? {
+                return Err(NesError::new(&format!(
+                    "Hit BRK at {:04X} in frame {}",
+                    self.cpu.program_counter, frame
+                )));
+            }
+
+            for check in self.checks.iter().filter(|check| check.frame == frame) {
+                let value = self.cpu.bus.mem_peek(check.address)?;
+
+                if value != check.value {
+                    return Err(NesError::new(&format!(
+                        "Frame {}: expected {:02X} at {:04X} but found {:02X}",
+                        frame, check.value, check.address, value
+                    )));
+                }
+            }
+        }
+
+        self.cpu.instruction_budget = budget;
+
+        Ok(self.cpu)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::PRG_ROM_PAGE_SIZE;
+
+    #[test]
+    fn test_demo() {
+        // LDA #$01; STA $4016; LDA #$00; STA $4016; LDA $4016; STA $10; JMP $8000
+        let program = [
+            0xa9, 0x01, 0x8d, 0x16, 0x40, 0xa9, 0x00, 0x8d, 0x16, 0x40, 0xad, 0x16, 0x40, 0x85,
+            0x10, 0x4c, 0x00, 0x80,
+        ];
+
+        let mut rom: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+        rom.extend([0; 8]);
+
+        let mut prg = vec![0; PRG_ROM_PAGE_SIZE];
+        prg[..program.len()].copy_from_slice(&program);
+        prg[0x3ffc] = 0x00;
+        prg[0x3ffd] = 0x80;
+        rom.extend(prg);
+
+        let demo = || {
+            let mut demo = Demo::load(&rom).unwrap().with_input("2:A").unwrap();
+            demo.instructions_per_frame = 100;
+            demo
+        };
+
+        demo()
+            .expect_ram(1, 0x0010, 0x40)
+            .expect_ram(2, 0x0010, 0x41)
+            .run()
+            .unwrap();
+
+        assert!(demo().expect_ram(3, 0x0010, 0x41).run().is_err());
+    }
+}
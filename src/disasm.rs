@@ -0,0 +1,259 @@
+//! Static disassembly of PRG ROM, without running it.
+
+use std::collections::BTreeSet;
+
+use crate::cartridge::{Cartridge, PRG_ROM_PAGE_SIZE};
+use crate::opcodes::{AddressingMode, Instruction, OpCode, OpCodeDetail};
+
+/// Code/data log bit marking a byte that was executed as code.
+pub const CDL_CODE: u8 = 0b01;
+
+/// One decoded instruction, or a byte of data that isn't one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    pub text: String,
+}
+
+/// Decode the instruction at the start of `bytes`, which lives at `address`. None if the bytes
+/// aren't a known opcode with all of its operands.
+pub fn disassemble_one(bytes: &[u8], address: u16) -> Option<Line> {
+    let opcode = OpCode::from_code(bytes.first()?).ok()?;
+    let detail = OpCodeDetail::from_opcode(&opcode);
+    let length = detail.bytes as usize;
+
+    if bytes.len() < length {
+        return None;
+    }
+
+    let byte = bytes.get(1).copied().unwrap_or(0);
+    let word = u16::from_le_bytes([byte, bytes.get(2).copied().unwrap_or(0)]);
+
+    let operand = match detail.address_mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => " A".to_string(),
+        AddressingMode::Immediate => format!(" #${:02X}", byte),
+        AddressingMode::ZeroPage => format!(" ${:02X}", byte),
+        AddressingMode::ZeroPageX => format!(" ${:02X},X", byte),
+        AddressingMode::ZeroPageY => format!(" ${:02X},Y", byte),
+        AddressingMode::Absolute => format!(" ${:04X}", word),
+        AddressingMode::AbsoluteX => format!(" ${:04X},X", word),
+        AddressingMode::AbsoluteY => format!(" ${:04X},Y", word),
+        AddressingMode::Indirect => format!(" (${:04X})", word),
+        AddressingMode::IndirectX => format!(" (${:02X},X)", byte),
+        AddressingMode::IndirectY => format!(" (${:02X}),Y", byte),
+        AddressingMode::Relative => format!(" ${:04X}", branch_target(address, byte)),
+    };
+
+    Some(Line {
+        address,
+        bytes: bytes[..length].to_vec(),
+        text: format!("{}{}", detail.instruction.to_string(), operand),
+    })
+}
+
+/// Disassemble a whole PRG ROM mapped at `base`.
+///
+/// With a code/data log, bytes the log never saw executed are emitted as data, so tables don't
+/// turn into nonsense instructions. Without one everything that decodes is treated as code.
+pub fn disassemble(prg: &[u8], base: u16, cdl: Option<&[u8]>) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut offset = 0;
+
+    while offset < prg.len() {
+        let address = base.wrapping_add(offset as u16);
+        let is_code = match cdl {
+            Some(cdl) => cdl.get(offset).is_some_and(|flags| flags & CDL_CODE != 0),
+            None => true,
+        };
+
+        let line = if is_code {
+            disassemble_one(&prg[offset..], address)
+        } else {
+            None
+        };
+
+        let line = line.unwrap_or_else(|| Line {
+            address,
+            bytes: vec![prg[offset]],
+            text: format!(".db ${:02X}", prg[offset]),
+        });
+
+        offset += line.bytes.len();
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Every address that a branch, jump or subroutine call in `lines` goes to.
+pub fn jump_targets(lines: &[Line]) -> BTreeSet<u16> {
+    let mut targets = BTreeSet::new();
+
+    for line in lines {
+        let Some(Ok(opcode)) = line.bytes.first().map(OpCode::from_code) else {
+            continue;
+        };
+        let detail = OpCodeDetail::from_opcode(&opcode);
+
+        // Data lines can start with an opcode byte but never have its operands
+        match (
+            detail.instruction,
+            detail.address_mode,
+            line.bytes.as_slice(),
+        ) {
+            (_, AddressingMode::Relative, [_, offset]) => {
+                targets.insert(branch_target(line.address, *offset));
+            }
+            (Instruction::JMP | Instruction::JSR, AddressingMode::Absolute, [_, low, high]) => {
+                targets.insert(u16::from_le_bytes([*low, *high]));
+            }
+            _ => {}
+        }
+    }
+
+    targets
+}
+
+/// A full listing of a cartridge's PRG ROM, one bank at a time, with labels on jump targets and
+/// a marker at each interrupt handler.
+///
+/// Banks are cut in the smallest size the mapper switches. Each is disassembled at the address it
+/// is mapped to at power-on, or in the window at $8000 if it isn't mapped. The vectors are read
+/// from the end of the ROM, which every supported mapper fixes at the top of memory, and handlers
+/// are only marked in banks that are mapped at power-on.
+pub fn listing(cartridge: &Cartridge, cdl: Option<&[u8]>) -> String {
+    let prg = &cartridge.prg_rom;
+    let bank_size = [0x8000, 0xa000, 0xc000, 0xe000]
+        .map(|address| cartridge.prg_bank_size(address))
+        .into_iter()
+        .min()
+        .unwrap_or(PRG_ROM_PAGE_SIZE);
+    let windows: Vec<u16> = (0x8000..=0xffff).step_by(bank_size).collect();
+
+    let vector = |offset: usize| {
+        let start = prg.len().saturating_sub(offset);
+        u16::from_le_bytes([
+            prg.get(start).copied().unwrap_or(0),
+            prg.get(start + 1).copied().unwrap_or(0),
+        ])
+    };
+    let vectors =
+        [(6, "NMI"), (4, "RESET"), (2, "IRQ")].map(|(offset, name)| (vector(offset), name));
+
+    let mut listing = String::new();
+
+    for (bank, start) in (0..prg.len()).step_by(bank_size).enumerate() {
+        let data = &prg[start..(start + bank_size).min(prg.len())];
+
+        // The highest window wins, so a mirrored 16KB NROM bank sits where its vectors are
+        let mapped = windows
+            .iter()
+            .rev()
+            .copied()
+            .find(|window| cartridge.mapper.get_pgr_address(*window) % prg.len() == start);
+        let base = mapped.unwrap_or(0x8000 + (start % cartridge.prg_bank_size(0x8000)) as u16);
+
+        let lines = disassemble(
+            data,
+            base,
+            cdl.map(|cdl| cdl.get(start..).unwrap_or_default()),
+        );
+        let targets = jump_targets(&lines);
+
+        if !listing.is_empty() {
+            listing.push('\n');
+        }
+        listing.push_str(&format!("; Bank {:02X} at ${:04X}\n", bank, base));
+
+        for line in lines {
+            for (handler, name) in vectors.iter() {
+                if mapped.is_some() && *handler == line.address {
+                    listing.push_str(&format!("\n; {} handler\n", name));
+                }
+            }
+
+            if targets.contains(&line.address) {
+                listing.push_str(&format!("L{:04X}:\n", line.address));
+            }
+
+            let bytes: Vec<String> = line.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+
+            listing.push_str(&format!(
+                "  {:04X}  {:<10}{}\n",
+                line.address,
+                bytes.join(" "),
+                line.text
+            ));
+        }
+    }
+
+    listing
+}
+
+fn branch_target(address: u16, offset: u8) -> u16 {
+    address.wrapping_add(2).wrapping_add(offset as i8 as u16)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_disassemble() {
+        // LDA #$01, STA $0200,X, BNE -5, then a data byte
+        let prg = [0xa9, 0x01, 0x9d, 0x00, 0x02, 0xd0, 0xf9, 0xff];
+
+        let lines = disassemble(&prg, 0x8000, None);
+        let text: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+
+        assert_eq!(text, ["LDA #$01", "STA $0200,X", "BNE $8000", ".db $FF"]);
+        assert_eq!(jump_targets(&lines), BTreeSet::from([0x8000]));
+    }
+
+    #[test]
+    fn test_disassemble_with_cdl() {
+        let prg = [0xa9, 0x01, 0xa9, 0x02];
+        let cdl = [CDL_CODE, CDL_CODE, 0b10, 0b10];
+
+        let lines = disassemble(&prg, 0xc000, Some(&cdl));
+        let text: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+
+        assert_eq!(text, ["LDA #$01", ".db $A9", ".db $02"]);
+    }
+
+    #[test]
+    fn test_listing_by_bank() {
+        // MMC3 with 64KB of PRG ROM, eight 8KB banks
+        let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x04, 0x00, 0x40, 0x00];
+        raw.extend([0; 8]);
+
+        let mut prg = vec![0; 4 * PRG_ROM_PAGE_SIZE];
+        // LDA #$02 at the start of bank 2, JMP $E000 at the start of bank 7
+        prg[0x4000..0x4002].copy_from_slice(&[0xa9, 0x02]);
+        prg[0xe000..0xe003].copy_from_slice(&[0x4c, 0x00, 0xe0]);
+        // The reset vector
+        prg[0xfffc..0xfffe].copy_from_slice(&[0x00, 0xe0]);
+        raw.extend(prg);
+
+        let listing = listing(&Cartridge::new(&raw).unwrap(), None);
+
+        // Banks 0, 1, 6 and 7 are mapped at power-on, the rest go in the window at $8000
+        for (bank, base) in [
+            (0, 0x8000),
+            (1, 0xa000),
+            (2, 0x8000),
+            (6, 0xc000),
+            (7, 0xe000),
+        ] {
+            assert!(listing.contains(&format!("; Bank {:02X} at ${:04X}\n", bank, base)));
+        }
+
+        assert!(listing.contains("; Bank 02 at $8000\n  8000  A9 02     LDA #$02\n"));
+        assert!(listing.contains(
+            "; Bank 07 at $E000\n\n; RESET handler\nLE000:\n  E000  4C 00 E0  JMP $E000\n"
+        ));
+        assert_eq!(listing.matches("RESET handler").count(), 1);
+    }
+}
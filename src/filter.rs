@@ -0,0 +1,341 @@
+use crate::errors::NesError;
+use crate::frame::Frame;
+use crate::palette::{indices_to_rgba, FRAME_HEIGHT, FRAME_WIDTH};
+use crate::png;
+
+/// An RGBA picture, four bytes per pixel, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbaImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: usize, height: usize) -> Self {
+        RgbaImage {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    /// The frame's palette indices turned into colours, at its original size.
+    pub fn from_frame(frame: &Frame) -> Self {
+        let mut image = RgbaImage::new(FRAME_WIDTH, FRAME_HEIGHT);
+        indices_to_rgba(&frame.pixels, &mut image.pixels);
+        image
+    }
+
+    /// The pixel at (x, y), with coordinates off the edge clamped back onto it. An empty image
+    /// reads as transparent black.
+    pub fn pixel(&self, x: isize, y: isize) -> [u8; 4] {
+        let x = x.clamp(0, (self.width as isize - 1).max(0)) as usize;
+        let y = y.clamp(0, (self.height as isize - 1).max(0)) as usize;
+        let start = (y * self.width + x) * 4;
+
+        self.pixels
+            .get(start..start + 4)
+            .and_then(|pixel| pixel.try_into().ok())
+            .unwrap_or_default()
+    }
+
+    /// Pixels off the edge are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, pixel: [u8; 4]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let start = (y * self.width + x) * 4;
+
+        if let Some(target) = self.pixels.get_mut(start..start + 4) {
+            target.copy_from_slice(&pixel);
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode_rgba(self.width, self.height, &self.pixels)
+    }
+}
+
+/// One stage of post-processing between the PPU's frame and the screen.
+pub trait VideoFilter {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage;
+}
+
+/// Filters run one after another, e.g. scale2x then scanlines. Frontends pick the stages at
+/// runtime with `filter_by_name` rather than each writing their own scalers.
+#[derive(Default)]
+pub struct FilterChain {
+    filters: Vec<Box<dyn VideoFilter>>,
+}
+
+impl FilterChain {
+    pub fn new() -> Self {
+        FilterChain::default()
+    }
+
+    pub fn push(&mut self, filter: Box<dyn VideoFilter>) {
+        self.filters.push(filter);
+    }
+
+    pub fn clear(&mut self) {
+        self.filters.clear();
+    }
+
+    /// Colour a frame and run it through every filter in turn.
+    pub fn render(&self, frame: &Frame) -> RgbaImage {
+        let mut image = RgbaImage::from_frame(frame);
+
+        for filter in &self.filters {
+            image = filter.apply(&image);
+        }
+
+        image
+    }
+}
+
+/// Build one of the built in filters from its name: `nearest2x`, `nearest3x`, `nearest4x`,
+/// `scale2x`, `scale3x`, `scanlines` or `ntsc`.
+pub fn filter_by_name(name: &str) -> Result<Box<dyn VideoFilter>, NesError> {
+    match name.to_lowercase().as_str() {
+        "nearest2x" => Ok(Box::new(Nearest { scale: 2 })),
+        "nearest3x" => Ok(Box::new(Nearest { scale: 3 })),
+        "nearest4x" => Ok(Box::new(Nearest { scale: 4 })),
+        "scale2x" => Ok(Box::new(Scale2x)),
+        "scale3x" => Ok(Box::new(Scale3x)),
+        "scanlines" => Ok(Box::new(Scanlines { brightness: 0.6 })),
+        "ntsc" => Ok(Box::new(Ntsc)),
+        _ => Err(NesError::new(&format!("Unknown video filter {}", name))),
+    }
+}
+
+/// Blow every pixel up into a `scale` by `scale` block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nearest {
+    pub scale: usize,
+}
+
+impl VideoFilter for Nearest {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage {
+        let mut output = RgbaImage::new(image.width * self.scale, image.height * self.scale);
+
+        for y in 0..output.height {
+            for x in 0..output.width {
+                let pixel = image.pixel((x / self.scale) as isize, (y / self.scale) as isize);
+                output.set_pixel(x, y, pixel);
+            }
+        }
+
+        output
+    }
+}
+
+/// The Scale2x (AdvMAME2x) edge smoothing scaler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale2x;
+
+impl VideoFilter for Scale2x {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage {
+        let mut output = RgbaImage::new(image.width * 2, image.height * 2);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let (ix, iy) = (x as isize, y as isize);
+
+                let p = image.pixel(ix, iy);
+                let a = image.pixel(ix, iy - 1);
+                let b = image.pixel(ix + 1, iy);
+                let c = image.pixel(ix - 1, iy);
+                let d = image.pixel(ix, iy + 1);
+
+                let e0 = if c == a && c != d && a != b { a } else { p };
+                let e1 = if a == b && a != c && b != d { b } else { p };
+                let e2 = if d == c && d != b && c != a { c } else { p };
+                let e3 = if b == d && b != a && d != c { d } else { p };
+
+                output.set_pixel(x * 2, y * 2, e0);
+                output.set_pixel(x * 2 + 1, y * 2, e1);
+                output.set_pixel(x * 2, y * 2 + 1, e2);
+                output.set_pixel(x * 2 + 1, y * 2 + 1, e3);
+            }
+        }
+
+        output
+    }
+}
+
+/// The Scale3x (AdvMAME3x) edge smoothing scaler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale3x;
+
+impl VideoFilter for Scale3x {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage {
+        let mut output = RgbaImage::new(image.width * 3, image.height * 3);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let (ix, iy) = (x as isize, y as isize);
+
+                let a = image.pixel(ix - 1, iy - 1);
+                let b = image.pixel(ix, iy - 1);
+                let c = image.pixel(ix + 1, iy - 1);
+                let d = image.pixel(ix - 1, iy);
+                let e = image.pixel(ix, iy);
+                let f = image.pixel(ix + 1, iy);
+                let g = image.pixel(ix - 1, iy + 1);
+                let h = image.pixel(ix, iy + 1);
+                let i = image.pixel(ix + 1, iy + 1);
+
+                let top_left = d == b && b != f && d != h;
+                let top_right = b == f && b != d && f != h;
+                let bottom_left = d == h && d != b && h != f;
+                let bottom_right = h == f && d != h && b != f;
+
+                let block = [
+                    if top_left { d } else { e },
+                    if (top_left && e != c) || (top_right && e != a) {
+                        b
+                    } else {
+                        e
+                    },
+                    if top_right { f } else { e },
+                    if (top_left && e != g) || (bottom_left && e != a) {
+                        d
+                    } else {
+                        e
+                    },
+                    e,
+                    if (top_right && e != i) || (bottom_right && e != c) {
+                        f
+                    } else {
+                        e
+                    },
+                    if bottom_left { d } else { e },
+                    if (bottom_left && e != i) || (bottom_right && e != g) {
+                        h
+                    } else {
+                        e
+                    },
+                    if bottom_right { f } else { e },
+                ];
+
+                for (index, pixel) in block.iter().enumerate() {
+                    output.set_pixel(x * 3 + index % 3, y * 3 + index / 3, *pixel);
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Darken every other row, like the gaps between a CRT's scanlines. Best after a 2x scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scanlines {
+    /// How bright the dark rows are, from 0.0 (black) to 1.0 (untouched).
+    pub brightness: f32,
+}
+
+impl VideoFilter for Scanlines {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage {
+        let mut output = image.clone();
+        let row_bytes = image.width * 4;
+
+        for row in output.pixels.chunks_exact_mut(row_bytes).skip(1).step_by(2) {
+            for pixel in row.chunks_exact_mut(4) {
+                for channel in &mut pixel[..3] {
+                    *channel = (*channel as f32 * self.brightness) as u8;
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// A cheap stand in for composite video: each pixel is blended with its horizontal neighbours,
+/// softening dithering the way a TV does. It doesn't model the NTSC signal itself, so there are no
+/// dot crawl or colour artifacts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ntsc;
+
+impl VideoFilter for Ntsc {
+    fn apply(&self, image: &RgbaImage) -> RgbaImage {
+        let mut output = RgbaImage::new(image.width, image.height);
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let (ix, iy) = (x as isize, y as isize);
+
+                let left = image.pixel(ix - 1, iy);
+                let centre = image.pixel(ix, iy);
+                let right = image.pixel(ix + 1, iy);
+
+                let mut pixel = centre;
+                for channel in 0..3 {
+                    pixel[channel] = ((left[channel] as u16
+                        + 2 * centre[channel] as u16
+                        + right[channel] as u16)
+                        / 4) as u8;
+                }
+
+                output.set_pixel(x, y, pixel);
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BLACK: [u8; 4] = [0, 0, 0, 0xff];
+    const WHITE: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+    fn image(pixels: &[[u8; 4]], width: usize) -> RgbaImage {
+        RgbaImage {
+            width,
+            height: pixels.len() / width,
+            pixels: pixels.concat(),
+        }
+    }
+
+    #[test]
+    fn test_scale2x_smooths_diagonal() {
+        let input = image(&[WHITE, BLACK, BLACK, WHITE], 2);
+
+        let output = Scale2x.apply(&input);
+
+        assert_eq!((output.width, output.height), (4, 4));
+        // The top right pixel's lower left corner joins up the white diagonal.
+        assert_eq!(output.pixel(2, 1), WHITE);
+        assert_eq!(output.pixel(3, 0), BLACK);
+
+        let flat = image(&[WHITE; 4], 2);
+        assert_eq!(Scale3x.apply(&flat), Nearest { scale: 3 }.apply(&flat));
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut chain = FilterChain::new();
+        chain.push(filter_by_name("Nearest2x").unwrap());
+        chain.push(filter_by_name("scanlines").unwrap());
+
+        let mut frame = Frame::new();
+        frame.set_pixel(0, 0, 0x30);
+
+        let output = chain.render(&frame);
+
+        assert_eq!(
+            (output.width, output.height),
+            (FRAME_WIDTH * 2, FRAME_HEIGHT * 2)
+        );
+        assert_eq!(output.pixel(1, 0), WHITE);
+        assert_eq!(output.pixel(1, 1), [0x99, 0x99, 0x99, 0xff]);
+
+        assert!(filter_by_name("hq9x").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::filter::RgbaImage;
+use crate::palette::{indices_to_rgba, FRAME_HEIGHT, FRAME_WIDTH};
+
+const TILE_SIZE: usize = 8;
+
+/// A part of the screen that changed, in pixels. Always whole 8x8 tiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirtyRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A single screen of palette indices, one byte per pixel, row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub pixels: Vec<u8>,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Frame {
+            pixels: vec![0; FRAME_WIDTH * FRAME_HEIGHT],
+        }
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, index: u8) {
+        if x < FRAME_WIDTH && y < FRAME_HEIGHT {
+            self.pixels[y * FRAME_WIDTH + x] = index;
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<u8> {
+        if x < FRAME_WIDTH && y < FRAME_HEIGHT {
+            Some(self.pixels[y * FRAME_WIDTH + x])
+        } else {
+            None
+        }
+    }
+
+    /// The tiles that differ from `previous`, with neighbouring tiles in a row joined into one
+    /// rectangle, so a terminal or network frontend only has to send what changed.
+    pub fn dirty_rects(&self, previous: &Frame) -> Vec<DirtyRect> {
+        let mut rects = vec![];
+
+        for tile_y in 0..FRAME_HEIGHT / TILE_SIZE {
+            let mut run: Option<DirtyRect> = None;
+
+            for tile_x in 0..FRAME_WIDTH / TILE_SIZE {
+                if !self.tile_differs(previous, tile_x, tile_y) {
+                    rects.extend(run.take());
+                    continue;
+                }
+
+                match run.as_mut() {
+                    Some(rect) => rect.width += TILE_SIZE,
+                    None => {
+                        run = Some(DirtyRect {
+                            x: tile_x * TILE_SIZE,
+                            y: tile_y * TILE_SIZE,
+                            width: TILE_SIZE,
+                            height: TILE_SIZE,
+                        })
+                    }
+                }
+            }
+
+            rects.extend(run);
+        }
+
+        rects
+    }
+
+    fn tile_differs(&self, other: &Frame, tile_x: usize, tile_y: usize) -> bool {
+        (0..TILE_SIZE).any(|row| {
+            let start = (tile_y * TILE_SIZE + row) * FRAME_WIDTH + tile_x * TILE_SIZE;
+            self.pixels.get(start..start + TILE_SIZE) != other.pixels.get(start..start + TILE_SIZE)
+        })
+    }
+}
+
+/// A picture of any size in system palette indices, for tile sheets and other debug views.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl IndexedImage {
+    pub fn to_rgba(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        indices_to_rgba(&self.pixels, &mut image.pixels);
+        image
+    }
+}
+
+/// Two frames: one being drawn into, and the last completed one which the frontend can borrow.
+///
+/// Presenting a frame just swaps which is which, so handing a finished frame to the frontend never
+/// copies the pixels.
+#[derive(Debug, Clone, Default)]
+pub struct FrameBuffers {
+    frames: [Frame; 2],
+    front: usize,
+}
+
+impl FrameBuffers {
+    pub fn new() -> Self {
+        FrameBuffers::default()
+    }
+
+    /// The most recently completed frame.
+    pub fn front(&self) -> &Frame {
+        &self.frames[self.front]
+    }
+
+    /// The frame currently being drawn.
+    pub fn back_mut(&mut self) -> &mut Frame {
+        &mut self.frames[1 - self.front]
+    }
+
+    /// Mark the frame being drawn as complete. The old front frame becomes the new back frame and
+    /// keeps its pixels until they are drawn over.
+    pub fn present(&mut self) {
+        self.front = 1 - self.front;
+    }
+
+    /// What changed in the frame just presented, before anything is drawn over the one before.
+    pub fn dirty_rects(&self) -> Vec<DirtyRect> {
+        self.front().dirty_rects(&self.frames[1 - self.front])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_present_swaps_frames() {
+        let mut buffers = FrameBuffers::new();
+
+        buffers.back_mut().set_pixel(10, 20, 0x16);
+        assert_eq!(buffers.front().pixel(10, 20), Some(0x00));
+
+        let back_address = buffers.back_mut().pixels.as_ptr();
+        buffers.present();
+
+        assert_eq!(buffers.front().pixel(10, 20), Some(0x16));
+        assert_eq!(buffers.front().pixels.as_ptr(), back_address);
+    }
+
+    #[test]
+    fn test_dirty_rects() {
+        let mut buffers = FrameBuffers::new();
+        buffers.present();
+        assert!(buffers.dirty_rects().is_empty());
+
+        let frame = buffers.back_mut();
+        frame.set_pixel(8, 0, 0x16);
+        frame.set_pixel(23, 7, 0x16);
+        frame.set_pixel(255, 239, 0x16);
+        buffers.present();
+
+        assert_eq!(
+            buffers.dirty_rects(),
+            [
+                DirtyRect {
+                    x: 8,
+                    y: 0,
+                    width: 16,
+                    height: 8
+                },
+                DirtyRect {
+                    x: 248,
+                    y: 232,
+                    width: 8,
+                    height: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pixel_out_of_range() {
+        let mut frame = Frame::new();
+        frame.set_pixel(FRAME_WIDTH, 0, 0x01);
+
+        assert_eq!(frame.pixel(FRAME_WIDTH, 0), None);
+        assert!(frame.pixels.iter().all(|pixel| *pixel == 0));
+    }
+}
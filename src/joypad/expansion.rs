@@ -0,0 +1,247 @@
+/// Number of rows scanned on the Family BASIC keyboard.
+pub const KEYBOARD_ROWS: usize = 9;
+
+/// The storage in the original Turbo File. The Turbo File II holds four times as much, selected
+/// by a switch on the device, which is left out here.
+pub const TURBO_FILE_SIZE: usize = 8192;
+
+/// A device plugged into the Famicom expansion port, which shares $4016/$4017 with the
+/// controllers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpansionDevice {
+    /// The microphone on the Famicom's second controller, seen in bit 2 of $4016.
+    Microphone {
+        active: bool,
+    },
+    FamilyBasicKeyboard(Keyboard),
+    TurboFile(TurboFile),
+}
+
+impl ExpansionDevice {
+    /// A write to $4016, which the expansion port sees as well as the controllers.
+    pub fn write(&mut self, data: u8) {
+        match self {
+            ExpansionDevice::Microphone { .. } => {}
+            ExpansionDevice::FamilyBasicKeyboard(keyboard) => keyboard.write(data),
+            ExpansionDevice::TurboFile(turbo_file) => turbo_file.write(data),
+        }
+    }
+
+    /// The bits this device drives on a $4016 read.
+    pub fn read_4016(&self) -> u8 {
+        match self {
+            ExpansionDevice::Microphone { active } => (*active as u8) << 2,
+            ExpansionDevice::FamilyBasicKeyboard(_) | ExpansionDevice::TurboFile(_) => 0,
+        }
+    }
+
+    /// The bits this device drives on a $4017 read.
+    pub fn read_4017(&self) -> u8 {
+        match self {
+            ExpansionDevice::Microphone { .. } => 0,
+            ExpansionDevice::FamilyBasicKeyboard(keyboard) => keyboard.read(),
+            ExpansionDevice::TurboFile(turbo_file) => turbo_file.read(),
+        }
+    }
+}
+
+/// The Family BASIC keyboard: a matrix of 9 rows by 2 columns of 4 keys.
+///
+/// Software selects a row and column through $4016 writes and reads the four keys back in bits
+/// 1-4 of $4017, low when pressed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Keyboard {
+    /// One byte per row, the low nibble is column 0 and the high nibble column 1.
+    pub keys: [u8; KEYBOARD_ROWS],
+    row: usize,
+    column: usize,
+    enabled: bool,
+}
+
+impl Keyboard {
+    pub fn new() -> Self {
+        Keyboard::default()
+    }
+
+    /// Press or release one of the eight keys on a row, column 0 keys being 0-3 and column 1 keys
+    /// 4-7.
+    pub fn set_key(&mut self, row: usize, key: u8, pressed: bool) {
+        if row >= KEYBOARD_ROWS || key > 7 {
+            return;
+        }
+
+        if pressed {
+            self.keys[row] |= 1 << key;
+        } else {
+            self.keys[row] &= !(1 << key);
+        }
+    }
+
+    /// Bit 0 resets the scan to row 0, bit 1 selects the column and bit 2 enables the keyboard.
+    /// The row advances each time the column goes from 1 back to 0.
+    pub fn write(&mut self, data: u8) {
+        let column = ((data >> 1) & 1) as usize;
+
+        if self.column == 1 && column == 0 {
+            self.row += 1;
+        }
+
+        self.column = column;
+        self.enabled = data & 0b100 != 0;
+
+        if data & 1 == 1 {
+            self.row = 0;
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+
+        let pressed = match self.keys.get(self.row) {
+            Some(keys) => (keys >> (self.column * 4)) & 0x0f,
+            None => 0,
+        };
+
+        (!pressed & 0x0f) << 1
+    }
+}
+
+/// ASCII's Turbo File, a battery backed store a handful of Famicom RPGs save to, written and read
+/// a bit at a time.
+///
+/// On $4016 writes bit 0 is the data bit, bit 1 low resets to the start of the file, and bit 2
+/// high writes the data bit at the current position. Bringing bit 2 low again moves on to the
+/// next bit. The bit at the current position is read back in bit 2 of $4017.
+///
+/// The contents are the device's own save, separate from the cartridge's; frontends persist them
+/// with `data` and `load`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurboFile {
+    /// The switch on the side of the device. While it's on, writes still clock through the file
+    /// but leave the contents alone.
+    pub write_protected: bool,
+    data: Vec<u8>,
+    position: usize,
+    bit: u8,
+    last_write: u8,
+}
+
+impl Default for TurboFile {
+    fn default() -> Self {
+        TurboFile {
+            write_protected: false,
+            data: vec![0; TURBO_FILE_SIZE],
+            position: 0,
+            bit: 0,
+            last_write: 0,
+        }
+    }
+}
+
+impl TurboFile {
+    pub fn new() -> Self {
+        TurboFile::default()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Replace the contents with a saved file, padded or cut to the device's size.
+    pub fn load(&mut self, data: &[u8]) {
+        self.data = data.to_vec();
+        self.data.resize(TURBO_FILE_SIZE, 0);
+    }
+
+    pub fn write(&mut self, data: u8) {
+        if data & 0b010 == 0 {
+            self.position = 0;
+            self.bit = 0;
+        } else if data & 0b100 != 0 {
+            if self.write_protected {
+                self.last_write = data;
+                return;
+            }
+
+            let mask = 1 << self.bit;
+            self.data[self.position] =
+                (self.data[self.position] & !mask) | ((data & 1) << self.bit);
+        } else if self.last_write & 0b100 != 0 {
+            self.bit = (self.bit + 1) % 8;
+
+            if self.bit == 0 {
+                self.position = (self.position + 1) % self.data.len();
+            }
+        }
+
+        self.last_write = data;
+    }
+
+    pub fn read(&self) -> u8 {
+        ((self.data[self.position] >> self.bit) & 1) << 2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keyboard_scan() {
+        let mut keyboard = Keyboard::new();
+        keyboard.set_key(0, 1, true);
+        keyboard.set_key(1, 6, true);
+
+        keyboard.write(0b101);
+        assert_eq!(keyboard.read(), 0b1_1010);
+
+        keyboard.write(0b110);
+        assert_eq!(keyboard.read(), 0b1_1110);
+
+        keyboard.write(0b100);
+        keyboard.write(0b110);
+        assert_eq!(keyboard.read(), 0b1_0110);
+
+        keyboard.write(0b000);
+        assert_eq!(keyboard.read(), 0);
+    }
+
+    #[test]
+    fn test_turbo_file() {
+        let mut turbo_file = TurboFile::new();
+
+        // Write 1, 0, 1 into the first three bits.
+        turbo_file.write(0b000);
+        for bit in [1, 0, 1] {
+            turbo_file.write(0b110 | bit);
+            turbo_file.write(0b010);
+        }
+        assert_eq!(turbo_file.data()[0], 0b101);
+
+        let mut restored = TurboFile::new();
+        restored.load(turbo_file.data());
+        restored.write_protected = true;
+
+        let mut device = ExpansionDevice::TurboFile(restored);
+        device.write(0b000);
+
+        let mut bits = vec![];
+        for _ in 0..3 {
+            device.write(0b010);
+            bits.push(device.read_4017() >> 2);
+            device.write(0b110);
+        }
+
+        assert_eq!(bits, [1, 0, 1]);
+    }
+
+    #[test]
+    fn test_microphone() {
+        let microphone = ExpansionDevice::Microphone { active: true };
+
+        assert_eq!(microphone.read_4016(), 0b100);
+        assert_eq!(microphone.read_4017(), 0);
+    }
+}
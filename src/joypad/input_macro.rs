@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::joypad::Buttons;
+
+/// A short run of controller input, one entry per frame, e.g. a fireball motion or a menu combo.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputMacro {
+    frames: Vec<Buttons>,
+}
+
+impl InputMacro {
+    pub fn new(frames: Vec<Buttons>) -> Self {
+        InputMacro { frames }
+    }
+
+    pub fn frames(&self) -> &[Buttons] {
+        &self.frames
+    }
+}
+
+/// Captures what the player holds each frame until recording is stopped.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MacroRecorder {
+    frames: Option<Vec<Buttons>>,
+}
+
+impl MacroRecorder {
+    pub fn new() -> Self {
+        MacroRecorder::default()
+    }
+
+    /// Start a new recording, throwing away any that was in progress.
+    pub fn start(&mut self) {
+        self.frames = Some(vec![]);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.frames.is_some()
+    }
+
+    /// Call once a frame with the buttons held. Does nothing unless recording.
+    pub fn record_frame(&mut self, buttons: Buttons) {
+        if let Some(frames) = self.frames.as_mut() {
+            frames.push(buttons);
+        }
+    }
+
+    /// Finish recording, returning the macro if one was being recorded.
+    pub fn stop(&mut self) -> Option<InputMacro> {
+        self.frames.take().map(InputMacro::new)
+    }
+}
+
+/// Macros bound to frontend keys. Sits between the player's input and the `Joypad`: pressing a
+/// bound key starts its macro, and `next_frame` mixes the playing macro into whatever is held.
+#[derive(Debug, Clone)]
+pub struct MacroBindings<K> {
+    bindings: HashMap<K, InputMacro>,
+    /// The macro being played and the frame it is up to.
+    playing: Option<(InputMacro, usize)>,
+}
+
+impl<K: Eq + Hash> Default for MacroBindings<K> {
+    fn default() -> Self {
+        MacroBindings {
+            bindings: HashMap::new(),
+            playing: None,
+        }
+    }
+}
+
+impl<K: Eq + Hash> MacroBindings<K> {
+    pub fn new() -> Self {
+        MacroBindings::default()
+    }
+
+    /// Bind `input_macro` to `key`, replacing whatever was bound to it.
+    pub fn bind(&mut self, key: K, input_macro: InputMacro) {
+        self.bindings.insert(key, input_macro);
+    }
+
+    pub fn unbind(&mut self, key: &K) -> Option<InputMacro> {
+        self.bindings.remove(key)
+    }
+
+    /// Start the macro bound to `key` from its first frame. Returns false if nothing is bound.
+    pub fn trigger(&mut self, key: &K) -> bool {
+        match self.bindings.get(key) {
+            Some(input_macro) => {
+                self.playing = Some((input_macro.clone(), 0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    /// The buttons to send to the controller this frame: `held` plus the playing macro's frame.
+    pub fn next_frame(&mut self, held: Buttons) -> Buttons {
+        let Some((input_macro, frame)) = self.playing.as_mut() else {
+            return held;
+        };
+
+        let buttons = match input_macro.frames.get(*frame) {
+            Some(buttons) => Buttons {
+                bits: held.bits | buttons.bits,
+            },
+            None => held,
+        };
+
+        *frame += 1;
+
+        if *frame >= input_macro.frames.len() {
+            self.playing = None;
+        }
+
+        buttons
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::joypad::Button;
+
+    fn pressed(button: Button) -> Buttons {
+        let mut buttons = Buttons::new();
+        buttons.set(button, true);
+        buttons
+    }
+
+    #[test]
+    fn test_record_and_play() {
+        let mut recorder = MacroRecorder::new();
+        recorder.record_frame(pressed(Button::A));
+        recorder.start();
+        recorder.record_frame(pressed(Button::Down));
+        recorder.record_frame(pressed(Button::Right));
+        recorder.record_frame(pressed(Button::B));
+
+        let fireball = recorder.stop().unwrap();
+        assert_eq!(fireball.frames().len(), 3);
+        assert!(!recorder.is_recording());
+
+        let mut bindings = MacroBindings::new();
+        bindings.bind('f', fireball);
+
+        assert!(!bindings.trigger(&'g'));
+        assert!(bindings.trigger(&'f'));
+
+        assert_eq!(bindings.next_frame(Buttons::new()), pressed(Button::Down));
+        assert_eq!(bindings.next_frame(Buttons::new()), pressed(Button::Right));
+
+        let buttons = bindings.next_frame(pressed(Button::Select));
+        assert!(buttons.is_pressed(Button::B));
+        assert!(buttons.is_pressed(Button::Select));
+
+        assert!(!bindings.is_playing());
+        assert_eq!(bindings.next_frame(Buttons::new()), Buttons::new());
+    }
+}
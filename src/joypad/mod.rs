@@ -0,0 +1,245 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::Debug;
+
+use crate::errors::NesError;
+
+pub mod expansion;
+pub mod input_macro;
+pub mod paddle;
+pub mod power_pad;
+pub mod script;
+pub mod vs_system;
+
+/// Anything that can be plugged into one of the two controller ports.
+///
+/// Reads go through `&self` as bus reads are not mutable, so devices which shift their state out
+/// keep their position in a `Cell`.
+pub trait ControllerDevice: Any + Debug {
+    /// A copy of the device in its current state, so a whole machine can be cloned.
+    fn clone_box(&self) -> Box<dyn ControllerDevice>;
+
+    /// A write to $4016, which every port sees.
+    fn strobe(&mut self, data: u8);
+
+    /// The bits this device drives on a read of its port, advancing to the next bit.
+    fn read(&self) -> u8;
+
+    /// What the next read would return, without advancing. For debuggers and input displays.
+    fn peek(&self) -> u8;
+}
+
+/// The buttons on a standard controller, in the order they are shifted out of $4016/$4017.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Button {
+    A,
+    B,
+    Select,
+    Start,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Button {
+    pub const ALL: [Button; 8] = [
+        Button::A,
+        Button::B,
+        Button::Select,
+        Button::Start,
+        Button::Up,
+        Button::Down,
+        Button::Left,
+        Button::Right,
+    ];
+
+    /// The bit this button occupies in a controller report.
+    pub fn bit(&self) -> u8 {
+        match self {
+            Button::A => 0b0000_0001,
+            Button::B => 0b0000_0010,
+            Button::Select => 0b0000_0100,
+            Button::Start => 0b0000_1000,
+            Button::Up => 0b0001_0000,
+            Button::Down => 0b0010_0000,
+            Button::Left => 0b0100_0000,
+            Button::Right => 0b1000_0000,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Button, NesError> {
+        match name.to_ascii_lowercase().as_str() {
+            "a" => Ok(Button::A),
+            "b" => Ok(Button::B),
+            "select" => Ok(Button::Select),
+            "start" => Ok(Button::Start),
+            "up" => Ok(Button::Up),
+            "down" => Ok(Button::Down),
+            "left" => Ok(Button::Left),
+            "right" => Ok(Button::Right),
+            _ => Err(NesError::new(&format!("Unknown button {}", name))),
+        }
+    }
+}
+
+/// Which buttons are held on one controller.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Buttons {
+    pub bits: u8,
+}
+
+impl Buttons {
+    pub fn new() -> Self {
+        Buttons { bits: 0 }
+    }
+
+    pub fn set(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.bits |= button.bit();
+        } else {
+            self.bits &= !button.bit();
+        }
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.bits & button.bit() != 0
+    }
+}
+
+/// A standard controller, read one button at a time through $4016/$4017.
+///
+/// Reading shifts the next button out, which has to happen through `&self` as bus reads are not
+/// mutable, so the position in the report is kept in a `Cell`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Joypad {
+    pub buttons: Buttons,
+    strobe: bool,
+    /// The buttons as they were when strobe was last released, which is what gets shifted out.
+    latched: Buttons,
+    button_index: Cell<u8>,
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Joypad::default()
+    }
+}
+
+impl Clone for Box<dyn ControllerDevice> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl ControllerDevice for Joypad {
+    fn clone_box(&self) -> Box<dyn ControllerDevice> {
+        Box::new(self.clone())
+    }
+
+    /// While bit 0 is set the shift register keeps reloading, so every read returns the live state
+    /// of button A. Clearing it latches all eight buttons, and later presses don't show up until
+    /// the next strobe. Writing 0 again while it is already clear changes nothing.
+    fn strobe(&mut self, data: u8) {
+        let strobe = data & 1 == 1;
+
+        if strobe {
+            self.button_index.set(0);
+        } else if self.strobe {
+            self.latched = self.buttons;
+        }
+
+        self.strobe = strobe;
+    }
+
+    fn read(&self) -> u8 {
+        let index = self.button_index.get();
+        let pressed = self.peek();
+
+        if !self.strobe && index <= 7 {
+            self.button_index.set(index + 1);
+        }
+
+        pressed
+    }
+
+    /// The next button in the report in bit 0. After all eight buttons a standard controller
+    /// returns 1.
+    fn peek(&self) -> u8 {
+        let index = self.button_index.get();
+
+        if self.strobe {
+            return self.buttons.bits & 1;
+        }
+
+        if index > 7 {
+            return 1;
+        }
+
+        (self.latched.bits >> index) & 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strobe_held_high_reads_a() {
+        let mut joypad = Joypad::new();
+        joypad.buttons.set(Button::B, true);
+        joypad.strobe(1);
+
+        assert_eq!([joypad.read(), joypad.read(), joypad.read()], [0, 0, 0]);
+
+        joypad.buttons.set(Button::A, true);
+
+        assert_eq!([joypad.read(), joypad.read()], [1, 1]);
+    }
+
+    #[test]
+    fn test_strobe_release_latches_buttons() {
+        let mut joypad = Joypad::new();
+        joypad.buttons.set(Button::Start, true);
+        joypad.strobe(1);
+        joypad.strobe(0);
+
+        joypad.buttons.set(Button::Start, false);
+        joypad.buttons.set(Button::A, true);
+
+        let report: Vec<u8> = (0..10).map(|_| joypad.read()).collect();
+
+        assert_eq!(report, [0, 0, 0, 1, 0, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_only_strobe_release_latches() {
+        let mut joypad = Joypad::new();
+        joypad.buttons.set(Button::A, true);
+        joypad.strobe(1);
+        joypad.strobe(0);
+
+        assert_eq!(joypad.read(), 1);
+
+        // Another write of 0 neither relatches nor restarts the report
+        joypad.buttons.set(Button::A, false);
+        joypad.buttons.set(Button::B, true);
+        joypad.strobe(0);
+
+        assert_eq!([joypad.read(), joypad.read()], [0, 0]);
+    }
+
+    #[test]
+    fn test_joypad_peek_does_not_advance() {
+        let mut joypad = Joypad::new();
+        joypad.buttons.set(Button::B, true);
+        joypad.strobe(1);
+        joypad.strobe(0);
+
+        assert_eq!(joypad.read(), 0);
+        assert_eq!(joypad.peek(), 1);
+        assert_eq!(joypad.peek(), 1);
+        assert_eq!(joypad.read(), 1);
+        assert_eq!(joypad.peek(), 0);
+    }
+}
@@ -0,0 +1,93 @@
+use std::cell::Cell;
+
+use crate::joypad::ControllerDevice;
+
+/// The Arkanoid Vaus controller: a knob read as an 8 bit serial value and a single fire button.
+///
+/// Strobing latches the knob position, which is then shifted out most significant bit first,
+/// inverted, in bit 4 of each read. The fire button is always visible in bit 3.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paddle {
+    /// How far the knob is turned. Arkanoid expects roughly 98 at the far left to 242 at the far
+    /// right.
+    pub position: u8,
+    pub fire: bool,
+    strobe: bool,
+    latched: u8,
+    bit_index: Cell<u8>,
+}
+
+impl Default for Paddle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Paddle {
+    pub fn new() -> Self {
+        Paddle {
+            position: 0x98,
+            fire: false,
+            strobe: false,
+            latched: 0,
+            bit_index: Cell::new(0),
+        }
+    }
+}
+
+impl ControllerDevice for Paddle {
+    fn clone_box(&self) -> Box<dyn ControllerDevice> {
+        Box::new(self.clone())
+    }
+
+    fn strobe(&mut self, data: u8) {
+        self.strobe = data & 1 == 1;
+
+        if self.strobe {
+            self.latched = self.position;
+            self.bit_index.set(0);
+        }
+    }
+
+    fn read(&self) -> u8 {
+        let index = self.bit_index.get();
+        let data = self.peek();
+
+        if !self.strobe && index <= 7 {
+            self.bit_index.set(index + 1);
+        }
+
+        data
+    }
+
+    fn peek(&self) -> u8 {
+        let index = self.bit_index.get();
+
+        let data = if index > 7 {
+            0
+        } else {
+            !(self.latched << index) >> 7 & 1
+        };
+
+        (data << 4) | ((self.fire as u8) << 3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paddle_serial_position() {
+        let mut paddle = Paddle::new();
+        paddle.position = 0b1010_0000;
+        paddle.fire = true;
+
+        paddle.strobe(1);
+        paddle.strobe(0);
+
+        let bits: Vec<u8> = (0..8).map(|_| paddle.read()).collect();
+
+        assert_eq!(bits, [0x08, 0x18, 0x08, 0x18, 0x18, 0x18, 0x18, 0x18]);
+    }
+}
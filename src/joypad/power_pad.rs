@@ -0,0 +1,97 @@
+use std::cell::Cell;
+
+use crate::joypad::ControllerDevice;
+
+/// The order buttons are shifted out of bit 3 of a read.
+const LOW_ORDER: [u8; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+/// The order buttons are shifted out of bit 4 of a read, after which it reads 1.
+const HIGH_ORDER: [u8; 4] = [4, 3, 12, 8];
+
+/// The Power Pad mat, twelve buttons numbered 1 to 12 as printed on side B.
+///
+/// Each read shifts one button out of each of two serial lines, bits 3 and 4.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PowerPad {
+    pressed: [bool; 12],
+    strobe: bool,
+    bit_index: Cell<u8>,
+}
+
+impl PowerPad {
+    pub fn new() -> Self {
+        PowerPad::default()
+    }
+
+    /// Press or release one of the buttons, numbered 1 to 12. Other numbers are ignored.
+    pub fn set_button(&mut self, button: u8, pressed: bool) {
+        if (1..=12).contains(&button) {
+            self.pressed[button as usize - 1] = pressed;
+        }
+    }
+
+    pub fn is_pressed(&self, button: u8) -> bool {
+        (1..=12).contains(&button) && self.pressed[button as usize - 1]
+    }
+}
+
+impl ControllerDevice for PowerPad {
+    fn clone_box(&self) -> Box<dyn ControllerDevice> {
+        Box::new(self.clone())
+    }
+
+    fn strobe(&mut self, data: u8) {
+        self.strobe = data & 1 == 1;
+
+        if self.strobe {
+            self.bit_index.set(0);
+        }
+    }
+
+    fn read(&self) -> u8 {
+        let index = self.bit_index.get() as usize;
+        let data = self.peek();
+
+        if !self.strobe && index < LOW_ORDER.len() {
+            self.bit_index.set(index as u8 + 1);
+        }
+
+        data
+    }
+
+    fn peek(&self) -> u8 {
+        let index = self.bit_index.get() as usize;
+
+        let low = match LOW_ORDER.get(index) {
+            Some(button) => self.is_pressed(*button) as u8,
+            None => 1,
+        };
+        let high = match HIGH_ORDER.get(index) {
+            Some(button) => self.is_pressed(*button) as u8,
+            None => 1,
+        };
+
+        (high << 4) | (low << 3)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_power_pad_serial_order() {
+        let mut power_pad = PowerPad::new();
+        power_pad.set_button(1, true);
+        power_pad.set_button(12, true);
+
+        power_pad.strobe(1);
+        power_pad.strobe(0);
+
+        let bits: Vec<u8> = (0..10).map(|_| power_pad.read()).collect();
+
+        assert_eq!(
+            bits,
+            [0x00, 0x08, 0x10, 0x00, 0x10, 0x10, 0x10, 0x10, 0x18, 0x18]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::errors::NesError;
+use crate::joypad::{Button, Buttons};
+
+/// A quick way to script controller input for automated tests, lighter than a full movie file.
+///
+/// Each entry is a frame (or inclusive range of frames) and the buttons held, joined with `+`:
+/// ```text
+/// 120:Start
+/// 121-130:Right+A   # comments run to the end of the line
+/// ```
+/// Entries may be on separate lines or separated by commas or spaces. Frames that aren't mentioned
+/// have nothing pressed, and overlapping entries are combined.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputScript {
+    entries: Vec<(u64, u64, Buttons)>,
+}
+
+impl InputScript {
+    pub fn parse(script: &str) -> Result<Self, NesError> {
+        let mut entries = vec![];
+
+        for line in script.lines() {
+            let line = line.split('#').next().unwrap_or("");
+
+            for entry in line
+                .split([',', ' ', '\t'])
+                .filter(|entry| !entry.is_empty())
+            {
+                entries.push(parse_entry(entry)?);
+            }
+        }
+
+        Ok(InputScript { entries })
+    }
+
+    /// The buttons held during `frame`.
+    pub fn buttons_for_frame(&self, frame: u64) -> Buttons {
+        let mut buttons = Buttons::new();
+
+        for (start, end, entry_buttons) in &self.entries {
+            if (*start..=*end).contains(&frame) {
+                buttons.bits |= entry_buttons.bits;
+            }
+        }
+
+        buttons
+    }
+
+    /// The last frame with any input, if there is any.
+    pub fn last_frame(&self) -> Option<u64> {
+        self.entries.iter().map(|(_, end, _)| *end).max()
+    }
+}
+
+fn parse_entry(entry: &str) -> Result<(u64, u64, Buttons), NesError> {
+    let (frames, button_names) = entry
+        .split_once(':')
+        .ok_or_else(|| NesError::new(&format!("Expected frame:buttons but got {}", entry)))?;
+
+    let (start, end) = match frames.split_once('-') {
+        Some((start, end)) => (parse_frame(start)?, parse_frame(end)?),
+        None => {
+            let frame = parse_frame(frames)?;
+            (frame, frame)
+        }
+    };
+
+    if end < start {
+        return Err(NesError::new(&format!(
+            "Frame range {} is backwards",
+            frames
+        )));
+    }
+
+    let mut buttons = Buttons::new();
+
+    for name in button_names.split('+') {
+        buttons.set(Button::from_name(name)?, true);
+    }
+
+    Ok((start, end, buttons))
+}
+
+fn parse_frame(frame: &str) -> Result<u64, NesError> {
+    frame
+        .parse()
+        .map_err(|_| NesError::new(&format!("Invalid frame number {}", frame)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let script =
+            InputScript::parse("120:Start\n121-130:Right+A # run and jump\n125:b").unwrap();
+
+        assert_eq!(script.buttons_for_frame(119), Buttons::new());
+        assert!(script.buttons_for_frame(120).is_pressed(Button::Start));
+        assert!(!script.buttons_for_frame(121).is_pressed(Button::Start));
+
+        let buttons = script.buttons_for_frame(125);
+        assert!(buttons.is_pressed(Button::Right));
+        assert!(buttons.is_pressed(Button::A));
+        assert!(buttons.is_pressed(Button::B));
+
+        assert_eq!(script.buttons_for_frame(131), Buttons::new());
+        assert_eq!(script.last_frame(), Some(130));
+    }
+
+    #[test]
+    fn test_parse_single_line() {
+        let script = InputScript::parse("1:A, 2:B 3:Up").unwrap();
+
+        assert!(script.buttons_for_frame(3).is_pressed(Button::Up));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(InputScript::parse("120").is_err());
+        assert!(InputScript::parse("x:A").is_err());
+        assert!(InputScript::parse("10-5:A").is_err());
+        assert!(InputScript::parse("10:Jump").is_err());
+    }
+}
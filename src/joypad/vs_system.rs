@@ -0,0 +1,39 @@
+/// The bits of a $4016 read driven by the cabinet: the service button and two coin slots, and the
+/// first two DIP switches.
+pub(crate) const VS_4016_BITS: u8 = 0b0111_1100;
+/// The bits of a $4017 read driven by the cabinet: DIP switches 3 to 8.
+pub(crate) const VS_4017_BITS: u8 = 0b1111_1100;
+
+/// The coin slots, service button and DIP switches of a Vs. System cabinet, which games read
+/// through the controller ports.
+///
+/// $4016 has the service button in bit 2, DIP switches 1 and 2 in bits 3-4 and the coin slots in
+/// bits 5-6. $4017 has DIP switches 3 to 8 in bits 2-7.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct VsSystemInputs {
+    /// The eight DIP switches, switch 1 in bit 0. Games use them for difficulty, lives and price.
+    pub dip_switches: u8,
+    /// Whether a coin is passing through slot 1 or 2. Games count coins on the edge, so hold each
+    /// one for a frame or two and let go.
+    pub coins: [bool; 2],
+    pub service: bool,
+}
+
+impl VsSystemInputs {
+    pub fn new() -> Self {
+        VsSystemInputs::default()
+    }
+
+    /// The bits the cabinet drives on a $4016 read.
+    pub fn read_4016(&self) -> u8 {
+        (self.service as u8) << 2
+            | (self.dip_switches & 0b11) << 3
+            | (self.coins[0] as u8) << 5
+            | (self.coins[1] as u8) << 6
+    }
+
+    /// The bits the cabinet drives on a $4017 read.
+    pub fn read_4017(&self) -> u8 {
+        self.dip_switches & VS_4017_BITS
+    }
+}
@@ -0,0 +1,28 @@
+/// `text` escaped for use inside a JSON string, for the few places that write JSON by hand.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            character if character.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a\"b\n"), "a\\\"b\\u000a");
+        assert_eq!(escape("LDA ($10),Y"), "LDA ($10),Y");
+    }
+}
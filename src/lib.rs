@@ -1,9 +1,51 @@
-use rand::Rng;
+//! An NES emulator.
+//!
+//! Load a ROM with [`cartridge::Cartridge::new`], put it on a [`bus::CpuBus`] and run it with
+//! [`cpu::CPU`]. Controllers are plugged into the bus through [`joypad::ControllerDevice`], and
+//! [`debugger`], [`disasm`] and [`cpu::trace`] are there for looking inside while it runs.
+//!
+//! There is one implementation of each of these; the other modules are the pieces they are built
+//! from. [`prelude`] gathers the types most users need.
+//!
+//! Everything but the 6502 core in [`cpu`] is behind the default `nes` feature. Without it the CPU
+//! runs on any [`cpu::bus::Bus`], for embedding in other systems.
 
+#[cfg(feature = "nes")]
 pub mod bus;
+#[cfg(feature = "nes")]
 pub mod cartridge;
+#[cfg(feature = "nes")]
+pub mod compat;
 pub mod cpu;
+#[cfg(feature = "nes")]
+pub mod debugger;
+#[cfg(feature = "nes")]
+pub mod demo;
+#[cfg(feature = "nes")]
+pub mod disasm;
 pub mod errors;
+#[cfg(feature = "nes")]
+pub mod filter;
+#[cfg(feature = "nes")]
+pub mod frame;
+#[cfg(feature = "nes")]
+pub(crate) mod hash;
+#[cfg(feature = "nes")]
+pub mod joypad;
+#[cfg(feature = "nes")]
+pub(crate) mod json;
 pub mod memory;
-pub mod opcodes;
-pub mod status;
+#[cfg(feature = "nes")]
+pub mod nametable;
+pub(crate) mod opcodes;
+#[cfg(feature = "nes")]
+pub mod palette;
+#[cfg(feature = "nes")]
+pub(crate) mod png;
+#[cfg(feature = "nes")]
+pub mod prelude;
+#[cfg(feature = "nes")]
+pub mod registers;
+#[cfg(feature = "nes")]
+pub mod savestate;
+pub(crate) mod status;
@@ -0,0 +1,372 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Parser, Subcommand};
+
+use nes_emulator::bus::{BusFault, CpuBus, ErrorPolicy, FAULT_LOG_CAPACITY};
+use nes_emulator::cartridge::chr::tile_sheet;
+use nes_emulator::cartridge::header::repair;
+use nes_emulator::cartridge::info::describe;
+use nes_emulator::cartridge::profile::ProfileDatabase;
+use nes_emulator::cartridge::Cartridge;
+use nes_emulator::cpu::accuracy::Accuracy;
+use nes_emulator::cpu::trace::CallDepth;
+use nes_emulator::cpu::{trace, StopReason, CPU};
+use nes_emulator::debugger::lockstep::run_lockstep;
+use nes_emulator::disasm::listing;
+use nes_emulator::errors::NesError;
+
+#[derive(Parser)]
+#[command(name = "nes-emulator", about = "An NES emulator")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a ROM headless from its reset vector
+    Run {
+        rom: PathBuf,
+        /// Write a nestest.log style trace of every instruction to this file
+        #[arg(long)]
+        trace: Option<PathBuf>,
+        /// Write the trace as JSON lines instead
+        #[arg(long)]
+        json: bool,
+        /// Indent the trace by subroutine and interrupt depth, or add a depth field to JSON
+        #[arg(long)]
+        call_depth: bool,
+        /// Start at this address (hex) instead of the reset vector
+        #[arg(long, value_parser = parse_hex)]
+        start: Option<u16>,
+        /// Stop after this many instructions
+        #[arg(long)]
+        instructions: Option<u64>,
+        /// Treat unmapped reads and bad writes as open bus instead of stopping
+        #[arg(long)]
+        continue_on_error: bool,
+    },
+    /// Run nestest.nes in automation mode, printing its trace
+    Nestest {
+        #[arg(long, default_value = "nestest/nestest.nes")]
+        rom: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run a ROM at two accuracy levels in lockstep and report where they first differ
+    Compare {
+        rom: PathBuf,
+        #[arg(long, value_parser = parse_accuracy, default_value = "balanced")]
+        left: Accuracy,
+        #[arg(long, value_parser = parse_accuracy, default_value = "cycle")]
+        right: Accuracy,
+        /// Start at this address (hex) instead of the reset vector
+        #[arg(long, value_parser = parse_hex)]
+        start: Option<u16>,
+        #[arg(long, default_value_t = 100_000)]
+        instructions: u64,
+    },
+    /// Print a ROM's header details and hashes
+    Rominfo { rom: PathBuf },
+    /// Write a copy of a ROM with a corrected header
+    RepairHeader {
+        rom: PathBuf,
+        output: PathBuf,
+        /// A game profile database to take mirroring from
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Save a ROM's CHR tiles as a PNG sheet, 16 tiles to a row
+    Chrdump {
+        rom: PathBuf,
+        output: PathBuf,
+        /// Four system palette entries (hex) for pixel values 0-3
+        #[arg(long, value_parser = parse_palette, default_value = "0f,00,10,30")]
+        palette: [u8; 4],
+    },
+    /// Disassemble a ROM's PRG, optionally guided by a code/data log
+    Disasm {
+        rom: PathBuf,
+        #[arg(long)]
+        cdl: Option<PathBuf>,
+    },
+}
+
+fn parse_hex(value: &str) -> Result<u16, String> {
+    u16::from_str_radix(value.trim_start_matches("0x").trim_start_matches('$'), 16)
+        .map_err(|error| error.to_string())
+}
+
+fn parse_accuracy(value: &str) -> Result<Accuracy, String> {
+    match value {
+        "fast" => Ok(Accuracy::Fast),
+        "balanced" => Ok(Accuracy::Balanced),
+        "cycle" => Ok(Accuracy::Cycle),
+        _ => Err("Expected fast, balanced or cycle".to_string()),
+    }
+}
+
+fn parse_palette(value: &str) -> Result<[u8; 4], String> {
+    let entries = value
+        .split(',')
+        .map(|entry| u8::from_str_radix(entry.trim(), 16).map_err(|error| error.to_string()))
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    entries
+        .try_into()
+        .map_err(|_| "Expected four palette entries".to_string())
+}
+
+fn write(path: &PathBuf, data: &[u8]) {
+    if let Err(error) = fs::write(path, data) {
+        eprintln!("Could not write {}: {}", path.display(), error);
+        process::exit(1);
+    }
+}
+
+fn read(path: &PathBuf) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|error| {
+        eprintln!("Could not read {}: {}", path.display(), error);
+        process::exit(1);
+    })
+}
+
+fn load(path: &PathBuf) -> CPU {
+    let cartridge = Cartridge::from_file(path).unwrap_or_else(|error| {
+        eprintln!("Could not load {}: {}", path.display(), error);
+        process::exit(1);
+    });
+
+    let mut cpu = CPU::new(CpuBus::new(cartridge));
+
+    match cpu.boot(true) {
+        Ok(Some(warning)) => eprintln!("{} Starting at $8000 instead.", warning),
+        Ok(None) => {}
+        Err(error) => {
+            eprintln!("Could not reset CPU: {}", error);
+            process::exit(1);
+        }
+    }
+
+    cpu
+}
+
+fn run(
+    cpu: &mut CPU,
+    mut output: Option<Box<dyn Write>>,
+    json: bool,
+    mut call_depth: Option<CallDepth>,
+) -> Result<StopReason, NesError> {
+    let write_error =
+        |error: std::io::Error| NesError::new(&format!("Could not write trace: {}", error));
+
+    let stop_reason = cpu.try_run_with_callback(|cpu| {
+        let Some(output) = output.as_mut() else {
+            return Ok(());
+        };
+
+        let depth = call_depth
+            .as_mut()
+            .and_then(|call_depth| call_depth.observe(cpu).ok());
+
+        let line = if json {
+            trace::format_trace_json(cpu).map(|line| match depth {
+                Some(depth) => trace::with_depth(&line, depth),
+                None => line,
+            })
+        } else {
+            trace::format_trace(cpu).map(|line| match depth {
+                Some(depth) => trace::indent(&line, depth),
+                None => line,
+            })
+        };
+
+        writeln!(output, "{}", line?).map_err(write_error)
+    })?;
+
+    if let Some(output) = output.as_mut() {
+        output.flush().map_err(write_error)?;
+    }
+
+    Ok(stop_reason)
+}
+
+/// List the accesses `--continue-on-error` let through, so a run that finished isn't mistaken for
+/// one that went cleanly.
+fn report_faults(faults: &[BusFault]) {
+    if faults.is_empty() {
+        return;
+    }
+
+    if faults.len() == FAULT_LOG_CAPACITY {
+        eprintln!(
+            "The last {} bus faults, earlier ones were dropped:",
+            faults.len()
+        );
+    } else {
+        eprintln!("{} bus faults:", faults.len());
+    }
+
+    for fault in faults {
+        eprintln!(
+            "  {:?} of ${:04X}: {}",
+            fault.access, fault.address, fault.kind
+        );
+    }
+}
+
+fn main() {
+    let result = match Cli::parse().command {
+        Command::Run {
+            rom,
+            trace,
+            json,
+            call_depth,
+            start,
+            instructions,
+            continue_on_error,
+        } => {
+            let mut cpu = load(&rom);
+
+            if continue_on_error {
+                cpu.bus.error_policy = ErrorPolicy::Continue;
+            }
+
+            if let Some(start) = start {
+                cpu.program_counter = start;
+            }
+            cpu.instruction_budget = instructions;
+
+            let output = trace.map(|path| {
+                let file = File::create(&path).unwrap_or_else(|error| {
+                    eprintln!("Could not create {}: {}", path.display(), error);
+                    process::exit(1);
+                });
+                Box::new(BufWriter::new(file)) as Box<dyn Write>
+            });
+
+            let result = run(&mut cpu, output, json, call_depth.then(CallDepth::new));
+            report_faults(&cpu.bus.take_faults());
+            result
+        }
+        Command::Nestest { rom, json } => {
+            let mut cpu = load(&rom);
+            cpu.program_counter = 0xc000;
+
+            run(&mut cpu, Some(Box::new(std::io::stdout())), json, None)
+        }
+        Command::Compare {
+            rom,
+            left,
+            right,
+            start,
+            instructions,
+        } => {
+            let mut machines = [load(&rom), load(&rom)];
+
+            for (cpu, accuracy) in machines.iter_mut().zip([left, right]) {
+                cpu.accuracy = accuracy;
+
+                if let Some(start) = start {
+                    cpu.program_counter = start;
+                }
+            }
+
+            let [mut left, mut right] = machines;
+
+            match run_lockstep(&mut left, &mut right, instructions, |_, _| {}) {
+                Ok(None) => {
+                    println!("No divergence in {} instructions", instructions);
+                    return;
+                }
+                Ok(Some(divergence)) => {
+                    println!(
+                        "Diverged after instruction {} at {:04X}:",
+                        divergence.instruction, divergence.program_counter
+                    );
+                    for difference in divergence.differences {
+                        println!("  {}", difference);
+                    }
+                    for (side, error) in [
+                        ("left", divergence.left_error),
+                        ("right", divergence.right_error),
+                    ] {
+                        if let Some(error) = error {
+                            println!("  The {} machine stopped: {}", side, error);
+                        }
+                    }
+                    process::exit(1);
+                }
+                Err(error) => Err(error),
+            }
+        }
+        Command::Rominfo { rom } => match describe(&read(&rom)) {
+            Ok(info) => {
+                print!("{}", info);
+                return;
+            }
+            Err(error) => Err(error),
+        },
+        Command::RepairHeader { rom, output, db } => {
+            let database = match db {
+                Some(db) => ProfileDatabase::parse(&String::from_utf8_lossy(&read(&db))),
+                None => Ok(ProfileDatabase::new()),
+            };
+
+            match database.and_then(|database| repair(&read(&rom), &database)) {
+                Ok(repaired) => {
+                    write(&output, &repaired);
+                    return;
+                }
+                Err(error) => Err(error),
+            }
+        }
+        Command::Chrdump {
+            rom,
+            output,
+            palette,
+        } => {
+            let cartridge = Cartridge::new(&read(&rom)).unwrap_or_else(|error| {
+                eprintln!("Could not load {}: {}", rom.display(), error);
+                process::exit(1);
+            });
+
+            if cartridge.chr_rom.is_empty() {
+                eprintln!(
+                    "{} has CHR RAM, so there are no tiles to dump",
+                    rom.display()
+                );
+                process::exit(1);
+            }
+
+            write(
+                &output,
+                &tile_sheet(&cartridge.chr_rom, palette).to_rgba().to_png(),
+            );
+            return;
+        }
+        Command::Disasm { rom, cdl } => {
+            let cartridge = Cartridge::new(&read(&rom)).unwrap_or_else(|error| {
+                eprintln!("Could not load {}: {}", rom.display(), error);
+                process::exit(1);
+            });
+
+            print!(
+                "{}",
+                listing(&cartridge, cdl.map(|cdl| read(&cdl)).as_deref())
+            );
+            return;
+        }
+    };
+
+    match result {
+        Ok(reason) => eprintln!("Stopped: {:?}", reason),
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            process::exit(1);
+        }
+    }
+}
@@ -1,12 +1,32 @@
+use std::fmt;
+
 use crate::errors::NesError;
 
 /// A memory object with read and write operations. Stores an array of 0xFFFF bytes.
-
+///
+/// Some hardware registers change when they are read: the controllers shift out their next button,
+/// and the PPU clears VBlank on $2002 and advances its address on $2007. Reads still take `&self`
+/// so that anything holding a shared reference to the machine can read memory, and devices behind
+/// such registers keep their state in `Cell`s. `mem_read` is the CPU's read and has those side
+/// effects; `mem_peek` is for debuggers and traces and must never change anything.
 pub trait Mem {
     fn mem_write(&mut self, address: u16, data: u8) -> Result<(), NesError>;
 
     fn mem_read(&self, address: u16) -> Result<u8, NesError>;
 
+    /// Read what `mem_read` would return without any of its side effects. Implementations with
+    /// side-effectful reads must override this.
+    fn mem_peek(&self, address: u16) -> Result<u8, NesError> {
+        self.mem_read(address)
+    }
+
+    fn mem_peek_u16(&self, address: u16) -> Result<u16, NesError> {
+        let lo = self.mem_peek(address)?;
+        let hi = self.mem_peek(address.wrapping_add(1))?;
+
+        Ok(u16::from_le_bytes([lo, hi]))
+    }
+
     fn mem_write_u16(&mut self, address: u16, data: u16) -> Result<(), NesError> {
         let [lo, hi] = data.to_le_bytes();
         self.mem_write(address, lo)?;
@@ -20,32 +40,40 @@ pub trait Mem {
 
         Ok(u16::from_le_bytes([lo, hi]))
     }
-
-    fn mem_read_u16_wrapping_boundary(&self, address: u16) -> Result<u16, NesError> {
-        let lo = self.mem_read(address)?;
-
-        let hi_address = address.wrapping_add(1);
-
-        if (hi_address & 0xff00) == (address & 0xff00) {
-            Ok(u16::from_le_bytes([lo, self.mem_read(hi_address)?]))
-        } else {
-            Ok(u16::from_le_bytes([lo, self.mem_read(address & 0xff00)?]))
-        }
-    }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct RAM {
     storage: Vec<u8>,
 }
 
 impl Mem for RAM {
     fn mem_write(&mut self, address: u16, data: u8) -> Result<(), NesError> {
-        self.storage[address as usize] = data;
+        let byte = self
+            .storage
+            .get_mut(address as usize)
+            .ok_or_else(|| out_of_range(address))?;
+        *byte = data;
         Ok(())
     }
 
     fn mem_read(&self, address: u16) -> Result<u8, NesError> {
-        Ok(self.storage[address as usize])
+        self.storage
+            .get(address as usize)
+            .copied()
+            .ok_or_else(|| out_of_range(address))
+    }
+}
+
+fn out_of_range(address: u16) -> NesError {
+    NesError::new(&format!("RAM has no address {:04X}", address))
+}
+
+impl fmt::Debug for RAM {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RAM")
+            .field("size", &self.storage.len())
+            .finish()
     }
 }
 
@@ -56,6 +84,14 @@ impl RAM {
         }
     }
 
+    pub fn as_slice(&self) -> &[u8] {
+        &self.storage
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.storage
+    }
+
     // pub fn print_page(&self, page: u8) {
     //     for i in 0..(0xf + 1) {
     //         let i = (i << 4) as u8;
@@ -0,0 +1,149 @@
+use crate::cartridge::chr::decode_tile;
+use crate::cartridge::Mirroring;
+use crate::errors::NesError;
+use crate::frame::IndexedImage;
+
+/// The size of one nametable including its attribute table.
+pub const NAMETABLE_SIZE: usize = 0x400;
+const TILES_WIDE: usize = 32;
+const TILES_HIGH: usize = 30;
+const ATTRIBUTE_TABLE_OFFSET: usize = 0x3c0;
+
+/// Everything needed to draw the background layer as the PPU holds it, for map ripping and for
+/// checking scroll logic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nametables<'a> {
+    /// The console's 2KB of VRAM, or 4KB for four screen cartridges.
+    pub vram: &'a [u8],
+    pub mirroring: Mirroring,
+    /// The pattern tables, as mapped into PPU $0000-$1FFF.
+    pub chr: &'a [u8],
+    /// $0000 or $1000, whichever PPUCTRL picks for the background.
+    pub pattern_table: u16,
+    /// Palette RAM at $3F00-$3F1F.
+    pub palette: &'a [u8; 32],
+}
+
+impl Nametables<'_> {
+    /// Draw nametable `index` ($2000, $2400, $2800 or $2C00 for 0-3) as a 256x240 image.
+    pub fn render(&self, index: usize) -> Result<IndexedImage, NesError> {
+        let table = self.table(index)?;
+
+        let width = TILES_WIDE * 8;
+        let height = TILES_HIGH * 8;
+        let mut pixels = vec![self.palette[0]; width * height];
+
+        for tile_y in 0..TILES_HIGH {
+            for tile_x in 0..TILES_WIDE {
+                let tile_index = table[tile_y * TILES_WIDE + tile_x] as usize;
+                let tile = decode_tile(self.chr, self.pattern_table as usize / 16 + tile_index)
+                    .unwrap_or_default();
+
+                let attribute = table[ATTRIBUTE_TABLE_OFFSET + (tile_y / 4) * 8 + tile_x / 4];
+                let shift = ((tile_y % 4) / 2) * 4 + ((tile_x % 4) / 2) * 2;
+                let palette = ((attribute >> shift) & 0b11) as usize;
+
+                for (y, row) in tile.iter().enumerate() {
+                    for (x, value) in row.iter().enumerate() {
+                        if *value != 0 {
+                            pixels[(tile_y * 8 + y) * width + tile_x * 8 + x] =
+                                self.palette[palette * 4 + *value as usize];
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(IndexedImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// All four nametables stitched into one 512x480 image, with the mirrored copies drawn where
+    /// the mirroring puts them.
+    pub fn render_all(&self) -> Result<IndexedImage, NesError> {
+        let width = TILES_WIDE * 8 * 2;
+        let height = TILES_HIGH * 8 * 2;
+        let mut pixels = vec![0; width * height];
+
+        for index in 0..4 {
+            let image = self.render(index)?;
+            let left = (index % 2) * image.width;
+            let top = (index / 2) * image.height;
+
+            for (y, row) in image.pixels.chunks_exact(image.width).enumerate() {
+                let start = (top + y) * width + left;
+                pixels[start..start + image.width].copy_from_slice(row);
+            }
+        }
+
+        Ok(IndexedImage {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// The bytes of logical nametable `index`, after mirroring.
+    fn table(&self, index: usize) -> Result<&[u8], NesError> {
+        let physical = match (self.mirroring, index) {
+            (_, 4..) => return Err(NesError::new(&format!("No nametable {}", index))),
+            (Mirroring::Horizontal, _) => index / 2,
+            (Mirroring::Vertical, _) => index % 2,
+            (Mirroring::FourScreen, _) => index,
+            (Mirroring::SingleScreenLower, _) => 0,
+            (Mirroring::SingleScreenUpper, _) => 1,
+            (Mirroring::Mapped(pages), _) => pages[index] as usize,
+        };
+
+        self.vram
+            .get(physical * NAMETABLE_SIZE..(physical + 1) * NAMETABLE_SIZE)
+            .ok_or_else(|| NesError::new("VRAM is too small for the mirroring"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::chr::TILE_SIZE;
+
+    #[test]
+    fn test_render_all() {
+        // Tile 1 is solid colour 3.
+        let mut chr = vec![0; TILE_SIZE * 2];
+        chr[TILE_SIZE..TILE_SIZE * 2].fill(0xff);
+
+        let mut vram = vec![0; NAMETABLE_SIZE * 2];
+        // Second physical nametable: tile 1 top left, using background palette 2.
+        vram[NAMETABLE_SIZE] = 1;
+        vram[NAMETABLE_SIZE + ATTRIBUTE_TABLE_OFFSET] = 0b10;
+
+        let mut palette = [0; 32];
+        palette[0] = 0x0f;
+        palette[11] = 0x16;
+
+        let nametables = Nametables {
+            vram: &vram,
+            mirroring: Mirroring::Vertical,
+            chr: &chr,
+            pattern_table: 0,
+            palette: &palette,
+        };
+
+        let image = nametables.render_all().unwrap();
+
+        assert_eq!((image.width, image.height), (512, 480));
+        assert_eq!(image.pixels[0], 0x0f);
+        assert_eq!(image.pixels[256], 0x16);
+        assert_eq!(image.pixels[240 * 512 + 256 + 7], 0x16);
+        assert_eq!(image.pixels[240 * 512 + 256 + 8], 0x0f);
+
+        let four_screen = Nametables {
+            mirroring: Mirroring::FourScreen,
+            ..nametables
+        };
+        assert!(four_screen.render(2).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+pub const FRAME_WIDTH: usize = 256;
+pub const FRAME_HEIGHT: usize = 240;
+
+/// The 64 colours the NES can output, as RGB.
+#[rustfmt::skip]
+pub static SYSTEM_PALETTE: [(u8, u8, u8); 64] = [
+    (0x80, 0x80, 0x80), (0x00, 0x3d, 0xa6), (0x00, 0x12, 0xb0), (0x44, 0x00, 0x96),
+    (0xa1, 0x00, 0x5e), (0xc7, 0x00, 0x28), (0xba, 0x06, 0x00), (0x8c, 0x17, 0x00),
+    (0x5c, 0x2f, 0x00), (0x10, 0x45, 0x00), (0x05, 0x4a, 0x00), (0x00, 0x47, 0x2e),
+    (0x00, 0x41, 0x66), (0x00, 0x00, 0x00), (0x05, 0x05, 0x05), (0x05, 0x05, 0x05),
+    (0xc7, 0xc7, 0xc7), (0x00, 0x77, 0xff), (0x21, 0x55, 0xff), (0x82, 0x37, 0xfa),
+    (0xeb, 0x2f, 0xb5), (0xff, 0x29, 0x50), (0xff, 0x22, 0x00), (0xd6, 0x32, 0x00),
+    (0xc4, 0x62, 0x00), (0x35, 0x80, 0x00), (0x05, 0x8f, 0x00), (0x00, 0x8a, 0x55),
+    (0x00, 0x99, 0xcc), (0x21, 0x21, 0x21), (0x09, 0x09, 0x09), (0x09, 0x09, 0x09),
+    (0xff, 0xff, 0xff), (0x0f, 0xd7, 0xff), (0x69, 0xa2, 0xff), (0xd4, 0x80, 0xff),
+    (0xff, 0x45, 0xf3), (0xff, 0x61, 0x8b), (0xff, 0x88, 0x33), (0xff, 0x9c, 0x12),
+    (0xfa, 0xbc, 0x20), (0x9f, 0xe3, 0x0e), (0x2b, 0xf0, 0x35), (0x0c, 0xf0, 0xa4),
+    (0x05, 0xfb, 0xff), (0x5e, 0x5e, 0x5e), (0x0d, 0x0d, 0x0d), (0x0d, 0x0d, 0x0d),
+    (0xff, 0xff, 0xff), (0xa6, 0xfc, 0xff), (0xb3, 0xec, 0xff), (0xda, 0xab, 0xeb),
+    (0xff, 0xa8, 0xf9), (0xff, 0xab, 0xb3), (0xff, 0xd2, 0xb0), (0xff, 0xef, 0xa6),
+    (0xff, 0xf7, 0x9c), (0xd7, 0xe8, 0x95), (0xa6, 0xed, 0xaf), (0xa2, 0xf2, 0xda),
+    (0x99, 0xff, 0xfc), (0xdd, 0xdd, 0xdd), (0x11, 0x11, 0x11), (0x11, 0x11, 0x11),
+];
+
+/// Each palette entry packed as the four RGBA bytes it is written out as.
+static RGBA_LOOKUP: [[u8; 4]; 64] = build_rgba_lookup();
+
+const fn build_rgba_lookup() -> [[u8; 4]; 64] {
+    let mut lookup = [[0; 4]; 64];
+    let mut index = 0;
+
+    while index < 64 {
+        let (r, g, b) = SYSTEM_PALETTE[index];
+        lookup[index] = [r, g, b, 0xff];
+        index += 1;
+    }
+
+    lookup
+}
+
+/// Convert a frame of palette indices into RGBA bytes, four per pixel. Only the low six bits of
+/// each index are used, so any byte is safe to pass in.
+///
+/// Every frontend does this once per pixel per frame, so it goes through a lookup table with whole
+/// pixel stores which the compiler can vectorise, rather than building each colour channel by
+/// channel.
+pub fn indices_to_rgba(indices: &[u8], rgba: &mut [u8]) {
+    for (pixel, index) in rgba.chunks_exact_mut(4).zip(indices) {
+        pixel.copy_from_slice(&RGBA_LOOKUP[(index & 0x3f) as usize]);
+    }
+}
+
+/// Convert a frame of palette indices into RGB bytes, three per pixel.
+pub fn indices_to_rgb(indices: &[u8], rgb: &mut [u8]) {
+    for (pixel, index) in rgb.chunks_exact_mut(3).zip(indices) {
+        let (r, g, b) = SYSTEM_PALETTE[(index & 0x3f) as usize];
+        pixel.copy_from_slice(&[r, g, b]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_indices_to_rgba() {
+        let indices = [0x00, 0x0d, 0x30, 0x41];
+        let mut rgba = [0; 16];
+
+        indices_to_rgba(&indices, &mut rgba);
+
+        assert_eq!(
+            rgba,
+            [
+                0x80, 0x80, 0x80, 0xff, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x3d,
+                0xa6, 0xff
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rgba_matches_rgb() {
+        let indices: Vec<u8> = (0..=255).collect();
+        let mut rgba = vec![0; indices.len() * 4];
+        let mut rgb = vec![0; indices.len() * 3];
+
+        indices_to_rgba(&indices, &mut rgba);
+        indices_to_rgb(&indices, &mut rgb);
+
+        for (rgba_pixel, rgb_pixel) in rgba.chunks_exact(4).zip(rgb.chunks_exact(3)) {
+            assert_eq!(&rgba_pixel[..3], rgb_pixel);
+            assert_eq!(rgba_pixel[3], 0xff);
+        }
+    }
+}
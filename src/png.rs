@@ -0,0 +1,110 @@
+use crate::hash::crc32;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The most a stored (uncompressed) deflate block can hold.
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+/// Encode RGBA pixels, four bytes each and row by row, as a PNG file.
+///
+/// The image data is stored without compression, which keeps this small enough not to need a
+/// deflate implementation. Files come out about as big as the raw pixels, which is fine for tile
+/// sheets and debugging dumps.
+pub fn encode_rgba(width: usize, height: usize, rgba: &[u8]) -> Vec<u8> {
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = vec![];
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, no filtering, no interlacing.
+    header.extend([8, 6, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    let mut scanlines = Vec::with_capacity(height * (width * 4 + 1));
+    // An empty image has no rows at all, and chunks can't be zero sized
+    for row in rgba
+        .chunks_exact(width.max(1) * 4)
+        .take(height * (width > 0) as usize)
+    {
+        scanlines.push(0);
+        scanlines.extend(row);
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32KB window and no preset dictionary.
+    let mut zlib = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK).peekable();
+
+    if blocks.peek().is_none() {
+        zlib.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        zlib.push(last as u8);
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend(block);
+    }
+
+    zlib.extend(adler32(data).to_be_bytes());
+
+    zlib
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_rgba() {
+        let png = encode_rgba(1, 1, &[0xff, 0x00, 0x00, 0xff]);
+
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(png[12..16], *b"IHDR");
+        assert_eq!(png[16..24], [0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+
+        // A filter byte and one pixel in a single stored block.
+        assert_eq!(
+            zlib_stored(&[0, 0xff, 0x00, 0x00, 0xff]),
+            [0x78, 0x01, 1, 5, 0, 0xfa, 0xff, 0, 0xff, 0x00, 0x00, 0xff, 0x05, 0x00, 0x01, 0xff]
+        );
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+}
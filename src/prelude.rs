@@ -0,0 +1,14 @@
+//! The types most users need, in one import:
+//! ```
+//! use nes_emulator::prelude::*;
+//! ```
+//! Everything here is covered by semver. Modules marked `#[doc(hidden)]` are implementation
+//! details and may change in any release.
+
+pub use crate::bus::CpuBus;
+pub use crate::cartridge::{Cartridge, Mirroring};
+pub use crate::cpu::{StopReason, CPU};
+pub use crate::errors::NesError;
+pub use crate::frame::{Frame, FrameBuffers};
+pub use crate::joypad::{Button, Buttons, ControllerDevice, Joypad};
+pub use crate::memory::Mem;
@@ -0,0 +1,187 @@
+//! The memory mapped registers at $2000-$2007 and $4000-$4017, by name.
+
+/// The eight PPU registers, which repeat every 8 bytes up to $3FFF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum PpuRegister {
+    Ctrl = 0x2000,
+    Mask = 0x2001,
+    Status = 0x2002,
+    OamAddr = 0x2003,
+    OamData = 0x2004,
+    Scroll = 0x2005,
+    Addr = 0x2006,
+    Data = 0x2007,
+}
+
+impl PpuRegister {
+    pub const ALL: [PpuRegister; 8] = [
+        PpuRegister::Ctrl,
+        PpuRegister::Mask,
+        PpuRegister::Status,
+        PpuRegister::OamAddr,
+        PpuRegister::OamData,
+        PpuRegister::Scroll,
+        PpuRegister::Addr,
+        PpuRegister::Data,
+    ];
+
+    /// The register at `address` or any of its mirrors.
+    pub fn from_address(address: u16) -> Option<Self> {
+        match address {
+            0x2000..=0x3fff => Some(PpuRegister::ALL[(address & 0x0007) as usize]),
+            _ => None,
+        }
+    }
+
+    /// The register's own address, not a mirror.
+    pub fn address(self) -> u16 {
+        self as u16
+    }
+
+    /// The name the NESdev wiki uses, like PPUSTATUS.
+    pub fn name(self) -> &'static str {
+        match self {
+            PpuRegister::Ctrl => "PPUCTRL",
+            PpuRegister::Mask => "PPUMASK",
+            PpuRegister::Status => "PPUSTATUS",
+            PpuRegister::OamAddr => "OAMADDR",
+            PpuRegister::OamData => "OAMDATA",
+            PpuRegister::Scroll => "PPUSCROLL",
+            PpuRegister::Addr => "PPUADDR",
+            PpuRegister::Data => "PPUDATA",
+        }
+    }
+}
+
+/// The APU and I/O registers. $4009 and $400D aren't connected to anything but are listed so every
+/// address in the block has a name.
+///
+/// $4016 and $4017 are the controller ports when read. Writing $4016 strobes both controllers,
+/// while a write to $4017 goes to the APU frame counter instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum ApuRegister {
+    Pulse1Ctrl = 0x4000,
+    Pulse1Sweep = 0x4001,
+    Pulse1TimerLow = 0x4002,
+    Pulse1Length = 0x4003,
+    Pulse2Ctrl = 0x4004,
+    Pulse2Sweep = 0x4005,
+    Pulse2TimerLow = 0x4006,
+    Pulse2Length = 0x4007,
+    TriangleCtrl = 0x4008,
+    TriangleUnused = 0x4009,
+    TriangleTimerLow = 0x400a,
+    TriangleLength = 0x400b,
+    NoiseCtrl = 0x400c,
+    NoiseUnused = 0x400d,
+    NoisePeriod = 0x400e,
+    NoiseLength = 0x400f,
+    DmcCtrl = 0x4010,
+    DmcLoad = 0x4011,
+    DmcAddress = 0x4012,
+    DmcLength = 0x4013,
+    OamDma = 0x4014,
+    Status = 0x4015,
+    Joypad1 = 0x4016,
+    Joypad2 = 0x4017,
+}
+
+impl ApuRegister {
+    pub const ALL: [ApuRegister; 24] = [
+        ApuRegister::Pulse1Ctrl,
+        ApuRegister::Pulse1Sweep,
+        ApuRegister::Pulse1TimerLow,
+        ApuRegister::Pulse1Length,
+        ApuRegister::Pulse2Ctrl,
+        ApuRegister::Pulse2Sweep,
+        ApuRegister::Pulse2TimerLow,
+        ApuRegister::Pulse2Length,
+        ApuRegister::TriangleCtrl,
+        ApuRegister::TriangleUnused,
+        ApuRegister::TriangleTimerLow,
+        ApuRegister::TriangleLength,
+        ApuRegister::NoiseCtrl,
+        ApuRegister::NoiseUnused,
+        ApuRegister::NoisePeriod,
+        ApuRegister::NoiseLength,
+        ApuRegister::DmcCtrl,
+        ApuRegister::DmcLoad,
+        ApuRegister::DmcAddress,
+        ApuRegister::DmcLength,
+        ApuRegister::OamDma,
+        ApuRegister::Status,
+        ApuRegister::Joypad1,
+        ApuRegister::Joypad2,
+    ];
+
+    /// The register at `address`. These aren't mirrored, and $4018-$401F (normally disabled test
+    /// registers) have no name.
+    pub fn from_address(address: u16) -> Option<Self> {
+        match address {
+            0x4000..=0x4017 => Some(ApuRegister::ALL[(address - 0x4000) as usize]),
+            _ => None,
+        }
+    }
+
+    pub fn address(self) -> u16 {
+        self as u16
+    }
+
+    /// The name the NESdev wiki uses, like SQ1_VOL. $4017 is called JOY2 after what reads see.
+    pub fn name(self) -> &'static str {
+        match self {
+            ApuRegister::Pulse1Ctrl => "SQ1_VOL",
+            ApuRegister::Pulse1Sweep => "SQ1_SWEEP",
+            ApuRegister::Pulse1TimerLow => "SQ1_LO",
+            ApuRegister::Pulse1Length => "SQ1_HI",
+            ApuRegister::Pulse2Ctrl => "SQ2_VOL",
+            ApuRegister::Pulse2Sweep => "SQ2_SWEEP",
+            ApuRegister::Pulse2TimerLow => "SQ2_LO",
+            ApuRegister::Pulse2Length => "SQ2_HI",
+            ApuRegister::TriangleCtrl => "TRI_LINEAR",
+            ApuRegister::TriangleUnused => "TRI_UNUSED",
+            ApuRegister::TriangleTimerLow => "TRI_LO",
+            ApuRegister::TriangleLength => "TRI_HI",
+            ApuRegister::NoiseCtrl => "NOISE_VOL",
+            ApuRegister::NoiseUnused => "NOISE_UNUSED",
+            ApuRegister::NoisePeriod => "NOISE_LO",
+            ApuRegister::NoiseLength => "NOISE_HI",
+            ApuRegister::DmcCtrl => "DMC_FREQ",
+            ApuRegister::DmcLoad => "DMC_RAW",
+            ApuRegister::DmcAddress => "DMC_START",
+            ApuRegister::DmcLength => "DMC_LEN",
+            ApuRegister::OamDma => "OAM_DMA",
+            ApuRegister::Status => "SND_CHN",
+            ApuRegister::Joypad1 => "JOY1",
+            ApuRegister::Joypad2 => "JOY2",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_addresses_round_trip() {
+        for register in PpuRegister::ALL {
+            assert_eq!(
+                PpuRegister::from_address(register.address()),
+                Some(register)
+            );
+        }
+
+        for register in ApuRegister::ALL {
+            assert_eq!(
+                ApuRegister::from_address(register.address()),
+                Some(register)
+            );
+        }
+
+        assert_eq!(PpuRegister::from_address(0x3ffa), Some(PpuRegister::Status));
+        assert_eq!(PpuRegister::from_address(0x4000), None);
+        assert_eq!(ApuRegister::from_address(0x4018), None);
+    }
+}
@@ -0,0 +1,376 @@
+//! Save states: a snapshot of the CPU, its RAM and the cartridge that can be written to disk and
+//! loaded back later.
+//!
+//! A state starts with a magic number and the CRC-32s of the PRG and CHR ROM it was taken from,
+//! followed by one section per component:
+//!
+//! ```text
+//! "NESS" prg_crc:u32 chr_crc:u32 { tag:[u8; 4] version:u16 length:u32 payload:[u8; length] }*
+//! ```
+//!
+//! All numbers are little endian. Each section carries its own version, so changing how one
+//! component is stored only bumps that section. When a section's layout changes, add a
+//! [`Migration`] to [`MIGRATIONS`] that turns the previous version's payload into the new one;
+//! states from older versions of the crate are then upgraded step by step as they load. Sections
+//! this version doesn't know about are skipped.
+
+use crate::cartridge::{Cartridge, Mirroring};
+use crate::cpu::CPU;
+use crate::errors::NesError;
+use crate::hash::crc32;
+
+const MAGIC: &[u8; 4] = b"NESS";
+const HEADER_LENGTH: usize = 12;
+const SECTION_HEADER_LENGTH: usize = 10;
+
+const CPU_SECTION: Section = Section {
+    tag: *b"CPU ",
+    version: 2,
+};
+const RAM_SECTION: Section = Section {
+    tag: *b"RAM ",
+    version: 1,
+};
+const CARTRIDGE_SECTION: Section = Section {
+    tag: *b"CART",
+    version: 1,
+};
+
+/// A section as read: its tag, version and payload.
+type RawSection<'a> = ([u8; 4], u16, &'a [u8]);
+
+/// A kind of section and the version this crate writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Section {
+    tag: [u8; 4],
+    version: u16,
+}
+
+/// Upgrades a section's payload from version `to - 1` to version `to`.
+pub struct Migration {
+    pub tag: [u8; 4],
+    pub to: u16,
+    pub migrate: fn(&[u8]) -> Result<Vec<u8>, NesError>,
+}
+
+/// Every layout change since version 1 of each section, oldest first.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    tag: CPU_SECTION.tag,
+    to: 2,
+    migrate: add_cycles,
+}];
+
+/// CPU version 2 added the cycle count. Version 1 states didn't keep it, so it starts again from 0.
+fn add_cycles(payload: &[u8]) -> Result<Vec<u8>, NesError> {
+    Ok([payload, &0u64.to_le_bytes()].concat())
+}
+
+/// Snapshot everything needed to carry on from here. Transient state (a pending NMI, debugger
+/// watches, listeners) isn't included.
+pub fn save_state(cpu: &CPU) -> Vec<u8> {
+    let cartridge = &cpu.bus.cartridge;
+
+    let mut state = MAGIC.to_vec();
+    state.extend(crc32(&cartridge.prg_rom).to_le_bytes());
+    state.extend(crc32(&cartridge.chr_rom).to_le_bytes());
+
+    write_section(&mut state, CPU_SECTION, &cpu_payload(cpu));
+    write_section(&mut state, RAM_SECTION, cpu.bus.ram());
+    write_section(&mut state, CARTRIDGE_SECTION, &cartridge_payload(cartridge));
+
+    state
+}
+
+/// Carry on from a state made by `save_state`, with the same ROM loaded. Nothing is changed unless
+/// the whole state is valid.
+pub fn load_state(cpu: &mut CPU, state: &[u8]) -> Result<(), NesError> {
+    if state.len() < HEADER_LENGTH || &state[0..4] != MAGIC {
+        return Err(NesError::new("Not a save state."));
+    }
+
+    let cartridge = &cpu.bus.cartridge;
+    let prg_crc = u32::from_le_bytes(state[4..8].try_into().unwrap());
+    let chr_crc = u32::from_le_bytes(state[8..12].try_into().unwrap());
+    let loaded_prg_crc = crc32(&cartridge.prg_rom);
+    let loaded_chr_crc = crc32(&cartridge.chr_rom);
+
+    if (prg_crc, chr_crc) != (loaded_prg_crc, loaded_chr_crc) {
+        return Err(NesError::new(&format!(
+            "This save state is for a different ROM (PRG {:08X}, CHR {:08X}) than the one loaded \
+             (PRG {:08X}, CHR {:08X}).",
+            prg_crc, chr_crc, loaded_prg_crc, loaded_chr_crc
+        )));
+    }
+
+    let sections = read_sections(&state[HEADER_LENGTH..])?;
+    let payload = |section| current_payload(&sections, section, MIGRATIONS);
+
+    let registers = payload(CPU_SECTION)?;
+    let ram = payload(RAM_SECTION)?;
+    let cart = payload(CARTRIDGE_SECTION)?;
+
+    if registers.len() != 23 {
+        return Err(NesError::new(
+            "The CPU section of the save state is damaged.",
+        ));
+    }
+
+    if ram.len() != cpu.bus.ram().len() {
+        return Err(NesError::new(
+            "The RAM section of the save state is damaged.",
+        ));
+    }
+
+    restore_cartridge(&mut cpu.bus.cartridge, &cart)?;
+
+    cpu.register_a = registers[0];
+    cpu.register_x = registers[1];
+    cpu.register_y = registers[2];
+    cpu.status.set_from_byte(registers[3]);
+    cpu.program_counter = u16::from_le_bytes([registers[4], registers[5]]);
+    cpu.stack_pointer = registers[6];
+    cpu.instruction_count = u64::from_le_bytes(registers[7..15].try_into().unwrap());
+    cpu.cycles = u64::from_le_bytes(registers[15..23].try_into().unwrap());
+    cpu.bus.ram_mut().copy_from_slice(&ram);
+
+    Ok(())
+}
+
+fn write_section(state: &mut Vec<u8>, section: Section, payload: &[u8]) {
+    state.extend(section.tag);
+    state.extend(section.version.to_le_bytes());
+    state.extend((payload.len() as u32).to_le_bytes());
+    state.extend(payload);
+}
+
+/// Split the body of a state into its sections as (tag, version, payload).
+fn read_sections(mut body: &[u8]) -> Result<Vec<RawSection<'_>>, NesError> {
+    let mut sections = vec![];
+
+    while !body.is_empty() {
+        if body.len() < SECTION_HEADER_LENGTH {
+            return Err(NesError::new("The save state is cut short."));
+        }
+
+        let tag: [u8; 4] = body[0..4].try_into().unwrap();
+        let version = u16::from_le_bytes([body[4], body[5]]);
+        let length = u32::from_le_bytes(body[6..10].try_into().unwrap()) as usize;
+        body = &body[SECTION_HEADER_LENGTH..];
+
+        if body.len() < length {
+            return Err(NesError::new("The save state is cut short."));
+        }
+
+        sections.push((tag, version, &body[..length]));
+        body = &body[length..];
+    }
+
+    Ok(sections)
+}
+
+/// The payload of `section`, migrated up to the version this crate writes.
+fn current_payload(
+    sections: &[RawSection<'_>],
+    section: Section,
+    migrations: &[Migration],
+) -> Result<Vec<u8>, NesError> {
+    let name = String::from_utf8_lossy(&section.tag).trim_end().to_string();
+
+    let Some((_, mut version, payload)) = sections.iter().find(|(tag, _, _)| *tag == section.tag)
+    else {
+        return Err(NesError::new(&format!(
+            "The save state has no {} section.",
+            name
+        )));
+    };
+
+    if version > section.version {
+        return Err(NesError::new(&format!(
+            "The save state's {} section is version {}, but this version of the emulator only \
+             understands up to {}.",
+            name, version, section.version
+        )));
+    }
+
+    let mut payload = payload.to_vec();
+
+    while version < section.version {
+        let Some(migration) = migrations
+            .iter()
+            .find(|migration| migration.tag == section.tag && migration.to == version + 1)
+        else {
+            return Err(NesError::new(&format!(
+                "The save state's {} section is version {}, which can no longer be loaded.",
+                name, version
+            )));
+        };
+
+        payload = (migration.migrate)(&payload)?;
+        version += 1;
+    }
+
+    Ok(payload)
+}
+
+pub(crate) fn cpu_payload(cpu: &CPU) -> Vec<u8> {
+    let mut payload = vec![
+        cpu.register_a,
+        cpu.register_x,
+        cpu.register_y,
+        cpu.status.get_status_byte(),
+    ];
+    payload.extend(cpu.program_counter.to_le_bytes());
+    payload.push(cpu.stack_pointer);
+    payload.extend(cpu.instruction_count.to_le_bytes());
+    payload.extend(cpu.cycles.to_le_bytes());
+    payload
+}
+
+/// The mirroring, mapper registers and cartridge RAM, each but the mirroring behind a u32 length.
+pub(crate) fn cartridge_payload(cartridge: &Cartridge) -> Vec<u8> {
+    let mut payload = match cartridge.mirroring_type {
+        Mirroring::Horizontal => vec![0, 0, 0, 0, 0],
+        Mirroring::Vertical => vec![1, 0, 0, 0, 0],
+        Mirroring::FourScreen => vec![2, 0, 0, 0, 0],
+        Mirroring::SingleScreenLower => vec![3, 0, 0, 0, 0],
+        Mirroring::SingleScreenUpper => vec![4, 0, 0, 0, 0],
+        Mirroring::Mapped(nametables) => [&[5], &nametables[..]].concat(),
+    };
+
+    for part in [
+        cartridge.mapper.state(),
+        cartridge.prg_ram.clone(),
+        cartridge.chr_ram.clone(),
+    ] {
+        payload.extend((part.len() as u32).to_le_bytes());
+        payload.extend(part);
+    }
+
+    payload
+}
+
+/// Checked in full before anything is changed, so it's the last step that can fail.
+fn restore_cartridge(cartridge: &mut Cartridge, payload: &[u8]) -> Result<(), NesError> {
+    let damaged = || NesError::new("The cartridge section of the save state is damaged.");
+
+    let mirroring = match payload.first() {
+        Some(0) => Mirroring::Horizontal,
+        Some(1) => Mirroring::Vertical,
+        Some(2) => Mirroring::FourScreen,
+        Some(3) => Mirroring::SingleScreenLower,
+        Some(4) => Mirroring::SingleScreenUpper,
+        Some(5) if payload.len() >= 5 => Mirroring::Mapped(payload[1..5].try_into().unwrap()),
+        _ => return Err(damaged()),
+    };
+
+    let mut rest = payload.get(5..).ok_or_else(damaged)?;
+    let mut parts = vec![];
+
+    for _ in 0..3 {
+        let length = rest.get(0..4).ok_or_else(damaged)?;
+        let length = u32::from_le_bytes(length.try_into().unwrap()) as usize;
+        parts.push(rest.get(4..4 + length).ok_or_else(damaged)?);
+        rest = &rest[4 + length..];
+    }
+
+    let [mapper, prg_ram, chr_ram] = parts[..] else {
+        unreachable!()
+    };
+
+    if prg_ram.len() != cartridge.prg_ram.len() || chr_ram.len() != cartridge.chr_ram.len() {
+        return Err(damaged());
+    }
+
+    let mut restored_mapper = cartridge.mapper.clone();
+    restored_mapper.restore(mapper)?;
+
+    cartridge.mapper = restored_mapper;
+    cartridge.mirroring_type = mirroring;
+    cartridge.prg_ram.copy_from_slice(prg_ram);
+    cartridge.chr_ram.copy_from_slice(chr_ram);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::test::cpu_with_program;
+    use crate::memory::Mem;
+
+    #[test]
+    fn test_round_trip() {
+        // LDA #$42; STA $10; LDX #$07
+        let mut cpu = cpu_with_program(&[0xa9, 0x42, 0x85, 0x10, 0xa2, 0x07]);
+        cpu.run().unwrap();
+        let state = save_state(&cpu);
+
+        let mut restored = cpu_with_program(&[0xa9, 0x42, 0x85, 0x10, 0xa2, 0x07]);
+        load_state(&mut restored, &state).unwrap();
+
+        assert_eq!(restored.register_a, 0x42);
+        assert_eq!(restored.register_x, 0x07);
+        assert_eq!(restored.program_counter, cpu.program_counter);
+        assert_eq!(restored.instruction_count, cpu.instruction_count);
+        assert_eq!(restored.bus.mem_peek(0x0010).unwrap(), 0x42);
+        assert_eq!(save_state(&restored), state);
+    }
+
+    #[test]
+    fn test_different_rom() {
+        let state = save_state(&cpu_with_program(&[]));
+        let mut cpu = cpu_with_program(&[]);
+        cpu.bus.cartridge.prg_rom[0] = 0xea;
+        cpu.register_a = 0x33;
+
+        let error = load_state(&mut cpu, &state).unwrap_err();
+        assert!(error
+            .message
+            .starts_with("This save state is for a different ROM"));
+        assert_eq!(cpu.register_a, 0x33);
+    }
+
+    /// Replace the version of the CPU section in `state`, which comes first.
+    fn with_cpu_version(state: &[u8], version: u16) -> Vec<u8> {
+        let mut state = state.to_vec();
+        state[HEADER_LENGTH + 4..HEADER_LENGTH + 6].copy_from_slice(&version.to_le_bytes());
+        state
+    }
+
+    #[test]
+    fn test_newer_section() {
+        let mut cpu = cpu_with_program(&[]);
+        let state = with_cpu_version(&save_state(&cpu), CPU_SECTION.version + 1);
+
+        assert_eq!(
+            load_state(&mut cpu, &state).unwrap_err().message,
+            "The save state's CPU section is version 3, but this version of the emulator only \
+             understands up to 2."
+        );
+    }
+
+    #[test]
+    fn test_migration() {
+        let mut cpu = cpu_with_program(&[]);
+        cpu.register_y = 0x10;
+        cpu.cycles = 1234;
+        let state = save_state(&cpu);
+
+        // A version 1 CPU section is the same without the 8 byte cycle count on the end
+        let start = HEADER_LENGTH + SECTION_HEADER_LENGTH;
+        let mut old_state = state[..start + 15].to_vec();
+        old_state.extend(&state[start + 23..]);
+        old_state[HEADER_LENGTH + 4..HEADER_LENGTH + 10].copy_from_slice(&[1, 0, 15, 0, 0, 0]);
+
+        cpu.register_y = 0;
+        load_state(&mut cpu, &old_state).unwrap();
+        assert_eq!(cpu.register_y, 0x10);
+        assert_eq!(cpu.cycles, 0);
+
+        let state = with_cpu_version(&old_state, 0);
+        assert_eq!(
+            load_state(&mut cpu, &state).unwrap_err().message,
+            "The save state's CPU section is version 0, which can no longer be loaded."
+        );
+    }
+}
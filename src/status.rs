@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Flag {
     Negative,
     Overflow,
@@ -9,7 +10,7 @@ pub enum Flag {
     Carry,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Status {
     negative: bool,
     overflow: bool,
@@ -21,6 +22,12 @@ pub struct Status {
     carry: bool,
 }
 
+impl Default for Status {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Status {
     pub fn new() -> Self {
         Status {
@@ -91,7 +98,7 @@ impl Status {
         self.set_zero_flag(result);
         self.set_negative_flag(result);
 
-        return result;
+        result
     }
 
     pub fn set_increment_flags(&mut self, value: u8) -> u8 {
@@ -100,7 +107,7 @@ impl Status {
         self.set_zero_flag(result);
         self.set_negative_flag(result);
 
-        return result;
+        result
     }
 
     pub fn read_flag(&self, flag: Flag) -> bool {
@@ -159,7 +166,7 @@ mod test {
         let mut status = Status::new();
         status.set_flag(Flag::Negative, true);
 
-        assert_eq!(status.negative, true);
+        assert!(status.negative);
     }
 
     #[test]
@@ -169,7 +176,7 @@ mod test {
 
         let negative = status.read_flag(Flag::Negative);
 
-        assert_eq!(negative, true);
+        assert!(negative);
     }
 
     #[test]
@@ -177,7 +184,7 @@ mod test {
         let mut status = Status::new();
         status.set_negative_flag(0b1000_0000);
 
-        assert_eq!(status.negative, true);
+        assert!(status.negative);
     }
 
     #[test]
@@ -185,7 +192,7 @@ mod test {
         let mut status = Status::new();
         status.set_zero_flag(0b0000_0000);
 
-        assert_eq!(status.zero, true);
+        assert!(status.zero);
     }
 
     #[test]
@@ -198,7 +205,7 @@ mod test {
 
         let status_byte = status.get_status_byte();
 
-        assert_eq!(status_byte, 0b1100_0101);
+        assert_eq!(status_byte, 0b1110_0101);
     }
 
     #[test]
@@ -0,0 +1,84 @@
+//! Checks that once a CPU has been built, running it never touches the heap, so emulation timing
+//! stays predictable on WASM and realtime frontends.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+
+use nes_emulator::bus::{CpuBus, ErrorPolicy, FAULT_LOG_CAPACITY};
+use nes_emulator::cartridge::Cartridge;
+use nes_emulator::cpu::CPU;
+
+struct CountingAllocator;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(|counting| counting.get()) {
+            ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        }
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_steady_state_does_not_allocate() {
+    let raw = fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/nestest/nestest.nes"))
+        .expect("nestest.nes not found");
+
+    let mut cpu = CPU::new(CpuBus::new(Cartridge::new(&raw).unwrap()));
+    cpu.reset().expect("Could not reset CPU");
+    cpu.program_counter = 0xc000;
+    cpu.interrupt_log.enable();
+
+    COUNTING.with(|counting| counting.set(true));
+
+    for _ in 0..5000 {
+        cpu.step().unwrap();
+    }
+
+    COUNTING.with(|counting| counting.set(false));
+
+    assert_eq!(ALLOCATIONS.with(|allocations| allocations.get()), 0);
+}
+
+#[test]
+fn test_recording_faults_does_not_allocate() {
+    // LDA $2002; STA $8000; JMP $8000, which faults twice per loop with no PPU or registers
+    let mut raw: Vec<u8> = vec![0x4e, 0x45, 0x53, 0x1a, 0x01, 0x00, 0x00, 0x00];
+    raw.extend([0; 8]);
+    let mut prg_rom = vec![0; 0x4000];
+    prg_rom[..9].copy_from_slice(&[0xad, 0x02, 0x20, 0x8d, 0x00, 0x80, 0x4c, 0x00, 0x80]);
+    prg_rom[0x3ffc..].copy_from_slice(&[0x00, 0x80, 0x00, 0x80]);
+    raw.extend(prg_rom);
+
+    let mut bus = CpuBus::new(Cartridge::new(&raw).unwrap());
+    bus.error_policy = ErrorPolicy::Continue;
+
+    let mut cpu = CPU::new(bus);
+    cpu.reset().expect("Could not reset CPU");
+
+    COUNTING.with(|counting| counting.set(true));
+
+    // Enough to fill the fault log and start dropping the oldest
+    for _ in 0..5000 {
+        cpu.step().unwrap();
+    }
+
+    COUNTING.with(|counting| counting.set(false));
+
+    assert_eq!(ALLOCATIONS.with(|allocations| allocations.get()), 0);
+    assert_eq!(cpu.bus.take_faults().len(), FAULT_LOG_CAPACITY);
+}
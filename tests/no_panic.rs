@@ -0,0 +1,201 @@
+//! Throws random input at the public API. A library shouldn't take its host down, so everything
+//! here has to come back as a value or an error, never a panic. The seed is fixed so a failure
+//! can be replayed.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use nes_emulator::bus::{CpuBus, ErrorPolicy};
+use nes_emulator::cartridge::header::{export, repair};
+use nes_emulator::cartridge::info::describe;
+use nes_emulator::cartridge::loader::RomLoader;
+use nes_emulator::cartridge::profile::ProfileDatabase;
+use nes_emulator::cartridge::{Cartridge, Mirroring, NES_TAG, PRG_ROM_PAGE_SIZE};
+use nes_emulator::cpu::{trace, CPU};
+use nes_emulator::disasm::{disassemble, listing};
+use nes_emulator::filter::RgbaImage;
+use nes_emulator::frame::IndexedImage;
+use nes_emulator::memory::{Mem, RAM};
+use nes_emulator::nametable::Nametables;
+use nes_emulator::savestate::{load_state, save_state};
+
+const SEED: u64 = 0x6502;
+const ROUNDS: usize = 50;
+const INSTRUCTIONS: usize = 2000;
+const MAPPERS: [u8; 7] = [0, 4, 24, 26, 118, 119, 157];
+
+fn random_bytes(rng: &mut StdRng, length: usize) -> Vec<u8> {
+    (0..length).map(|_| rng.gen()).collect()
+}
+
+/// Up to `max_length` random bytes.
+fn some_bytes(rng: &mut StdRng, max_length: usize) -> Vec<u8> {
+    let length = rng.gen_range(0..max_length);
+    random_bytes(rng, length)
+}
+
+/// A ROM with a believable header for one of the supported mappers and random contents.
+fn random_rom(rng: &mut StdRng) -> Vec<u8> {
+    let mapper = MAPPERS[rng.gen_range(0..MAPPERS.len())];
+    let prg_pages = rng.gen_range(1..=4u8);
+    let chr_pages = rng.gen_range(0..=2u8);
+
+    let mut rom = NES_TAG.to_vec();
+    rom.extend([
+        prg_pages,
+        chr_pages,
+        (mapper << 4) | (rng.gen::<u8>() & 0b1011),
+        (mapper & 0xf0) | (rng.gen::<u8>() & 0b1011),
+    ]);
+    rom.extend(random_bytes(rng, 8));
+    rom.extend(random_bytes(
+        rng,
+        prg_pages as usize * PRG_ROM_PAGE_SIZE + chr_pages as usize * 8192,
+    ));
+    rom
+}
+
+fn exercise_cartridge(rng: &mut StdRng, cartridge: &mut Cartridge) {
+    let _ = cartridge.debug_state();
+    let _ = cartridge.save_data();
+    let _ = export(cartridge, None);
+    let _ = listing(cartridge, Some(&random_bytes(rng, 64)));
+
+    for _ in 0..256 {
+        let _ = cartridge.cpu_write(rng.gen_range(0x8000..=0xffff), rng.gen());
+        let _ = cartridge.cpu_read(rng.gen_range(0x8000..=0xffff));
+        let _ = cartridge.prg_ram_read(rng.gen_range(0x6000..=0x7fff));
+        cartridge.ppu_write(rng.gen_range(0..0x2000), rng.gen());
+        let _ = cartridge.ppu_read(rng.gen_range(0..0x2000));
+    }
+
+    let _ = cartridge.take_dirty_tiles();
+}
+
+fn exercise_cpu(rng: &mut StdRng, cartridge: Cartridge) {
+    let mut cpu = CPU::new(CpuBus::new(cartridge));
+    cpu.bus.error_policy = ErrorPolicy::Continue;
+    let _ = cpu.reset();
+
+    for _ in 0..INSTRUCTIONS {
+        if cpu.step().is_err() {
+            cpu.program_counter = rng.gen();
+        }
+    }
+
+    let _ = cpu.bus.take_faults();
+
+    let mut state = save_state(&cpu);
+    let _ = load_state(&mut cpu, &state);
+    state.truncate(rng.gen_range(0..state.len()));
+    let _ = load_state(&mut cpu, &state);
+    let _ = load_state(&mut cpu, &some_bytes(rng, 64));
+
+    let _ = cpu.bus.load_save(&random_bytes(rng, 8192));
+    let _ = cpu.bus.controller(rng.gen_range(0..4));
+}
+
+#[test]
+fn test_random_input_does_not_panic() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let database = ProfileDatabase::new();
+
+    for _ in 0..ROUNDS {
+        let garbage = some_bytes(&mut rng, 64);
+        let _ = Cartridge::new(&garbage);
+        let _ = describe(&garbage);
+        let _ = disassemble(&garbage, rng.gen(), Some(&garbage));
+
+        let mut rom = random_rom(&mut rng);
+
+        // Cut some short so the header promises more than there is
+        if rng.gen_bool(0.2) {
+            rom.truncate(rng.gen_range(0..rom.len()));
+        }
+
+        let _ = describe(&rom);
+        let _ = repair(&rom, &database);
+
+        let mut loader = RomLoader::new();
+        loader.push(&rom);
+        let _ = loader.expected_size();
+
+        if let Ok(mut cartridge) = loader.finish() {
+            exercise_cartridge(&mut rng, &mut cartridge);
+            exercise_cpu(&mut rng, cartridge);
+        }
+    }
+}
+
+#[test]
+fn test_top_of_address_space_does_not_panic() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for _ in 0..ROUNDS {
+        let Ok(cartridge) = Cartridge::new(&random_rom(&mut rng)) else {
+            continue;
+        };
+
+        let mut cpu = CPU::new(CpuBus::new(cartridge));
+        cpu.bus.error_policy = ErrorPolicy::Continue;
+
+        // An operand fetch from $FFFF wraps around to $0000
+        cpu.program_counter = 0xffff;
+        let _ = trace::trace(&cpu);
+        let _ = cpu.step();
+
+        // RTS pulling $FFFF returns to $0000
+        cpu.program_counter = 0x0000;
+        cpu.stack_pointer = 0xfd;
+        cpu.bus.mem_write(0x0000, 0x60).unwrap();
+        cpu.bus.mem_write(0x01fe, 0xff).unwrap();
+        cpu.bus.mem_write(0x01ff, 0xff).unwrap();
+        cpu.step().unwrap();
+        assert_eq!(cpu.program_counter, 0x0000);
+    }
+}
+
+#[test]
+fn test_odd_sizes_do_not_panic() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    let mut ram = RAM::new(0);
+    assert!(ram.mem_write(0x0000, 1).is_err());
+    assert!(ram.mem_read(0xffff).is_err());
+
+    let mut empty = RgbaImage::new(0, 0);
+    assert_eq!(empty.pixel(-1, 5), [0; 4]);
+    empty.set_pixel(3, 3, [0xff; 4]);
+    let _ = empty.to_png();
+
+    let palette = [0; 32];
+
+    for _ in 0..ROUNDS {
+        let vram = some_bytes(&mut rng, 0x1400);
+        let chr = some_bytes(&mut rng, 0x2400);
+        let mirroring = match rng.gen_range(0..6) {
+            0 => Mirroring::Horizontal,
+            1 => Mirroring::Vertical,
+            2 => Mirroring::FourScreen,
+            3 => Mirroring::SingleScreenLower,
+            4 => Mirroring::SingleScreenUpper,
+            _ => Mirroring::Mapped(rng.gen()),
+        };
+
+        let nametables = Nametables {
+            vram: &vram,
+            mirroring,
+            chr: &chr,
+            pattern_table: rng.gen(),
+            palette: &palette,
+        };
+        let _ = nametables.render(rng.gen_range(0..6));
+
+        let image = IndexedImage {
+            width: rng.gen_range(0..16),
+            height: rng.gen_range(0..16),
+            pixels: some_bytes(&mut rng, 256),
+        };
+        let _ = image.to_rgba();
+    }
+}
@@ -0,0 +1,153 @@
+//! Replays every fixture in `tests/regression` headless and checks the CPU against the recorded
+//! checkpoints, so refactors can't silently break ROMs that are known to work.
+//!
+//! A fixture is a text file of lines like:
+//! ```text
+//! rom nestest/nestest.nes
+//! crc32 9E179D92
+//! start C000
+//! checkpoint 1000 CF2D  50 18     BVC $CF47   ... SP:FB CYC:2343
+//! ```
+//! where each checkpoint is the expected trace line before that many instructions have run, and
+//! optionally the CPU cycle count at that point.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nes_emulator::bus::CpuBus;
+use nes_emulator::cartridge::Cartridge;
+use nes_emulator::cpu::coverage::OpcodeCoverage;
+use nes_emulator::cpu::{trace, CPU};
+use nes_emulator::memory::Mem;
+
+struct Fixture {
+    rom: PathBuf,
+    crc32: u32,
+    start: Option<u16>,
+    checkpoints: Vec<(u64, String)>,
+}
+
+fn parse_fixture(path: &Path) -> Fixture {
+    let contents = fs::read_to_string(path).expect("Could not read fixture");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    let mut rom = None;
+    let mut crc = None;
+    let mut start = None;
+    let mut checkpoints = vec![];
+
+    for line in contents.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once(' ').expect("Malformed fixture line");
+
+        match key {
+            "rom" => rom = Some(root.join(value)),
+            "crc32" => crc = Some(u32::from_str_radix(value, 16).expect("Bad crc32")),
+            "start" => start = Some(u16::from_str_radix(value, 16).expect("Bad start")),
+            "checkpoint" => {
+                let (count, expected) = value.split_once(' ').expect("Malformed checkpoint");
+                checkpoints.push((count.parse().expect("Bad count"), expected.to_string()));
+            }
+            _ => panic!("Unknown fixture key {}", key),
+        }
+    }
+
+    checkpoints.sort_by_key(|(count, _)| *count);
+
+    Fixture {
+        rom: rom.expect("Fixture has no rom"),
+        crc32: crc.expect("Fixture has no crc32"),
+        start,
+        checkpoints,
+    }
+}
+
+/// Opcodes none of the fixtures reach. Anything added to the opcode table has to be run by a
+/// fixture or listed here, and anything here that a fixture starts running has to come off.
+const UNCOVERED: [u8; 6] = [
+    0x00, // BRK
+    0x58, // CLI
+    0x82, // *NOP #
+    0x89, // *NOP #
+    0xc2, // *NOP #
+    0xe2, // *NOP #
+];
+
+/// The standard CRC-32, as the fixtures record it.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn replay(fixture: &Fixture, coverage: &mut OpcodeCoverage) {
+    let raw = fs::read(&fixture.rom).expect("Could not read rom");
+
+    assert_eq!(crc32(&raw), fixture.crc32, "{:?} has changed", fixture.rom);
+
+    let mut cpu = CPU::new(CpuBus::new(
+        Cartridge::new(&raw).expect("Could not load rom"),
+    ));
+    cpu.reset().expect("Could not reset CPU");
+
+    if let Some(start) = fixture.start {
+        cpu.program_counter = start;
+    }
+
+    for (count, expected) in &fixture.checkpoints {
+        while cpu.instruction_count < *count {
+            coverage.record(cpu.bus.mem_peek(cpu.program_counter).unwrap());
+            cpu.step().unwrap();
+        }
+
+        let mut line = trace::trace(&cpu).expect("Error producing trace");
+
+        if expected.contains(" CYC:") {
+            line = format!("{} CYC:{}", line, cpu.cycles);
+        }
+
+        assert_eq!(
+            &line, expected,
+            "{:?} diverged before instruction {}",
+            fixture.rom, count
+        );
+    }
+}
+
+#[test]
+fn test_regression_fixtures() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/regression");
+
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(directory)
+        .expect("No regression directory")
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    fixtures.sort();
+
+    assert!(!fixtures.is_empty());
+
+    let mut coverage = OpcodeCoverage::new();
+
+    for path in fixtures {
+        replay(&parse_fixture(&path), &mut coverage);
+    }
+
+    assert_eq!(
+        coverage.missing(),
+        UNCOVERED,
+        "Opcode coverage changed, these are now missing:\n{}",
+        coverage.report()
+    );
+}